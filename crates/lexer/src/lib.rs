@@ -1,50 +1,85 @@
 #[cfg(test)]
 mod test;
 
+use std::ops::Range;
 use std::str::Chars;
 
-use syntax::{SyntaxKind, Token};
+pub use syntax::{SyntaxKind, Token};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct SyntaxError {
     kind: SyntaxErrorKind,
     start: usize,
     end: usize,
 }
 
+impl SyntaxError {
+    pub fn kind(&self) -> &SyntaxErrorKind {
+        &self.kind
+    }
+
+    /// The byte range of the part of the source the error is about
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum SyntaxErrorKind {
     UnclosedStringLiteral,
+    UnclosedBlockComment,
+    /// A character that does not start any token
+    InvalidCharacter(char),
+    /// Letters right after the digits of a number, like the `px` in `10px`,
+    /// which start at `start` in the token
+    InvalidNumberSuffix {
+        start: usize,
+    },
+    /// A `\` in a string that is not followed by one of `\`, `"`, `n`, `t`,
+    /// `r` or `0`. It starts at `start` in the token.
+    InvalidEscape {
+        start: usize,
+        escaped: char,
+    },
 }
 
-/// Tokenize the entire source file into a vector
-pub fn tokenize_file(source: &str) -> (Vec<Token>, Vec<SyntaxError>) {
-    if source.is_empty() {
-        Default::default()
+impl SyntaxErrorKind {
+    /// The part of `token` that the error is about, relative to its start.
+    /// Unclosed literals point at where they are opened.
+    pub fn range_in(&self, token: &Token) -> Range<usize> {
+        match *self {
+            SyntaxErrorKind::UnclosedStringLiteral => 0..1,
+            SyntaxErrorKind::UnclosedBlockComment => 0..2,
+            SyntaxErrorKind::InvalidCharacter(_) => 0..token.len,
+            SyntaxErrorKind::InvalidNumberSuffix { start } => start..token.len,
+            SyntaxErrorKind::InvalidEscape { start, escaped } => {
+                start..start + 1 + escaped.len_utf8()
+            }
+        }
     }
+}
 
+/// Tokenize the entire source file into a vector
+pub fn tokenize_file(source: &str) -> (Vec<Token>, Vec<SyntaxError>) {
     let mut tokens = Vec::new();
     let mut errors = Vec::new();
     let mut offset = 0;
 
     for (token, error) in tokenize(source) {
-        let token_len = token.len;
-        let new_offset = offset + token_len;
-
-        tokens.push(token);
-
         if let Some(error) = error {
+            let range = error.range_in(&token);
             errors.push(SyntaxError {
                 kind: error,
-                start: offset,
-                end: new_offset,
+                start: offset + range.start,
+                end: offset + range.end,
             });
         }
 
-        offset = new_offset;
+        offset += token.len;
+        tokens.push(token);
     }
 
-    todo!()
+    (tokens, errors)
 }
 
 /// Tokenize returns an iterator over the string
@@ -56,7 +91,7 @@ pub fn tokenize(mut source: &str) -> impl Iterator<Item = (Token, Option<SyntaxE
         }
 
         let (token, err) = lex(source);
-        source = &source[token.len as usize..];
+        source = &source[token.len..];
         Some((token, err))
     })
 }
@@ -68,50 +103,92 @@ fn lex(source: &str) -> (Token, Option<SyntaxErrorKind>) {
         (
             Token {
                 kind: SyntaxKind::Whitespace,
-                len: source
-                    .find(is_not_whitespace)
-                    .unwrap_or_else(|| source.len()),
+                len: source.find(is_not_whitespace).unwrap_or(source.len()),
             },
             None,
         )
     } else if source.starts_with(is_digit) {
-        (consume_integer_or_float(source), None)
-    } else if source.starts_with(is_identifier) {
-        (
-            Token {
-                kind: SyntaxKind::Identifier,
-                len: source
-                    .find(is_not_identifier)
-                    .unwrap_or_else(|| source.len()),
-            },
-            None,
-        )
+        consume_integer_or_float(source)
+    } else if source.starts_with(is_identifier_start) {
+        (consume_identifier_or_keyword(source), None)
     } else if source.starts_with('"') {
         consume_string(source)
     } else if source.starts_with('/') {
         consume_comment_or_slash(source)
     } else {
-        let ch = source.chars().next().unwrap();
-        (
-            Token {
-                kind: match ch {
-                    '(' => SyntaxKind::LeftParen,
-                    ')' => SyntaxKind::RightParen,
-                    '{' => SyntaxKind::LeftBrace,
-                    '}' => SyntaxKind::RightBrace,
-                    '[' => SyntaxKind::LeftBracket,
-                    ']' => SyntaxKind::RightBracket,
-                    '+' => SyntaxKind::Plus,
-                    '-' => SyntaxKind::Minus,
-                    '*' => SyntaxKind::Star,
-                    '%' => SyntaxKind::Percent,
-                    _ => SyntaxKind::Error,
-                },
-                len: ch.len_utf8(),
-            },
-            None,
-        )
+        consume_punctuation(source)
+    }
+}
+
+/// Consumes a one or two character token, preferring the longer one
+fn consume_punctuation(source: &str) -> (Token, Option<SyntaxErrorKind>) {
+    let mut chars = source.chars();
+    let ch = chars.next().unwrap();
+
+    let two_char = match (ch, chars.next()) {
+        (':', Some(':')) => Some(SyntaxKind::ColonColon),
+        ('-', Some('>')) => Some(SyntaxKind::Arrow),
+        ('=', Some('>')) => Some(SyntaxKind::FatArrow),
+        ('=', Some('=')) => Some(SyntaxKind::EqualsEquals),
+        ('!', Some('=')) => Some(SyntaxKind::BangEquals),
+        ('<', Some('=')) => Some(SyntaxKind::LessThanEqual),
+        ('>', Some('=')) => Some(SyntaxKind::GreaterThanEqual),
+        ('&', Some('&')) => Some(SyntaxKind::AmpersandAmpersand),
+        ('|', Some('|')) => Some(SyntaxKind::BarBar),
+        _ => None,
+    };
+
+    if let Some(kind) = two_char {
+        return (Token { kind, len: 2 }, None);
     }
+
+    let kind = match ch {
+        '(' => SyntaxKind::LeftParen,
+        ')' => SyntaxKind::RightParen,
+        '{' => SyntaxKind::LeftBrace,
+        '}' => SyntaxKind::RightBrace,
+        '[' => SyntaxKind::LeftBracket,
+        ']' => SyntaxKind::RightBracket,
+        ',' => SyntaxKind::Comma,
+        ';' => SyntaxKind::Semicolon,
+        ':' => SyntaxKind::Colon,
+        '?' => SyntaxKind::Question,
+        '+' => SyntaxKind::Plus,
+        '-' => SyntaxKind::Minus,
+        '*' => SyntaxKind::Star,
+        '%' => SyntaxKind::Percent,
+        '=' => SyntaxKind::Equals,
+        '!' => SyntaxKind::Bang,
+        '<' => SyntaxKind::LessThan,
+        '>' => SyntaxKind::GreaterThan,
+        '|' => SyntaxKind::Bar,
+        _ => SyntaxKind::Error,
+    };
+
+    let error = (kind == SyntaxKind::Error).then_some(SyntaxErrorKind::InvalidCharacter(ch));
+
+    (
+        Token {
+            kind,
+            len: ch.len_utf8(),
+        },
+        error,
+    )
+}
+
+/// Consumes an identifier, which is a keyword or `_` if it is spelled like
+/// one
+fn consume_identifier_or_keyword(source: &str) -> Token {
+    let len = source.find(is_not_identifier).unwrap_or(source.len());
+    let text = &source[..len];
+
+    let kind = if text == "_" {
+        SyntaxKind::Underscore
+    } else {
+        SyntaxKind::from_keyword(text).unwrap_or(SyntaxKind::Identifier)
+    };
+
+    Token { kind, len }
 }
 
 /// Either consumes a comment or a single slash
@@ -119,12 +196,15 @@ fn lex(source: &str) -> (Token, Option<SyntaxErrorKind>) {
 fn consume_comment_or_slash(source: &str) -> (Token, Option<SyntaxErrorKind>) {
     let mut chars = source.chars();
 
+    if chars.clone().nth(1) == Some('*') {
+        return consume_block_comment(source);
+    }
+
     // If the second char in the iterator is not a slash then it's not a comment
     if chars.clone().nth(1) != Some('/') {
         return (
             Token {
                 kind: SyntaxKind::Slash,
-                // Is this always valid..?
                 len: 1,
             },
             None,
@@ -133,66 +213,137 @@ fn consume_comment_or_slash(source: &str) -> (Token, Option<SyntaxErrorKind>) {
 
     let initial_len = source.len();
 
+    // `///` is a doc comment, but `////` is not
+    let kind = if source.starts_with("///") && !source.starts_with("////") {
+        SyntaxKind::DocComment
+    } else {
+        SyntaxKind::Comment
+    };
+
     chars.next(); // The leading slash
     chars.next(); // The second slash
 
-    // Keep looping until we hit a newline or eof
+    // Keep looping until we hit a newline or eof, which are not part of the
+    // comment
     loop {
-        match chars.next() {
+        match chars.clone().next() {
             None | Some('\n') => {
                 return (
                     Token {
-                        kind: SyntaxKind::Comment,
+                        kind,
                         len: initial_len - chars.as_str().len(),
                     },
                     None,
                 )
             }
 
-            _ => {}
+            _ => {
+                chars.next();
+            }
         }
     }
 }
 
-fn consume_integer_or_float(source: &str) -> Token {
+/// Consumes a block comment, which can contain other block comments.
+/// An unclosed comment runs until the end of the source.
+/// This function assumes that the leading `/*` has been verified.
+fn consume_block_comment(source: &str) -> (Token, Option<SyntaxErrorKind>) {
+    let mut chars = source.chars();
+    let initial_len = source.len();
+
+    chars.next(); // The leading slash
+    chars.next(); // The star
+
+    let mut depth = 1;
+    loop {
+        match (chars.next(), chars.clone().next()) {
+            (Some('/'), Some('*')) => {
+                chars.next();
+                depth += 1;
+            }
+
+            (Some('*'), Some('/')) => {
+                chars.next();
+                depth -= 1;
+
+                if depth == 0 {
+                    return (
+                        Token {
+                            kind: SyntaxKind::Comment,
+                            len: initial_len - chars.as_str().len(),
+                        },
+                        None,
+                    );
+                }
+            }
+
+            (Some(_), _) => {}
+
+            (None, _) => {
+                return (
+                    Token {
+                        kind: SyntaxKind::Comment,
+                        len: initial_len,
+                    },
+                    Some(SyntaxErrorKind::UnclosedBlockComment),
+                )
+            }
+        }
+    }
+}
+
+fn consume_integer_or_float(source: &str) -> (Token, Option<SyntaxErrorKind>) {
     let mut chars = source.chars();
     let initial_len = source.len();
 
     // Eat all the decimal digits
     eat_decimal_digits(&mut chars);
 
-    match (chars.clone().next(), chars.clone().nth(1)) {
-        (Some('.'), Some('0'..='9')) => {
-            // Eat the leading dot.
-            chars.next();
-            eat_decimal_digits(&mut chars);
-            Token {
-                kind: SyntaxKind::Float,
-                len: initial_len - chars.as_str().len(),
-            }
+    let mut kind = SyntaxKind::Integer;
+
+    if let (Some('.'), Some('0'..='9')) = (chars.clone().next(), chars.clone().nth(1)) {
+        // Eat the leading dot.
+        chars.next();
+        eat_decimal_digits(&mut chars);
+        kind = SyntaxKind::Float;
+    }
+
+    // An `e` that is not followed by digits is not an exponent
+    let mut exponent = chars.clone();
+    if let Some('e' | 'E') = exponent.next() {
+        if let Some('+' | '-') = exponent.clone().next() {
+            exponent.next();
         }
 
-        (Some('e' | 'E'), _) => todo!("Exponents are not supported yet"),
+        if exponent.clone().next().is_some_and(is_digit) {
+            eat_decimal_digits(&mut exponent);
+            chars = exponent;
+            kind = SyntaxKind::Float;
+        }
+    }
 
-        _ => Token {
-            kind: SyntaxKind::Integer,
-            len: initial_len - chars.as_str().len(),
+    // Letters right after the number are part of it, so that `10px` is not
+    // read as `10` followed by `px`
+    let start = initial_len - chars.as_str().len();
+    let suffix_len = chars
+        .as_str()
+        .find(is_not_identifier)
+        .unwrap_or(chars.as_str().len());
+    let error = (suffix_len > 0).then_some(SyntaxErrorKind::InvalidNumberSuffix { start });
+
+    (
+        Token {
+            kind,
+            len: start + suffix_len,
         },
-    }
+        error,
+    )
 }
 
 /// Consumes decimal digits
-fn eat_decimal_digits<'a>(chars: &mut Chars<'a>) {
-    loop {
-        match chars.clone().next() {
-            Some('_') => {
-                chars.next();
-            }
-            Some('0'..='9') => {
-                chars.next();
-            }
-            _ => break,
-        }
+fn eat_decimal_digits(chars: &mut Chars) {
+    while let Some('0'..='9' | '_') = chars.clone().next() {
+        chars.next();
     }
 }
 
@@ -204,12 +355,19 @@ fn consume_string(source: &str) -> (Token, Option<SyntaxErrorKind>) {
     // Eat the initial character
     chars.next();
 
+    let mut error = None;
+
     loop {
+        let start = initial_len - chars.as_str().len();
+
         match chars.next() {
-            // An backslash escapes any subsequent character
-            Some('\\') => {
-                chars.next(); // The the subsequent char;
-            }
+            // An backslash escapes the subsequent character
+            Some('\\') => match chars.next() {
+                Some('\\' | '"' | 'n' | 't' | 'r' | '0') | None => {}
+                Some(escaped) => {
+                    error.get_or_insert(SyntaxErrorKind::InvalidEscape { start, escaped });
+                }
+            },
 
             Some('"') => {
                 return (
@@ -217,7 +375,7 @@ fn consume_string(source: &str) -> (Token, Option<SyntaxErrorKind>) {
                         kind: SyntaxKind::String,
                         len: initial_len - chars.as_str().len(),
                     },
-                    None,
+                    error,
                 );
             }
 
@@ -248,11 +406,13 @@ fn is_digit(ch: char) -> bool {
     ch.is_ascii_digit()
 }
 
+/// Identifiers follow UAX #31, with `_` and `$` allowed anywhere in them
+fn is_identifier_start(ch: char) -> bool {
+    ch == '_' || ch == '$' || unicode_ident::is_xid_start(ch)
+}
+
 fn is_identifier(ch: char) -> bool {
-    match ch {
-        'a'..='z' | 'A'..='Z' => true,
-        _ => false,
-    }
+    ch == '$' || unicode_ident::is_xid_continue(ch)
 }
 
 fn is_not_identifier(ch: char) -> bool {
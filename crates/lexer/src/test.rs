@@ -1,4 +1,6 @@
 use super::tokenize;
+use super::tokenize_file;
+use super::SyntaxError;
 use super::SyntaxErrorKind;
 use super::SyntaxKind;
 
@@ -31,12 +33,106 @@ test_valid_token!(minus_token, "-", SyntaxKind::Minus, 1);
 test_valid_token!(slash_token, "/", SyntaxKind::Slash, 1);
 test_valid_token!(start_token, "*", SyntaxKind::Star, 1);
 test_valid_token!(percent_token, "%", SyntaxKind::Percent, 1);
+test_valid_token!(equals_token, "=", SyntaxKind::Equals, 1);
+test_valid_token!(bang_token, "!", SyntaxKind::Bang, 1);
+test_valid_token!(less_than_token, "<", SyntaxKind::LessThan, 1);
+test_valid_token!(greater_than_token, ">", SyntaxKind::GreaterThan, 1);
+test_valid_token!(bar_token, "|", SyntaxKind::Bar, 1);
+test_valid_token!(comma_token, ",", SyntaxKind::Comma, 1);
+test_valid_token!(semicolon_token, ";", SyntaxKind::Semicolon, 1);
+test_valid_token!(colon_token, ":", SyntaxKind::Colon, 1);
+test_valid_token!(question_token, "?", SyntaxKind::Question, 1);
+test_valid_token!(colon_colon_token, "::", SyntaxKind::ColonColon, 2);
+test_valid_token!(arrow_token, "->", SyntaxKind::Arrow, 2);
+test_valid_token!(fat_arrow_token, "=>", SyntaxKind::FatArrow, 2);
+test_valid_token!(equals_equals_token, "==", SyntaxKind::EqualsEquals, 2);
+test_valid_token!(bang_equals_token, "!=", SyntaxKind::BangEquals, 2);
+test_valid_token!(less_than_equal_token, "<=", SyntaxKind::LessThanEqual, 2);
+test_valid_token!(
+    greater_than_equal_token,
+    ">=",
+    SyntaxKind::GreaterThanEqual,
+    2
+);
+test_valid_token!(
+    ampersand_ampersand_token,
+    "&&",
+    SyntaxKind::AmpersandAmpersand,
+    2
+);
+test_valid_token!(bar_bar_token, "||", SyntaxKind::BarBar, 2);
+test_valid_token!(ampersand_token, "&", SyntaxKind::Error, 1);
+test_valid_token!(whitespace_token, " \n\t x", SyntaxKind::Whitespace, 4);
 test_valid_token!(ident_token, "abc", SyntaxKind::Identifier, 3);
-test_valid_token!(func_token, "func", SyntaxKind::Identifier, 4);
+test_valid_token!(ident_with_digits_token, "a1_b2$", SyntaxKind::Identifier, 6);
+test_valid_token!(leading_underscore_token, "_a", SyntaxKind::Identifier, 2);
+test_valid_token!(underscore_token, "_", SyntaxKind::Underscore, 1);
+test_valid_token!(unicode_ident_token, "café+", SyntaxKind::Identifier, 5);
+test_valid_token!(cjk_ident_token, "名前", SyntaxKind::Identifier, 6);
+test_valid_token!(
+    combining_mark_ident_token,
+    "cafe\u{301}",
+    SyntaxKind::Identifier,
+    6
+);
+test_valid_token!(dollar_ident_token, "$a", SyntaxKind::Identifier, 2);
+test_valid_token!(carriage_return_token, "\r\n\t x", SyntaxKind::Whitespace, 4);
+test_valid_token!(
+    unicode_whitespace_token,
+    "\u{a0}\u{3000}x",
+    SyntaxKind::Whitespace,
+    5
+);
+test_valid_token!(true_token, "true", SyntaxKind::True, 4);
+test_valid_token!(false_token, "false", SyntaxKind::False, 5);
+test_valid_token!(func_token, "func", SyntaxKind::FuncKeyword, 4);
+test_valid_token!(comp_token, "comp", SyntaxKind::CompKeyword, 4);
+test_valid_token!(let_token, "let", SyntaxKind::LetKeyword, 3);
+test_valid_token!(state_token, "state", SyntaxKind::StateKeyword, 5);
+test_valid_token!(mut_token, "mut", SyntaxKind::MutKeyword, 3);
+test_valid_token!(struct_token, "struct", SyntaxKind::StructKeyword, 6);
+test_valid_token!(enum_token, "enum", SyntaxKind::EnumKeyword, 4);
+test_valid_token!(match_token, "match", SyntaxKind::MatchKeyword, 5);
+test_valid_token!(effect_token, "effect", SyntaxKind::EffectKeyword, 6);
+test_valid_token!(on_mount_token, "onMount", SyntaxKind::OnMountKeyword, 7);
+test_valid_token!(on_update_token, "onUpdate", SyntaxKind::OnUpdateKeyword, 8);
+test_valid_token!(
+    on_destroy_token,
+    "onDestroy",
+    SyntaxKind::OnDestroyKeyword,
+    9
+);
+test_valid_token!(keyword_prefix_token, "letter", SyntaxKind::Identifier, 6);
 test_valid_token!(integer_token, "123", SyntaxKind::Integer, 3);
 test_valid_token!(float_token, "123.123", SyntaxKind::Float, 7);
+test_valid_token!(exponent_token, "1e10", SyntaxKind::Float, 4);
+test_valid_token!(signed_exponent_token, "1.5E-3", SyntaxKind::Float, 6);
+test_valid_token!(integer_before_plus_token, "1+", SyntaxKind::Integer, 1);
+test_valid_token!(integer_before_dot_token, "1.a", SyntaxKind::Integer, 1);
 test_valid_token!(string_token, "\"abc\"", SyntaxKind::String, 5);
+test_valid_token!(
+    string_with_escapes_token,
+    r#""\\ \" \n \t \r \0""#,
+    SyntaxKind::String,
+    19
+);
 test_valid_token!(comment_token, "// a long comment", SyntaxKind::Comment, 17);
+test_valid_token!(
+    comment_before_newline_token,
+    "// a\nb",
+    SyntaxKind::Comment,
+    4
+);
+test_valid_token!(doc_comment_token, "/// docs", SyntaxKind::DocComment, 8);
+test_valid_token!(four_slashes_token, "//// docs", SyntaxKind::Comment, 9);
+test_valid_token!(block_comment_token, "/* a\nb */ c", SyntaxKind::Comment, 9);
+test_valid_token!(empty_block_comment_token, "/**/", SyntaxKind::Comment, 4);
+test_valid_token!(
+    nested_block_comment_token,
+    "/* a /* b */ c */",
+    SyntaxKind::Comment,
+    17
+);
 
 // Invalid tokens
 test_invalid_token!(
@@ -46,3 +142,123 @@ test_invalid_token!(
     4,
     SyntaxErrorKind::UnclosedStringLiteral
 );
+test_invalid_token!(
+    invalid_escape_token,
+    r#""a\q\x""#,
+    SyntaxKind::String,
+    7,
+    SyntaxErrorKind::InvalidEscape {
+        start: 2,
+        escaped: 'q'
+    }
+);
+test_invalid_token!(
+    invalid_character_token,
+    "@a",
+    SyntaxKind::Error,
+    1,
+    SyntaxErrorKind::InvalidCharacter('@')
+);
+test_invalid_token!(
+    invalid_combining_mark_start_token,
+    "\u{301}a",
+    SyntaxKind::Error,
+    2,
+    SyntaxErrorKind::InvalidCharacter('\u{301}')
+);
+test_invalid_token!(
+    invalid_multibyte_character_token,
+    "€",
+    SyntaxKind::Error,
+    3,
+    SyntaxErrorKind::InvalidCharacter('€')
+);
+test_invalid_token!(
+    invalid_integer_suffix_token,
+    "10px;",
+    SyntaxKind::Integer,
+    4,
+    SyntaxErrorKind::InvalidNumberSuffix { start: 2 }
+);
+test_invalid_token!(
+    invalid_exponent_suffix_token,
+    "1e+",
+    SyntaxKind::Integer,
+    2,
+    SyntaxErrorKind::InvalidNumberSuffix { start: 1 }
+);
+test_invalid_token!(
+    invalid_float_suffix_token,
+    "1.5e3f32",
+    SyntaxKind::Float,
+    8,
+    SyntaxErrorKind::InvalidNumberSuffix { start: 5 }
+);
+test_invalid_token!(
+    invalid_block_comment_token,
+    "/* a /* b */",
+    SyntaxKind::Comment,
+    12,
+    SyntaxErrorKind::UnclosedBlockComment
+);
+
+#[test]
+fn tokenize_empty_file() {
+    let (tokens, errors) = tokenize_file("");
+    assert!(tokens.is_empty());
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn tokenize_file_with_tokens() {
+    let (tokens, errors) = tokenize_file("let a = b::c");
+    let tokens: Vec<_> = tokens.iter().map(|token| (token.kind, token.len)).collect();
+
+    assert_eq!(
+        tokens,
+        [
+            (SyntaxKind::LetKeyword, 3),
+            (SyntaxKind::Whitespace, 1),
+            (SyntaxKind::Identifier, 1),
+            (SyntaxKind::Whitespace, 1),
+            (SyntaxKind::Equals, 1),
+            (SyntaxKind::Whitespace, 1),
+            (SyntaxKind::Identifier, 1),
+            (SyntaxKind::ColonColon, 2),
+            (SyntaxKind::Identifier, 1),
+        ]
+    );
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn tokenize_file_with_errors() {
+    let (tokens, errors) = tokenize_file("1x \"\\a\" \"bc");
+
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(
+        errors,
+        [
+            SyntaxError {
+                kind: SyntaxErrorKind::InvalidNumberSuffix { start: 1 },
+                start: 1,
+                end: 2,
+            },
+            SyntaxError {
+                kind: SyntaxErrorKind::InvalidEscape {
+                    start: 1,
+                    escaped: 'a',
+                },
+                start: 4,
+                end: 6,
+            },
+            SyntaxError {
+                kind: SyntaxErrorKind::UnclosedStringLiteral,
+                start: 8,
+                end: 9,
+            },
+        ]
+    );
+    assert_eq!(errors[2].kind(), &SyntaxErrorKind::UnclosedStringLiteral);
+    assert_eq!(errors[2].range(), 8..9);
+}
@@ -1,5 +1,5 @@
 #[repr(u16)]
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum SyntaxKind {
     #[doc(hidden)]
     Tombstone,
@@ -11,8 +11,10 @@ pub enum SyntaxKind {
     Error,
     /// ` `
     Whitespace,
-    /// `//...`
+    /// `//...` or `/* ... */`
     Comment,
+    /// `///...`
+    DocComment,
     /// `(`
     LeftParen,
     /// `)`
@@ -25,10 +27,24 @@ pub enum SyntaxKind {
     LeftBracket,
     /// `]`
     RightBracket,
+    /// `,`
+    Comma,
+    /// `;`
+    Semicolon,
+    /// `:`
+    Colon,
+    /// `::`
+    ColonColon,
+    /// `?`
+    Question,
+    /// `_`
+    Underscore,
     /// `+`
     Plus,
     /// `-`
     Minus,
+    /// `->`
+    Arrow,
     /// `/`
     Slash,
     /// `*`
@@ -37,6 +53,28 @@ pub enum SyntaxKind {
     Percent,
     /// `=`
     Equals,
+    /// `=>`
+    FatArrow,
+    /// `==`
+    EqualsEquals,
+    /// `!`
+    Bang,
+    /// `!=`
+    BangEquals,
+    /// `<`
+    LessThan,
+    /// `<=`
+    LessThanEqual,
+    /// `>`
+    GreaterThan,
+    /// `>=`
+    GreaterThanEqual,
+    /// `&&`
+    AmpersandAmpersand,
+    /// `|`
+    Bar,
+    /// `||`
+    BarBar,
     /// An identifier
     Identifier,
     /// An integer literal
@@ -46,6 +84,36 @@ pub enum SyntaxKind {
     /// A string literal
     String,
 
+    // --- Keywords ---
+    /// `true`
+    True,
+    /// `false`
+    False,
+    /// `func`
+    FuncKeyword,
+    /// `comp`
+    CompKeyword,
+    /// `let`
+    LetKeyword,
+    /// `state`
+    StateKeyword,
+    /// `mut`
+    MutKeyword,
+    /// `struct`
+    StructKeyword,
+    /// `enum`
+    EnumKeyword,
+    /// `match`
+    MatchKeyword,
+    /// `effect`
+    EffectKeyword,
+    /// `onMount`
+    OnMountKeyword,
+    /// `onUpdate`
+    OnUpdateKeyword,
+    /// `onDestroy`
+    OnDestroyKeyword,
+
     // --- Nodes ---
 
     // Only used to assert when converting to and from u16
@@ -53,6 +121,35 @@ pub enum SyntaxKind {
     __LAST,
 }
 
+impl SyntaxKind {
+    /// The keyword spelled `text`, if there is one
+    pub fn from_keyword(text: &str) -> Option<Self> {
+        let kind = match text {
+            "true" => Self::True,
+            "false" => Self::False,
+            "func" => Self::FuncKeyword,
+            "comp" => Self::CompKeyword,
+            "let" => Self::LetKeyword,
+            "state" => Self::StateKeyword,
+            "mut" => Self::MutKeyword,
+            "struct" => Self::StructKeyword,
+            "enum" => Self::EnumKeyword,
+            "match" => Self::MatchKeyword,
+            "effect" => Self::EffectKeyword,
+            "onMount" => Self::OnMountKeyword,
+            "onUpdate" => Self::OnUpdateKeyword,
+            "onDestroy" => Self::OnDestroyKeyword,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    pub fn is_trivia(self) -> bool {
+        matches!(self, Self::Whitespace | Self::Comment | Self::DocComment)
+    }
+}
+
 impl From<u16> for SyntaxKind {
     #[inline]
     fn from(d: u16) -> Self {
@@ -1,12 +1,18 @@
 use syntax::{syntax_kind::SyntaxKind, SyntaxElement, SyntaxNode, SyntaxToken};
 
-use crate::token;
+use crate::{token, Pat, Type};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Expr {
+    AssignExpr(AssignExpr),
     BinaryExpr(BinaryExpr),
+    CallExpr(CallExpr),
+    ClosureExpr(ClosureExpr),
     Literal(Literal),
+    MatchExpr(MatchExpr),
     ParenExpr(ParenExpr),
+    PathExpr(PathExpr),
+    StructExpr(StructExpr),
     UnaryExpr(UnaryExpr),
     VariableRef(VariableRef),
 }
@@ -14,9 +20,15 @@ pub enum Expr {
 impl Expr {
     pub fn cast(node: SyntaxNode) -> Option<Self> {
         let result = match node.kind() {
+            SyntaxKind::AssignExpr => Self::AssignExpr(AssignExpr(node)),
             SyntaxKind::InfixExpr => Self::BinaryExpr(BinaryExpr(node)),
+            SyntaxKind::CallExpr => Self::CallExpr(CallExpr(node)),
+            SyntaxKind::ClosureExpr => Self::ClosureExpr(ClosureExpr(node)),
             SyntaxKind::Literal => Self::Literal(Literal(node)),
+            SyntaxKind::MatchExpr => Self::MatchExpr(MatchExpr(node)),
             SyntaxKind::ParenExpr => Self::ParenExpr(ParenExpr(node)),
+            SyntaxKind::PathExpr => Self::PathExpr(PathExpr(node)),
+            SyntaxKind::StructExpr => Self::StructExpr(StructExpr(node)),
             SyntaxKind::PrefixExpr => Self::UnaryExpr(UnaryExpr(node)),
             SyntaxKind::VariableRef => Self::VariableRef(VariableRef(node)),
             _ => return None,
@@ -24,12 +36,49 @@ impl Expr {
 
         Some(result)
     }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Self::AssignExpr(expr) => &expr.0,
+            Self::BinaryExpr(expr) => &expr.0,
+            Self::CallExpr(expr) => &expr.0,
+            Self::ClosureExpr(expr) => &expr.0,
+            Self::Literal(expr) => &expr.0,
+            Self::MatchExpr(expr) => &expr.0,
+            Self::ParenExpr(expr) => &expr.0,
+            Self::PathExpr(expr) => &expr.0,
+            Self::StructExpr(expr) => &expr.0,
+            Self::UnaryExpr(expr) => &expr.0,
+            Self::VariableRef(expr) => &expr.0,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct AssignExpr(SyntaxNode);
+
+impl AssignExpr {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    pub fn target(&self) -> Option<Expr> {
+        self.0.children().find_map(Expr::cast)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        self.0.children().filter_map(Expr::cast).nth(1)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct BinaryExpr(SyntaxNode);
 
 impl BinaryExpr {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
     pub fn lhs(&self) -> Option<Expr> {
         self.0.children().find_map(Expr::cast)
     }
@@ -63,14 +112,106 @@ impl BinaryExpr {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct CallExpr(SyntaxNode);
+
+impl CallExpr {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    pub fn callee(&self) -> Option<Expr> {
+        self.0.children().find_map(Expr::cast)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        self.0.children().find_map(ArgList::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArgList(SyntaxNode);
+
+impl ArgList {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::ArgList {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Expr> {
+        self.0.children().filter_map(Expr::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClosureExpr(SyntaxNode);
+
+impl ClosureExpr {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    pub fn param_list(&self) -> Option<ParamList> {
+        self.0.children().find_map(ParamList::cast)
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        self.0.children().find_map(Expr::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParamList(SyntaxNode);
+
+impl ParamList {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::ParamList {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = Param> {
+        self.0.children().filter_map(Param::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Param(SyntaxNode);
+
+impl Param {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::Param {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        self.0.children().find_map(Type::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum LiteralKind {
     Integer(token::Integer),
     String(token::String),
     Bool(bool),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Literal(SyntaxNode);
 
 impl Literal {
@@ -103,9 +244,60 @@ impl Literal {
     pub fn first_token(&self) -> Option<SyntaxToken> {
         self.0.first_token()
     }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchExpr(SyntaxNode);
+
+impl MatchExpr {
+    pub fn scrutinee(&self) -> Option<Expr> {
+        self.0.children().find_map(Expr::cast)
+    }
+
+    pub fn arms(&self) -> impl Iterator<Item = MatchArm> {
+        self.0
+            .children()
+            .find(|node| node.kind() == SyntaxKind::MatchArmList)
+            .into_iter()
+            .flat_map(|arm_list| arm_list.children())
+            .filter_map(MatchArm::cast)
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm(SyntaxNode);
+
+impl MatchArm {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::MatchArm {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    pub fn pat(&self) -> Option<Pat> {
+        self.0.children().find_map(Pat::cast)
+    }
+
+    pub fn expr(&self) -> Option<Expr> {
+        self.0.children().find_map(Expr::cast)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParenExpr(SyntaxNode);
 
 impl ParenExpr {
@@ -114,10 +306,76 @@ impl ParenExpr {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct PathExpr(SyntaxNode);
+
+impl PathExpr {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::PathExpr {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .filter(|token| token.kind() == SyntaxKind::Ident)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructExpr(SyntaxNode);
+
+impl StructExpr {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.first_token()
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = StructExprField> {
+        self.0.children().filter_map(StructExprField::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructExprField(SyntaxNode);
+
+impl StructExprField {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::StructExprField {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.first_token()
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        self.0.children().find_map(Expr::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct UnaryExpr(SyntaxNode);
 
 impl UnaryExpr {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
     pub fn expr(&self) -> Option<Expr> {
         self.0.children().find_map(Expr::cast)
     }
@@ -126,14 +384,22 @@ impl UnaryExpr {
         self.0
             .children_with_tokens()
             .filter_map(SyntaxElement::into_token)
-            .find(|token| token.kind() == SyntaxKind::Minus)
+            .find(|token| matches!(token.kind(), SyntaxKind::Minus | SyntaxKind::Bang))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VariableRef(SyntaxNode);
 
 impl VariableRef {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::VariableRef {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
     pub fn name(&self) -> Option<SyntaxToken> {
         self.0.first_token()
     }
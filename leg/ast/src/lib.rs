@@ -1,14 +1,18 @@
 mod expr;
+mod pat;
 mod stmt;
 mod token;
+mod ty;
 
 pub use expr::*;
+pub use pat::*;
 pub use stmt::*;
 pub use token::*;
+pub use ty::*;
 
-use syntax::{syntax_kind::SyntaxKind, SyntaxNode};
+use syntax::{syntax_kind::SyntaxKind, SyntaxElement, SyntaxNode};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Root(SyntaxNode);
 
 impl Root {
@@ -24,3 +28,23 @@ impl Root {
         self.0.children().filter_map(Stmt::cast)
     }
 }
+
+/// The text of the `///` comments before the item `node`, which the parser
+/// puts at its start, with one line per comment
+fn docs(node: &SyntaxNode) -> Option<std::string::String> {
+    let lines: Vec<_> = node
+        .children_with_tokens()
+        .map_while(SyntaxElement::into_token)
+        .take_while(|token| token.kind().is_trivia())
+        .filter(|token| token.kind() == SyntaxKind::DocComment)
+        .map(|token| {
+            let text = &token.text()["///".len()..];
+            text.strip_prefix(' ')
+                .unwrap_or(text)
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
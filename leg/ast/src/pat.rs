@@ -0,0 +1,57 @@
+use syntax::{syntax_kind::SyntaxKind, SyntaxElement, SyntaxNode, SyntaxToken};
+
+#[derive(Debug, Clone)]
+pub enum Pat {
+    IdentPat(IdentPat),
+    VariantPat(VariantPat),
+    WildcardPat(WildcardPat),
+}
+
+impl Pat {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        let result = match node.kind() {
+            SyntaxKind::IdentPat => Self::IdentPat(IdentPat(node)),
+            SyntaxKind::VariantPat => Self::VariantPat(VariantPat(node)),
+            SyntaxKind::WildcardPat => Self::WildcardPat(WildcardPat(node)),
+            _ => return None,
+        };
+
+        Some(result)
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Self::IdentPat(pat) => &pat.0,
+            Self::VariantPat(pat) => &pat.0,
+            Self::WildcardPat(pat) => &pat.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IdentPat(SyntaxNode);
+
+impl IdentPat {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.first_token()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VariantPat(SyntaxNode);
+
+impl VariantPat {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .filter(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = Pat> {
+        self.0.children().filter_map(Pat::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WildcardPat(SyntaxNode);
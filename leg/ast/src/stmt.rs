@@ -1,9 +1,13 @@
-use crate::Expr;
+use crate::{Expr, ParamList, RetType, TupleType, Type, VariableRef};
 use syntax::{syntax_kind::SyntaxKind, SyntaxElement, SyntaxNode, SyntaxToken};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Stmt {
     VariableDef(VariableDef),
+    StateDef(StateDef),
+    FunctionDecl(FunctionDecl),
+    StructDecl(StructDecl),
+    EnumDecl(EnumDecl),
     Expr(Expr),
 }
 
@@ -11,6 +15,10 @@ impl Stmt {
     pub fn cast(node: SyntaxNode) -> Option<Self> {
         let result = match node.kind() {
             SyntaxKind::VariableDef => Self::VariableDef(VariableDef(node)),
+            SyntaxKind::StateDef => Self::StateDef(StateDef(node)),
+            SyntaxKind::FunctionDecl => Self::FunctionDecl(FunctionDecl(node)),
+            SyntaxKind::StructDecl => Self::StructDecl(StructDecl(node)),
+            SyntaxKind::EnumDecl => Self::EnumDecl(EnumDecl(node)),
             _ => Self::Expr(Expr::cast(node)?),
         };
 
@@ -18,10 +26,14 @@ impl Stmt {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VariableDef(SyntaxNode);
 
 impl VariableDef {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
     pub fn name(&self) -> Option<SyntaxToken> {
         self.0
             .children_with_tokens()
@@ -29,7 +41,197 @@ impl VariableDef {
             .find(|token| token.kind() == SyntaxKind::Ident)
     }
 
+    /// The `///` comments before the declaration
+    pub fn docs(&self) -> Option<String> {
+        crate::docs(&self.0)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        self.0.children().find_map(Type::cast)
+    }
+
     pub fn value(&self) -> Option<Expr> {
         self.0.children().find_map(Expr::cast)
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct StateDef(SyntaxNode);
+
+impl StateDef {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    /// The `///` comments before the declaration
+    pub fn docs(&self) -> Option<String> {
+        crate::docs(&self.0)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        self.0.children().find_map(Type::cast)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        self.0.children().find_map(Expr::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionDecl(SyntaxNode);
+
+impl FunctionDecl {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.children().find_map(VariableRef::cast)?.name()
+    }
+
+    /// The `///` comments before the declaration
+    pub fn docs(&self) -> Option<String> {
+        crate::docs(&self.0)
+    }
+
+    pub fn param_list(&self) -> Option<ParamList> {
+        self.0.children().find_map(ParamList::cast)
+    }
+
+    pub fn ret_type(&self) -> Option<RetType> {
+        self.0.children().find_map(RetType::cast)
+    }
+
+    pub fn body(&self) -> Option<BlockStmt> {
+        self.0.children().find_map(BlockStmt::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructDecl(SyntaxNode);
+
+impl StructDecl {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    /// The `///` comments before the declaration
+    pub fn docs(&self) -> Option<String> {
+        crate::docs(&self.0)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = FieldDecl> {
+        self.0.children().filter_map(FieldDecl::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldDecl(SyntaxNode);
+
+impl FieldDecl {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::FieldDecl {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    /// The `///` comments before the declaration
+    pub fn docs(&self) -> Option<String> {
+        crate::docs(&self.0)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        self.0.children().find_map(Type::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumDecl(SyntaxNode);
+
+impl EnumDecl {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::EnumDecl {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    /// The `///` comments before the declaration
+    pub fn docs(&self) -> Option<String> {
+        crate::docs(&self.0)
+    }
+
+    pub fn variants(&self) -> impl Iterator<Item = VariantDecl> {
+        self.0.children().filter_map(VariantDecl::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VariantDecl(SyntaxNode);
+
+impl VariantDecl {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::VariantDecl {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    /// The `///` comments before the declaration
+    pub fn docs(&self) -> Option<String> {
+        crate::docs(&self.0)
+    }
+
+    /// The types of the variant's fields, or `None` for a unit variant
+    pub fn fields(&self) -> Option<TupleType> {
+        self.0.children().find_map(TupleType::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockStmt(SyntaxNode);
+
+impl BlockStmt {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::BlockStmt {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt> {
+        self.0.children().filter_map(Stmt::cast)
+    }
+}
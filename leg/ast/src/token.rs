@@ -1,4 +1,5 @@
 use syntax::{syntax_kind::SyntaxKind, SyntaxToken};
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Integer(SyntaxToken);
@@ -12,8 +13,17 @@ impl Integer {
         }
     }
 
+    /// The value of the literal, which can separate its digits with `_`. An
+    /// invalid suffix, which the parser reports, is left out.
     pub fn parse(&self) -> Option<u64> {
-        self.0.text().parse().ok()
+        self.0
+            .text()
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '_')
+            .filter(|c| *c != '_')
+            .collect::<std::string::String>()
+            .parse()
+            .ok()
     }
 }
 
@@ -33,3 +43,10 @@ impl String {
         self.0.text()
     }
 }
+
+/// The name the identifier `ident` stands for. Names are compared in Unicode
+/// normalization form C, so that an `é` is the same name whether it is
+/// written as one character or as an `e` followed by a combining accent.
+pub fn name(ident: &SyntaxToken) -> std::string::String {
+    ident.text().nfc().collect()
+}
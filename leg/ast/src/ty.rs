@@ -0,0 +1,118 @@
+use syntax::{syntax_kind::SyntaxKind, SyntaxElement, SyntaxNode, SyntaxToken};
+
+#[derive(Debug, Clone)]
+pub enum Type {
+    ArrayType(ArrayType),
+    FnType(FnType),
+    OptionalType(OptionalType),
+    PathType(PathType),
+    TupleType(TupleType),
+}
+
+impl Type {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        let result = match node.kind() {
+            SyntaxKind::ArrayType => Self::ArrayType(ArrayType(node)),
+            SyntaxKind::FnType => Self::FnType(FnType(node)),
+            SyntaxKind::OptionalType => Self::OptionalType(OptionalType(node)),
+            SyntaxKind::PathType => Self::PathType(PathType(node)),
+            SyntaxKind::TupleType => Self::TupleType(TupleType(node)),
+            _ => return None,
+        };
+
+        Some(result)
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Self::ArrayType(ty) => &ty.0,
+            Self::FnType(ty) => &ty.0,
+            Self::OptionalType(ty) => &ty.0,
+            Self::PathType(ty) => &ty.0,
+            Self::TupleType(ty) => &ty.0,
+        }
+    }
+}
+
+/// `[T]`
+#[derive(Debug, Clone)]
+pub struct ArrayType(SyntaxNode);
+
+impl ArrayType {
+    pub fn elem(&self) -> Option<Type> {
+        self.0.children().find_map(Type::cast)
+    }
+}
+
+/// `(A, B) -> R`
+#[derive(Debug, Clone)]
+pub struct FnType(SyntaxNode);
+
+impl FnType {
+    pub fn params(&self) -> impl Iterator<Item = Type> {
+        self.0.children().filter_map(Type::cast)
+    }
+
+    pub fn ret_type(&self) -> Option<RetType> {
+        self.0.children().find_map(RetType::cast)
+    }
+}
+
+/// `T?`
+#[derive(Debug, Clone)]
+pub struct OptionalType(SyntaxNode);
+
+impl OptionalType {
+    pub fn inner(&self) -> Option<Type> {
+        self.0.children().find_map(Type::cast)
+    }
+}
+
+/// `i32`, `String`
+#[derive(Debug, Clone)]
+pub struct PathType(SyntaxNode);
+
+impl PathType {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+}
+
+/// `(A, B)`, `()`
+#[derive(Debug, Clone)]
+pub struct TupleType(SyntaxNode);
+
+impl TupleType {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::TupleType {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = Type> {
+        self.0.children().filter_map(Type::cast)
+    }
+}
+
+/// `-> T`
+#[derive(Debug, Clone)]
+pub struct RetType(SyntaxNode);
+
+impl RetType {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::RetType {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        self.0.children().find_map(Type::cast)
+    }
+}
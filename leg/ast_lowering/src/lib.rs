@@ -1,6 +1,15 @@
+mod scope;
+mod source_map;
+
+use diagnostics::{Code, Diagnostic, Label};
 use hir::*;
-use la_arena::Arena;
-use syntax::syntax_kind::SyntaxKind;
+use la_arena::{Arena, Idx};
+use scope::Scopes;
+use smol_str::SmolStr;
+use syntax::{syntax_kind::SyntaxKind, SyntaxNode, SyntaxToken};
+use text_size::TextRange;
+
+pub use source_map::SourceMap;
 
 /// Lowers the given AST into HIR
 pub fn lower_root(root: ast::Root) -> (Database, Vec<Stmt>) {
@@ -13,9 +22,15 @@ pub fn lower_root(root: ast::Root) -> (Database, Vec<Stmt>) {
     (database, stmts)
 }
 
-#[derive(Default, Debug, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct Database {
     exprs: Arena<Expr>,
+    source_map: SourceMap,
+    type_refs: Arena<TypeRef>,
+    structs: Arena<Struct>,
+    enums: Arena<Enum>,
+    scopes: Scopes,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl Database {
@@ -23,24 +38,268 @@ impl Database {
         &self.exprs
     }
 
+    /// For passes that rewrite expressions in place. Rewritten expressions
+    /// keep the source range of the expression they replace.
+    pub fn exprs_mut(&mut self) -> &mut Arena<Expr> {
+        &mut self.exprs
+    }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// Where `expr` is in the source, without trailing trivia. Expressions
+    /// that were not lowered from the source have no range.
+    pub fn expr_range(&self, expr: Idx<Expr>) -> Option<TextRange> {
+        self.source_map.expr_range(expr)
+    }
+
+    /// Where the `let` or `state` definition whose value is `value` is in
+    /// the source, from its keyword to the end of the value
+    pub fn def_range(&self, value: Idx<Expr>) -> Option<TextRange> {
+        self.source_map.def_range(value)
+    }
+
+    pub fn type_refs(&self) -> &Arena<TypeRef> {
+        &self.type_refs
+    }
+
+    pub fn structs(&self) -> &Arena<Struct> {
+        &self.structs
+    }
+
+    pub fn enums(&self) -> &Arena<Enum> {
+        &self.enums
+    }
+
+    /// The problems found while lowering, in the order they were found
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Finds the enum declared with the given name
+    pub fn find_enum(&self, name: &str) -> Option<&Enum> {
+        self.enums
+            .iter()
+            .map(|(_, enum_def)| enum_def)
+            .find(|enum_def| enum_def.name == name)
+    }
+
+    /// Finds the struct declared with the given name
+    pub fn find_struct(&self, name: &str) -> Option<&Struct> {
+        self.structs
+            .iter()
+            .map(|(_, struct_def)| struct_def)
+            .find(|struct_def| struct_def.name == name)
+    }
+
+    /// Makes `name` visible to the statements lowered after this, as if it
+    /// was defined by a statement lowered before them
+    pub fn define(&mut self, name: SmolStr, kind: BindingKind) {
+        self.scopes.define(name, kind);
+    }
+
     pub fn lower_stmt(&mut self, stmt: ast::Stmt) -> Option<Stmt> {
         let result = match stmt {
-            ast::Stmt::VariableDef(variable_def) => Stmt::VariableDef {
-                name: variable_def.name()?.text().into(),
-                value: self.lower_expr(variable_def.value()),
-            },
-            ast::Stmt::Expr(expr) => Stmt::Expr(self.lower_expr(Some(expr))),
+            ast::Stmt::VariableDef(variable_def) => {
+                let name: SmolStr = ast::name(&variable_def.name()?).into();
+                let ty = variable_def.ty().map(|ty| self.lower_type(Some(ty)));
+                let value = self.lower_expr(variable_def.value());
+                let value = self.alloc_expr(value, variable_def.value(), variable_def.syntax());
+                self.source_map.insert_def(value, variable_def.syntax());
+                self.scopes.define(name.clone(), BindingKind::Variable);
+
+                Stmt::VariableDef {
+                    name,
+                    ty,
+                    value,
+                    docs: variable_def.docs(),
+                }
+            }
+            ast::Stmt::StateDef(state_def) => {
+                let name: SmolStr = ast::name(&state_def.name()?).into();
+                let ty = state_def.ty().map(|ty| self.lower_type(Some(ty)));
+                let value = self.lower_expr(state_def.value());
+                let value = self.alloc_expr(value, state_def.value(), state_def.syntax());
+                self.source_map.insert_def(value, state_def.syntax());
+                self.scopes.define(name.clone(), BindingKind::State);
+
+                Stmt::StateDef {
+                    name,
+                    ty,
+                    value,
+                    docs: state_def.docs(),
+                }
+            }
+            ast::Stmt::FunctionDecl(function_decl) => self.lower_function_decl(function_decl)?,
+            ast::Stmt::StructDecl(struct_decl) => self.lower_struct_decl(struct_decl)?,
+            ast::Stmt::EnumDecl(enum_decl) => self.lower_enum_decl(enum_decl)?,
+            ast::Stmt::Expr(expr) => {
+                let value = self.lower_expr(Some(expr.clone()));
+                Stmt::Expr(self.alloc_expr(value, Some(expr.clone()), expr.syntax()))
+            }
         };
 
         Some(result)
     }
 
+    fn lower_function_decl(&mut self, function_decl: ast::FunctionDecl) -> Option<Stmt> {
+        let name: SmolStr = ast::name(&function_decl.name()?).into();
+
+        // The function is visible inside of its own body to allow recursion
+        self.scopes.define(name.clone(), BindingKind::Function);
+
+        let params = self.lower_param_list(function_decl.param_list());
+        let ret_ty = function_decl
+            .ret_type()
+            .map(|ret_type| self.lower_type(ret_type.ty()));
+
+        self.scopes.push_frame();
+        for param in &params {
+            self.scopes.define(param.name.clone(), BindingKind::Param);
+        }
+
+        let body = function_decl
+            .body()
+            .into_iter()
+            .flat_map(|block| block.stmts())
+            .filter_map(|stmt| self.lower_stmt(stmt))
+            .collect();
+
+        self.scopes.pop_frame();
+
+        Some(Stmt::FunctionDef {
+            name,
+            params,
+            ret_ty,
+            body,
+            docs: function_decl.docs(),
+        })
+    }
+
+    fn lower_struct_decl(&mut self, struct_decl: ast::StructDecl) -> Option<Stmt> {
+        let name: SmolStr = ast::name(&struct_decl.name()?).into();
+        let fields = struct_decl
+            .fields()
+            .filter_map(|field| {
+                Some(Field {
+                    name: ast::name(&field.name()?).into(),
+                    ty: self.lower_type(field.ty()),
+                    docs: field.docs(),
+                })
+            })
+            .collect();
+
+        Some(Stmt::StructDef(self.structs.alloc(Struct {
+            name,
+            fields,
+            docs: struct_decl.docs(),
+        })))
+    }
+
+    fn lower_enum_decl(&mut self, enum_decl: ast::EnumDecl) -> Option<Stmt> {
+        let name: SmolStr = ast::name(&enum_decl.name()?).into();
+        let variants = enum_decl
+            .variants()
+            .filter_map(|variant| {
+                let name = ast::name(&variant.name()?).into();
+                let fields = variant
+                    .fields()
+                    .into_iter()
+                    .flat_map(|fields| fields.fields())
+                    .map(|field| self.lower_type(Some(field)))
+                    .collect();
+
+                Some(Variant {
+                    name,
+                    fields,
+                    docs: variant.docs(),
+                })
+            })
+            .collect();
+
+        Some(Stmt::EnumDef(self.enums.alloc(Enum {
+            name,
+            variants,
+            docs: enum_decl.docs(),
+        })))
+    }
+
+    fn lower_param_list(&mut self, param_list: Option<ast::ParamList>) -> Vec<Param> {
+        param_list
+            .into_iter()
+            .flat_map(|param_list| param_list.params())
+            .filter_map(|param| {
+                Some(Param {
+                    name: ast::name(&param.name()?).into(),
+                    ty: param.ty().map(|ty| self.lower_type(Some(ty))),
+                })
+            })
+            .collect()
+    }
+
+    fn lower_type(&mut self, ty: Option<ast::Type>) -> Idx<TypeRef> {
+        let type_ref = match ty {
+            Some(ast::Type::ArrayType(ty)) => TypeRef::Array {
+                elem: self.lower_type(ty.elem()),
+            },
+            Some(ast::Type::FnType(ty)) => TypeRef::Fn {
+                params: ty
+                    .params()
+                    .map(|param| self.lower_type(Some(param)))
+                    .collect(),
+                ret: self.lower_type(ty.ret_type().and_then(|ret_type| ret_type.ty())),
+            },
+            Some(ast::Type::OptionalType(ty)) => TypeRef::Optional {
+                inner: self.lower_type(ty.inner()),
+            },
+            Some(ast::Type::PathType(ty)) => match ty.name() {
+                Some(name) => TypeRef::Path {
+                    name: ast::name(&name).into(),
+                },
+                None => TypeRef::Missing,
+            },
+            Some(ast::Type::TupleType(ty)) => TypeRef::Tuple {
+                fields: ty
+                    .fields()
+                    .map(|field| self.lower_type(Some(field)))
+                    .collect(),
+            },
+            None => TypeRef::Missing,
+        };
+
+        self.type_refs.alloc(type_ref)
+    }
+
+    /// Allocates `expr`, which was lowered from `syntax`. If `syntax` is
+    /// missing, `expr` is a placeholder for it and points at `parent`.
+    fn alloc_expr(
+        &mut self,
+        expr: Expr,
+        syntax: Option<ast::Expr>,
+        parent: &SyntaxNode,
+    ) -> Idx<Expr> {
+        let idx = self.exprs.alloc(expr);
+        match syntax {
+            Some(syntax) => self.source_map.insert(idx, syntax.syntax(), false),
+            None => self.source_map.insert(idx, parent, true),
+        }
+
+        idx
+    }
+
     fn lower_expr(&mut self, expr: Option<ast::Expr>) -> Expr {
         if let Some(expr) = expr {
             match expr {
+                ast::Expr::AssignExpr(expr) => self.lower_assign_expr(expr),
                 ast::Expr::BinaryExpr(expr) => self.lower_binary_expr(expr),
+                ast::Expr::CallExpr(expr) => self.lower_call_expr(expr),
+                ast::Expr::ClosureExpr(expr) => self.lower_closure_expr(expr),
                 ast::Expr::Literal(expr) => self.lower_literal(expr),
+                ast::Expr::MatchExpr(expr) => self.lower_match_expr(expr),
                 ast::Expr::ParenExpr(expr) => self.lower_paren_expr(expr),
+                ast::Expr::PathExpr(expr) => self.lower_variant(expr, Vec::new()),
+                ast::Expr::StructExpr(expr) => self.lower_struct_expr(expr),
                 ast::Expr::UnaryExpr(expr) => self.lower_unary_expr(expr),
                 ast::Expr::VariableRef(expr) => self.lower_variable_ref(expr),
             }
@@ -49,8 +308,23 @@ impl Database {
         }
     }
 
+    fn lower_assign_expr(&mut self, expr: ast::AssignExpr) -> Expr {
+        let target = self.lower_expr(expr.target());
+        let value = self.lower_expr(expr.value());
+
+        Expr::Assign {
+            target: self.alloc_expr(target, expr.target(), expr.syntax()),
+            value: self.alloc_expr(value, expr.value(), expr.syntax()),
+        }
+    }
+
     fn lower_binary_expr(&mut self, expr: ast::BinaryExpr) -> Expr {
-        let op = match expr.op().unwrap().kind() {
+        let op = match expr.op() {
+            Some(op) => op,
+            None => return Expr::Missing,
+        };
+
+        let op = match op.kind() {
             SyntaxKind::Plus => BinaryOp::Add,
             SyntaxKind::Minus => BinaryOp::Sub,
             SyntaxKind::Slash => BinaryOp::Div,
@@ -64,32 +338,145 @@ impl Database {
             SyntaxKind::GreaterThanEqual => BinaryOp::Ge,
             SyntaxKind::BangEquals => BinaryOp::Ne,
             SyntaxKind::EqualsEquals => BinaryOp::Eq,
-            kind => {
-                eprintln!("Does not know how to handle: {:?}", kind);
-                unreachable!()
-            }
+            _ => unreachable!("BinaryExpr::op only returns binary operators"),
         };
 
         let lhs = self.lower_expr(expr.lhs());
         let rhs = self.lower_expr(expr.rhs());
 
         Expr::Binary {
-            lhs: self.exprs.alloc(lhs),
+            lhs: self.alloc_expr(lhs, expr.lhs(), expr.syntax()),
             op,
-            rhs: self.exprs.alloc(rhs),
+            rhs: self.alloc_expr(rhs, expr.rhs(), expr.syntax()),
+        }
+    }
+
+    fn lower_call_expr(&mut self, expr: ast::CallExpr) -> Expr {
+        // `Status::Loaded(user)` constructs a variant rather than calling a function
+        if let Some(ast::Expr::PathExpr(path)) = expr.callee() {
+            let args = self.lower_arg_list(expr.arg_list());
+            return self.lower_variant(path, args);
+        }
+
+        let callee = self.lower_expr(expr.callee());
+        let callee = self.alloc_expr(callee, expr.callee(), expr.syntax());
+        let args = self.lower_arg_list(expr.arg_list());
+
+        Expr::Call { callee, args }
+    }
+
+    fn lower_arg_list(&mut self, arg_list: Option<ast::ArgList>) -> Vec<Idx<Expr>> {
+        arg_list
+            .into_iter()
+            .flat_map(|arg_list| arg_list.args())
+            .map(|arg| {
+                let lowered = self.lower_expr(Some(arg.clone()));
+                self.alloc_expr(lowered, Some(arg.clone()), arg.syntax())
+            })
+            .collect()
+    }
+
+    fn lower_variant(&mut self, path: ast::PathExpr, args: Vec<Idx<Expr>>) -> Expr {
+        match path.segments().collect::<Vec<_>>().as_slice() {
+            [enum_name, variant] => Expr::Variant {
+                enum_name: ast::name(enum_name).into(),
+                variant: ast::name(variant).into(),
+                args,
+            },
+            segments => {
+                self.report_invalid_path(segments);
+                Expr::Missing
+            }
+        }
+    }
+
+    /// Paths can only name the variant of an enum. Paths with fewer than
+    /// two segments are incomplete and have already been reported by the
+    /// parser.
+    fn report_invalid_path(&mut self, segments: &[SyntaxToken]) {
+        if let [first, .., last] = segments {
+            if segments.len() > 2 {
+                let range = TextRange::new(first.text_range().start(), last.text_range().end());
+                self.diagnostics.push(
+                    Diagnostic::error(Code::INVALID_PATH, "paths can only name an enum variant")
+                        .with_label(Label::primary(range, "expected `Enum::Variant`")),
+                );
+            }
+        }
+    }
+
+    fn lower_struct_expr(&mut self, expr: ast::StructExpr) -> Expr {
+        let name = match expr.name() {
+            Some(name) => ast::name(&name).into(),
+            None => return Expr::Missing,
+        };
+        let fields = expr
+            .fields()
+            .filter_map(|field| {
+                let name = ast::name(&field.name()?).into();
+                let value = self.lower_expr(field.value());
+                Some((name, self.alloc_expr(value, field.value(), field.syntax())))
+            })
+            .collect();
+
+        Expr::Record { name, fields }
+    }
+
+    fn lower_match_expr(&mut self, expr: ast::MatchExpr) -> Expr {
+        let scrutinee = self.lower_expr(expr.scrutinee());
+        let scrutinee = self.alloc_expr(scrutinee, expr.scrutinee(), expr.syntax());
+
+        let arms = expr
+            .arms()
+            .map(|arm| {
+                let pat = self.lower_pat(arm.pat());
+
+                self.scopes.push_frame();
+                for name in pat.bindings() {
+                    self.scopes.define(name, BindingKind::Variable);
+                }
+                let expr = self.lower_expr(arm.expr());
+                self.scopes.pop_frame();
+
+                MatchArm {
+                    pat,
+                    expr: self.alloc_expr(expr, arm.expr(), arm.syntax()),
+                }
+            })
+            .collect();
+
+        Expr::Match { scrutinee, arms }
+    }
+
+    fn lower_closure_expr(&mut self, expr: ast::ClosureExpr) -> Expr {
+        let params = self.lower_param_list(expr.param_list());
+
+        self.scopes.enter_closure();
+        for param in &params {
+            self.scopes.define(param.name.clone(), BindingKind::Param);
+        }
+
+        let body = self.lower_expr(expr.body());
+        let captures = self.scopes.exit_closure();
+
+        Expr::Closure {
+            params,
+            body: self.alloc_expr(body, expr.body(), expr.syntax()),
+            captures,
         }
     }
 
     fn lower_unary_expr(&mut self, expr: ast::UnaryExpr) -> Expr {
-        let op = match expr.op().unwrap().kind() {
-            SyntaxKind::Minus => UnaryOp::Neg,
-            _ => unreachable!(),
+        let op = match expr.op().map(|op| op.kind()) {
+            Some(SyntaxKind::Minus) => UnaryOp::Neg,
+            Some(SyntaxKind::Bang) => UnaryOp::Not,
+            _ => return Expr::Missing,
         };
 
         let inner_expr = self.lower_expr(expr.expr());
 
         Expr::Unary {
-            expr: self.exprs.alloc(inner_expr),
+            expr: self.alloc_expr(inner_expr, expr.expr(), expr.syntax()),
             op,
         }
     }
@@ -111,9 +498,41 @@ impl Database {
     }
 
     fn lower_variable_ref(&mut self, expr: ast::VariableRef) -> Expr {
-        let name = expr.name().unwrap().text().into();
+        let name: SmolStr = match expr.name() {
+            Some(name) => ast::name(&name).into(),
+            None => return Expr::Missing,
+        };
+        self.scopes.resolve(&name);
         Expr::VariableRef { var: name }
     }
+
+    fn lower_pat(&mut self, pat: Option<ast::Pat>) -> Pat {
+        match pat {
+            Some(ast::Pat::IdentPat(pat)) => match pat.name() {
+                Some(name) => Pat::Bind {
+                    name: ast::name(&name).into(),
+                },
+                None => Pat::Wildcard,
+            },
+            Some(ast::Pat::VariantPat(pat)) => {
+                match pat.segments().collect::<Vec<_>>().as_slice() {
+                    [enum_name, variant] => Pat::Variant {
+                        enum_name: ast::name(enum_name).into(),
+                        variant: ast::name(variant).into(),
+                        fields: pat
+                            .fields()
+                            .map(|field| self.lower_pat(Some(field)))
+                            .collect(),
+                    },
+                    segments => {
+                        self.report_invalid_path(segments);
+                        Pat::Wildcard
+                    }
+                }
+            }
+            Some(ast::Pat::WildcardPat(_)) | None => Pat::Wildcard,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -125,12 +544,33 @@ mod tests {
         ast::Root::cast(parser::parse(input).syntax()).unwrap()
     }
 
-    fn check_stmt(input: &str, expected_hir: Stmt) {
+    fn check_stmt(input: &str, expected_hir: Stmt, expected_exprs: Arena<Expr>) {
         let root = parse(input);
         let ast = root.stmts().next().unwrap();
-        let hir = Database::default().lower_stmt(ast).unwrap();
+        let mut database = Database::default();
+        let hir = database.lower_stmt(ast).unwrap();
 
         assert_eq!(hir, expected_hir);
+        assert_eq!(database.exprs, expected_exprs);
+    }
+
+    /// Lowers every statement and returns the expression of the last one
+    fn lower_last_expr(input: &str) -> (Database, Expr) {
+        let (database, mut stmts) = lower_root(parse(input));
+        let expr = match stmts.pop().unwrap() {
+            Stmt::Expr(expr) | Stmt::VariableDef { value: expr, .. } => {
+                database.exprs()[expr].clone()
+            }
+            _ => unreachable!(),
+        };
+        (database, expr)
+    }
+
+    fn closure_captures(input: &str) -> Vec<Capture> {
+        match lower_last_expr(input).1 {
+            Expr::Closure { captures, .. } => captures,
+            _ => unreachable!(),
+        }
     }
 
     fn check_expr(input: &str, expected_hir: Expr, expected_database: Database) {
@@ -144,17 +584,26 @@ mod tests {
         let hir = database.lower_expr(Some(ast));
 
         assert_eq!(hir, expected_hir);
+
+        // The source map is covered by its own tests
+        database.source_map = SourceMap::default();
         assert_eq!(database, expected_database);
     }
 
     #[test]
     fn lower_variable_def() {
+        let mut exprs = Arena::new();
+        let value = exprs.alloc(Expr::VariableRef { var: "bar".into() });
+
         check_stmt(
             "let foo = bar",
             Stmt::VariableDef {
                 name: "foo".into(),
-                value: Expr::VariableRef { var: "bar".into() },
+                ty: None,
+                value,
+                docs: None,
             },
+            exprs,
         )
     }
 
@@ -167,21 +616,27 @@ mod tests {
 
     #[test]
     fn lower_variable_def_without_value() {
+        let mut exprs = Arena::new();
+        let value = exprs.alloc(Expr::Missing);
+
         check_stmt(
             "let a =",
             Stmt::VariableDef {
                 name: "a".into(),
-                value: Expr::Missing,
+                ty: None,
+                value,
+                docs: None,
             },
+            exprs,
         )
     }
 
     #[test]
     fn lower_expr_stmt() {
-        check_stmt(
-            "123",
-            Stmt::Expr(Expr::Literal(Literal::Integer { value: Some(123) })),
-        )
+        let mut exprs = Arena::new();
+        let expr = exprs.alloc(Expr::Literal(Literal::Integer { value: Some(123) }));
+
+        check_stmt("123", Stmt::Expr(expr), exprs)
     }
 
     #[test]
@@ -197,7 +652,10 @@ mod tests {
                 op: BinaryOp::Add,
                 rhs,
             },
-            Database { exprs },
+            Database {
+                exprs,
+                ..Database::default()
+            },
         );
     }
 
@@ -214,7 +672,10 @@ mod tests {
                 op: BinaryOp::Add,
                 rhs,
             },
-            Database { exprs },
+            Database {
+                exprs,
+                ..Database::default()
+            },
         );
     }
 
@@ -247,7 +708,28 @@ mod tests {
                 op: UnaryOp::Neg,
                 expr,
             },
-            Database { exprs },
+            Database {
+                exprs,
+                ..Database::default()
+            },
+        )
+    }
+
+    #[test]
+    fn lower_not_expr() {
+        let mut exprs = Arena::new();
+        let expr = exprs.alloc(Expr::Literal(Literal::Bool { value: true }));
+
+        check_expr(
+            "!true",
+            Expr::Unary {
+                op: UnaryOp::Not,
+                expr,
+            },
+            Database {
+                exprs,
+                ..Database::default()
+            },
         )
     }
 
@@ -262,7 +744,10 @@ mod tests {
                 op: UnaryOp::Neg,
                 expr,
             },
-            Database { exprs },
+            Database {
+                exprs,
+                ..Database::default()
+            },
         );
     }
 
@@ -274,4 +759,637 @@ mod tests {
             Database::default(),
         )
     }
+
+    #[test]
+    fn lower_state_def() {
+        let mut exprs = Arena::new();
+        let value = exprs.alloc(Expr::Literal(Literal::Integer { value: Some(0) }));
+
+        check_stmt(
+            "state count = 0",
+            Stmt::StateDef {
+                name: "count".into(),
+                ty: None,
+                value,
+                docs: None,
+            },
+            exprs,
+        )
+    }
+
+    #[test]
+    fn lower_assign_expr() {
+        let mut exprs = Arena::new();
+        let target = exprs.alloc(Expr::VariableRef { var: "a".into() });
+        let value = exprs.alloc(Expr::Literal(Literal::Integer { value: Some(1) }));
+
+        check_expr(
+            "a = 1",
+            Expr::Assign { target, value },
+            Database {
+                exprs,
+                ..Database::default()
+            },
+        );
+    }
+
+    #[test]
+    fn lower_call_expr() {
+        let mut exprs = Arena::new();
+        let callee = exprs.alloc(Expr::VariableRef { var: "add".into() });
+        let one = exprs.alloc(Expr::Literal(Literal::Integer { value: Some(1) }));
+        let two = exprs.alloc(Expr::Literal(Literal::Integer { value: Some(2) }));
+
+        check_expr(
+            "add(1, 2)",
+            Expr::Call {
+                callee,
+                args: vec![one, two],
+            },
+            Database {
+                exprs,
+                ..Database::default()
+            },
+        );
+    }
+
+    #[test]
+    fn lower_closure_expr() {
+        let mut exprs = Arena::new();
+        let lhs = exprs.alloc(Expr::VariableRef { var: "x".into() });
+        let rhs = exprs.alloc(Expr::Literal(Literal::Integer { value: Some(1) }));
+        let body = exprs.alloc(Expr::Binary {
+            op: BinaryOp::Add,
+            lhs,
+            rhs,
+        });
+
+        check_expr(
+            "|x| x + 1",
+            Expr::Closure {
+                params: vec![Param {
+                    name: "x".into(),
+                    ty: None,
+                }],
+                body,
+                captures: Vec::new(),
+            },
+            Database {
+                exprs,
+                ..Database::default()
+            },
+        );
+    }
+
+    #[test]
+    fn closure_captures_outer_bindings() {
+        assert_eq!(
+            closure_captures("let a = 1\nstate count = 0\n|x| x + a + count + a"),
+            vec![
+                Capture {
+                    name: "a".into(),
+                    kind: BindingKind::Variable,
+                },
+                Capture {
+                    name: "count".into(),
+                    kind: BindingKind::State,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn closure_captures_assigned_state() {
+        assert_eq!(
+            closure_captures("state count = 0\nlet increment = || count = count + 1"),
+            vec![Capture {
+                name: "count".into(),
+                kind: BindingKind::State,
+            }],
+        );
+    }
+
+    #[test]
+    fn closure_captures_defined_names() {
+        let mut database = Database::default();
+        database.define("count".into(), BindingKind::State);
+        let stmt = parse("|| count").stmts().next().unwrap();
+
+        let expr = match database.lower_stmt(stmt) {
+            Some(Stmt::Expr(expr)) => expr,
+            _ => unreachable!(),
+        };
+        match &database.exprs()[expr] {
+            Expr::Closure { captures, .. } => assert_eq!(
+                captures,
+                &[Capture {
+                    name: "count".into(),
+                    kind: BindingKind::State,
+                }],
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn closure_does_not_capture_unresolved_names() {
+        assert_eq!(closure_captures("|| log(1)"), Vec::new());
+    }
+
+    #[test]
+    fn closure_params_shadow_outer_bindings() {
+        assert_eq!(closure_captures("let a = 1\n|a| a"), Vec::new());
+    }
+
+    #[test]
+    fn resolve_names_in_normalization_form_c() {
+        assert_eq!(
+            closure_captures("let caf\u{e9} = 1\n|| cafe\u{301}"),
+            vec![Capture {
+                name: "caf\u{e9}".into(),
+                kind: BindingKind::Variable,
+            }],
+        );
+    }
+
+    #[test]
+    fn nested_closures_capture_through_outer_closure() {
+        let (database, expr) = lower_last_expr("let a = 1\n|x| |y| a + x + y");
+        let (body, captures) = match expr {
+            Expr::Closure { body, captures, .. } => (body, captures),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(
+            captures,
+            vec![Capture {
+                name: "a".into(),
+                kind: BindingKind::Variable,
+            }],
+        );
+
+        match &database.exprs()[body] {
+            Expr::Closure { captures, .. } => assert_eq!(
+                captures,
+                &vec![
+                    Capture {
+                        name: "a".into(),
+                        kind: BindingKind::Variable,
+                    },
+                    Capture {
+                        name: "x".into(),
+                        kind: BindingKind::Param,
+                    },
+                ],
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    fn check_types(input: &str, expected_type_refs: Arena<TypeRef>) {
+        let (database, _) = lower_root(parse(input));
+        assert_eq!(database.type_refs, expected_type_refs);
+    }
+
+    #[test]
+    fn lower_typed_variable_def() {
+        let root = parse("let a: i32 = 1");
+        let ast = root.stmts().next().unwrap();
+        let mut database = Database::default();
+        let hir = database.lower_stmt(ast).unwrap();
+
+        let mut type_refs = Arena::new();
+        let ty = type_refs.alloc(TypeRef::Path { name: "i32".into() });
+
+        assert_eq!(
+            hir,
+            Stmt::VariableDef {
+                name: "a".into(),
+                ty: Some(ty),
+                value: database.exprs().iter().next().unwrap().0,
+                docs: None,
+            },
+        );
+        assert_eq!(database.type_refs, type_refs);
+    }
+
+    #[test]
+    fn lower_fn_type() {
+        let mut type_refs = Arena::new();
+        let param = type_refs.alloc(TypeRef::Path { name: "i32".into() });
+        let ret = type_refs.alloc(TypeRef::Tuple { fields: Vec::new() });
+        type_refs.alloc(TypeRef::Fn {
+            params: vec![param],
+            ret,
+        });
+
+        check_types("let f: (i32) -> () = f", type_refs);
+    }
+
+    #[test]
+    fn lower_array_and_optional_types() {
+        let mut type_refs = Arena::new();
+        let elem = type_refs.alloc(TypeRef::Path {
+            name: "String".into(),
+        });
+        let inner = type_refs.alloc(TypeRef::Array { elem });
+        type_refs.alloc(TypeRef::Optional { inner });
+
+        check_types("let names: [String]? = names", type_refs);
+    }
+
+    #[test]
+    fn lower_missing_type() {
+        let mut type_refs = Arena::new();
+        let elem = type_refs.alloc(TypeRef::Missing);
+        type_refs.alloc(TypeRef::Array { elem });
+
+        check_types("let a: [] = 1", type_refs);
+    }
+
+    #[test]
+    fn lower_function_decl() {
+        let (database, stmts) = lower_root(parse("func f(x: String) -> bool {\n  x\n}"));
+
+        let mut type_refs = Arena::new();
+        let param_ty = type_refs.alloc(TypeRef::Path {
+            name: "String".into(),
+        });
+        let ret_ty = type_refs.alloc(TypeRef::Path {
+            name: "bool".into(),
+        });
+
+        assert_eq!(
+            stmts,
+            vec![Stmt::FunctionDef {
+                name: "f".into(),
+                params: vec![Param {
+                    name: "x".into(),
+                    ty: Some(param_ty),
+                }],
+                ret_ty: Some(ret_ty),
+                body: vec![Stmt::Expr(database.exprs().iter().next().unwrap().0)],
+                docs: None,
+            }],
+        );
+        assert_eq!(database.type_refs, type_refs);
+    }
+
+    #[test]
+    fn closure_captures_function_params() {
+        let (database, stmts) = lower_root(parse("func f(a) {\n  || a\n}"));
+        let closure = match &stmts[0] {
+            Stmt::FunctionDef { body, .. } => match body[0] {
+                Stmt::Expr(closure) => &database.exprs()[closure],
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        assert!(database.type_refs.is_empty());
+        assert_eq!(
+            closure,
+            &Expr::Closure {
+                params: Vec::new(),
+                body: database.exprs().iter().next().unwrap().0,
+                captures: vec![Capture {
+                    name: "a".into(),
+                    kind: BindingKind::Param,
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn lower_struct_decl() {
+        let (database, stmts) = lower_root(parse("struct User { name: String, age: i32 }"));
+        let user = match stmts[0] {
+            Stmt::StructDef(user) => user,
+            _ => unreachable!(),
+        };
+
+        let mut type_refs = Arena::new();
+        let name = type_refs.alloc(TypeRef::Path {
+            name: "String".into(),
+        });
+        let age = type_refs.alloc(TypeRef::Path { name: "i32".into() });
+
+        assert_eq!(
+            database.structs()[user],
+            Struct {
+                name: "User".into(),
+                fields: vec![
+                    Field {
+                        name: "name".into(),
+                        ty: name,
+                        docs: None,
+                    },
+                    Field {
+                        name: "age".into(),
+                        ty: age,
+                        docs: None,
+                    },
+                ],
+                docs: None,
+            },
+        );
+        assert_eq!(
+            database.find_struct("User"),
+            Some(&database.structs()[user])
+        );
+        assert_eq!(database.type_refs, type_refs);
+    }
+
+    #[test]
+    fn lower_enum_decl() {
+        let (database, _) = lower_root(parse(
+            "enum Status { Loading, Loaded(User), Failed(String) }",
+        ));
+
+        let mut type_refs = Arena::new();
+        let user = type_refs.alloc(TypeRef::Path {
+            name: "User".into(),
+        });
+        let string = type_refs.alloc(TypeRef::Path {
+            name: "String".into(),
+        });
+
+        assert_eq!(
+            database.find_enum("Status"),
+            Some(&Enum {
+                name: "Status".into(),
+                variants: vec![
+                    Variant {
+                        name: "Loading".into(),
+                        fields: Vec::new(),
+                        docs: None,
+                    },
+                    Variant {
+                        name: "Loaded".into(),
+                        fields: vec![user],
+                        docs: None,
+                    },
+                    Variant {
+                        name: "Failed".into(),
+                        fields: vec![string],
+                        docs: None,
+                    },
+                ],
+                docs: None,
+            }),
+        );
+        assert_eq!(database.find_enum("User"), None);
+        assert_eq!(database.type_refs, type_refs);
+    }
+
+    #[test]
+    fn lower_doc_comments() {
+        let (database, stmts) = lower_root(parse(
+            "/// The answer\nlet a = 42\n// Not a doc comment\nfunc f() {}\n\
+             /// A user\n/// of the app\nstruct User {\n  /// Full name\n  name: String\n}\n\
+             enum Status {\n  /// Not loaded yet\n  Loading\n}",
+        ));
+
+        let docs: Vec<_> = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::VariableDef { docs, .. } | Stmt::FunctionDef { docs, .. } => docs.clone(),
+                Stmt::StructDef(_) | Stmt::EnumDef(_) => None,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(docs, [Some("The answer".to_string()), None, None, None]);
+
+        let user = database.find_struct("User").unwrap();
+        assert_eq!(user.docs.as_deref(), Some("A user\nof the app"));
+        assert_eq!(user.fields[0].docs.as_deref(), Some("Full name"));
+
+        let status = database.find_enum("Status").unwrap();
+        assert_eq!(status.docs, None);
+        assert_eq!(status.variants[0].docs.as_deref(), Some("Not loaded yet"));
+    }
+
+    #[test]
+    fn lower_struct_expr() {
+        let mut exprs = Arena::new();
+        let name = exprs.alloc(Expr::Literal(Literal::String {
+            value: "\"a\"".into(),
+        }));
+        let age = exprs.alloc(Expr::Literal(Literal::Integer { value: Some(1) }));
+
+        check_expr(
+            "User { name: \"a\", age: 1 }",
+            Expr::Record {
+                name: "User".into(),
+                fields: vec![("name".into(), name), ("age".into(), age)],
+            },
+            Database {
+                exprs,
+                ..Database::default()
+            },
+        );
+    }
+
+    #[test]
+    fn lower_unit_variant() {
+        check_expr(
+            "Status::Loading",
+            Expr::Variant {
+                enum_name: "Status".into(),
+                variant: "Loading".into(),
+                args: Vec::new(),
+            },
+            Database::default(),
+        );
+    }
+
+    #[test]
+    fn lower_variant_constructor() {
+        let mut exprs = Arena::new();
+        let user = exprs.alloc(Expr::VariableRef { var: "user".into() });
+
+        check_expr(
+            "Status::Loaded(user)",
+            Expr::Variant {
+                enum_name: "Status".into(),
+                variant: "Loaded".into(),
+                args: vec![user],
+            },
+            Database {
+                exprs,
+                ..Database::default()
+            },
+        );
+    }
+
+    #[test]
+    fn expr_ranges_exclude_trailing_trivia() {
+        let (database, expr) = lower_last_expr("f(1 + 2 , (a) ) // done");
+        let (callee, args) = match expr {
+            Expr::Call { callee, args } => (callee, args),
+            _ => unreachable!(),
+        };
+
+        let range = |idx| {
+            let range = database.expr_range(idx).unwrap();
+            (u32::from(range.start()), u32::from(range.end()))
+        };
+
+        assert_eq!(range(callee), (0, 1));
+        assert_eq!(range(args[0]), (2, 7));
+        assert_eq!(range(args[1]), (10, 13));
+    }
+
+    #[test]
+    fn source_map_links_exprs_and_syntax() {
+        let input = "let a = 1\nf(a + (2)) // done";
+        let root = parser::parse(input).syntax();
+        let (database, _) = lower_root(ast::Root::cast(root.clone()).unwrap());
+        let source_map = database.source_map();
+
+        let sources: Vec<_> = database
+            .exprs()
+            .iter()
+            .map(|(idx, _)| {
+                let ptr = source_map.expr_syntax(idx).unwrap();
+                assert!(ast::Expr::cast(ptr.to_node(&root)).is_some());
+                assert_eq!(source_map.syntax_expr(ptr), Some(idx));
+
+                &input[database.expr_range(idx).unwrap()]
+            })
+            .collect();
+
+        assert_eq!(sources, ["1", "f", "a", "(2)", "a + (2)", "f(a + (2))"]);
+    }
+
+    #[test]
+    fn def_ranges_start_at_the_keyword() {
+        let input = "/// The count\nstate count = 0\nlet a = 1 // one";
+        let (database, stmts) = lower_root(parse(input));
+        let ranges: Vec<_> = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::VariableDef { value, .. } | Stmt::StateDef { value, .. } => {
+                    &input[database.def_range(*value).unwrap()]
+                }
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(ranges, ["state count = 0", "let a = 1"]);
+    }
+
+    #[test]
+    fn missing_exprs_point_at_their_parent() {
+        let (database, stmts) = lower_root(parse("let a = 1 +\nlet b ="));
+        let source_map = database.source_map();
+        let values: Vec<_> = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::VariableDef { value, .. } => *value,
+                _ => unreachable!(),
+            })
+            .collect();
+
+        let rhs = match database.exprs()[values[0]] {
+            Expr::Binary { rhs, .. } => rhs,
+            _ => unreachable!(),
+        };
+        assert_eq!(database.exprs()[rhs], Expr::Missing);
+        assert_eq!(
+            source_map.expr_syntax(rhs),
+            source_map.expr_syntax(values[0]),
+        );
+        assert_eq!(
+            source_map.syntax_expr(source_map.expr_syntax(rhs).unwrap()),
+            Some(values[0]),
+        );
+
+        assert_eq!(database.exprs()[values[1]], Expr::Missing);
+        assert_eq!(
+            database.expr_range(values[1]),
+            Some(TextRange::new(12.into(), 19.into())),
+        );
+    }
+
+    #[test]
+    fn lower_path_with_too_many_segments() {
+        let root = parse("let a = Status::Loaded::Nope\nmatch a { x::y::z => 0 }");
+        let (database, _) = lower_root(root);
+
+        let ranges: Vec<_> = database
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.range().unwrap()))
+            .collect();
+
+        assert_eq!(
+            ranges,
+            [
+                (Code::INVALID_PATH, TextRange::new(8.into(), 28.into())),
+                (Code::INVALID_PATH, TextRange::new(39.into(), 46.into())),
+            ],
+        );
+    }
+
+    #[test]
+    fn lower_match_expr() {
+        let mut exprs = Arena::new();
+        let scrutinee = exprs.alloc(Expr::VariableRef {
+            var: "status".into(),
+        });
+        let zero = exprs.alloc(Expr::Literal(Literal::Integer { value: Some(0) }));
+        let user = exprs.alloc(Expr::VariableRef { var: "user".into() });
+
+        check_expr(
+            "match status { Status::Loading => 0, Status::Loaded(user) => user }",
+            Expr::Match {
+                scrutinee,
+                arms: vec![
+                    MatchArm {
+                        pat: Pat::Variant {
+                            enum_name: "Status".into(),
+                            variant: "Loading".into(),
+                            fields: Vec::new(),
+                        },
+                        expr: zero,
+                    },
+                    MatchArm {
+                        pat: Pat::Variant {
+                            enum_name: "Status".into(),
+                            variant: "Loaded".into(),
+                            fields: vec![Pat::Bind {
+                                name: "user".into(),
+                            }],
+                        },
+                        expr: user,
+                    },
+                ],
+            },
+            Database {
+                exprs,
+                ..Database::default()
+            },
+        );
+    }
+
+    #[test]
+    fn match_arm_bindings_are_captured_as_variables() {
+        let (database, expr) = lower_last_expr("match a { Some::Value(x) => || x }");
+        let arms = match expr {
+            Expr::Match { arms, .. } => arms,
+            _ => unreachable!(),
+        };
+
+        match &database.exprs()[arms[0].expr] {
+            Expr::Closure { captures, .. } => assert_eq!(
+                captures,
+                &vec![Capture {
+                    name: "x".into(),
+                    kind: BindingKind::Variable,
+                }],
+            ),
+            _ => unreachable!(),
+        }
+    }
 }
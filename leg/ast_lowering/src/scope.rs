@@ -0,0 +1,84 @@
+use hir::{BindingKind, Capture};
+use smol_str::SmolStr;
+
+/// Keeps track of the bindings that are visible while lowering,
+/// and of the closures that are currently being lowered so that
+/// references to outer bindings can be recorded as captures.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Scopes {
+    frames: Vec<Vec<(SmolStr, BindingKind)>>,
+    closures: Vec<ClosureScope>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ClosureScope {
+    /// The index of the frame holding the closure's parameters
+    frame: usize,
+    captures: Vec<Capture>,
+}
+
+impl Default for Scopes {
+    fn default() -> Self {
+        Self {
+            frames: vec![Vec::new()],
+            closures: Vec::new(),
+        }
+    }
+}
+
+impl Scopes {
+    pub(crate) fn define(&mut self, name: SmolStr, kind: BindingKind) {
+        self.frames.last_mut().unwrap().push((name, kind));
+    }
+
+    pub(crate) fn push_frame(&mut self) {
+        self.frames.push(Vec::new());
+    }
+
+    pub(crate) fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    pub(crate) fn enter_closure(&mut self) {
+        self.push_frame();
+        self.closures.push(ClosureScope {
+            frame: self.frames.len() - 1,
+            captures: Vec::new(),
+        });
+    }
+
+    /// Leaves the innermost closure and returns the bindings it captured.
+    pub(crate) fn exit_closure(&mut self) -> Vec<Capture> {
+        self.pop_frame();
+        self.closures.pop().unwrap().captures
+    }
+
+    /// Resolves `name` to the innermost binding with that name. If the
+    /// binding lives outside of any of the closures being lowered, it is
+    /// recorded as a capture of each of them.
+    pub(crate) fn resolve(&mut self, name: &str) -> Option<BindingKind> {
+        let (frame, kind) = self
+            .frames
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(idx, frame)| {
+                frame
+                    .iter()
+                    .rev()
+                    .find(|(binding, _)| binding == name)
+                    .map(|(_, kind)| (idx, *kind))
+            })?;
+
+        for closure in self.closures.iter_mut().filter(|c| c.frame > frame) {
+            if !closure.captures.iter().any(|c| c.name == name) {
+                closure.captures.push(Capture {
+                    name: name.into(),
+                    kind,
+                });
+            }
+        }
+
+        Some(kind)
+    }
+}
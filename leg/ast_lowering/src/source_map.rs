@@ -0,0 +1,75 @@
+use hir::Expr;
+use la_arena::Idx;
+use std::collections::HashMap;
+use syntax::{trimmed_range, SyntaxNode, SyntaxNodePtr};
+use text_size::TextRange;
+
+/// Links every expression to the syntax node it was lowered from, and back.
+/// Placeholders for missing expressions point at the node they are missing
+/// from, so they can be reported too, but that node leads back to the
+/// expression lowered from it rather than to the placeholder.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SourceMap {
+    expr_to_syntax: HashMap<Idx<Expr>, ExprSource>,
+    syntax_to_expr: HashMap<SyntaxNodePtr, Idx<Expr>>,
+    /// The ranges of `let` and `state` definitions, by their value
+    def_ranges: HashMap<Idx<Expr>, TextRange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ExprSource {
+    ptr: SyntaxNodePtr,
+    /// The range of the node without trailing trivia, kept here since
+    /// trimming needs the node itself
+    range: TextRange,
+}
+
+impl SourceMap {
+    /// The node `expr` was lowered from
+    pub fn expr_syntax(&self, expr: Idx<Expr>) -> Option<SyntaxNodePtr> {
+        self.expr_to_syntax.get(&expr).map(|source| source.ptr)
+    }
+
+    /// Where `expr` is in the source, without trailing whitespace and comments
+    pub fn expr_range(&self, expr: Idx<Expr>) -> Option<TextRange> {
+        self.expr_to_syntax.get(&expr).map(|source| source.range)
+    }
+
+    /// The expression lowered from the node `ptr` points at
+    pub fn syntax_expr(&self, ptr: SyntaxNodePtr) -> Option<Idx<Expr>> {
+        self.syntax_to_expr.get(&ptr).copied()
+    }
+
+    /// Where the `let` or `state` definition whose value is `value` is in
+    /// the source, starting at its keyword
+    pub fn def_range(&self, value: Idx<Expr>) -> Option<TextRange> {
+        self.def_ranges.get(&value).copied()
+    }
+
+    pub(crate) fn insert_def(&mut self, value: Idx<Expr>, node: &SyntaxNode) {
+        // The `///` comments before the keyword are part of the node
+        let range = trimmed_range(node);
+        let start = node
+            .children_with_tokens()
+            .find(|element| !element.kind().is_trivia())
+            .map_or(range.start(), |element| element.text_range().start());
+
+        self.def_ranges
+            .insert(value, TextRange::new(start, range.end()));
+    }
+
+    pub(crate) fn insert(&mut self, expr: Idx<Expr>, node: &SyntaxNode, is_placeholder: bool) {
+        let ptr = SyntaxNodePtr::new(node);
+        self.expr_to_syntax.insert(
+            expr,
+            ExprSource {
+                ptr,
+                range: trimmed_range(node),
+            },
+        );
+
+        if !is_placeholder {
+            self.syntax_to_expr.insert(ptr, expr);
+        }
+    }
+}
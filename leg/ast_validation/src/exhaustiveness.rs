@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use ast::{EnumDecl, MatchExpr, Pat};
+use syntax::SyntaxNode;
+
+/// The variants of every enum declared in a file, with the number of
+/// fields each of them has
+pub(crate) struct Enums(HashMap<String, Vec<(String, usize)>>);
+
+impl Enums {
+    pub(crate) fn collect(root: &SyntaxNode) -> Self {
+        let enums = root
+            .descendants()
+            .filter_map(EnumDecl::cast)
+            .filter_map(|enum_decl| {
+                let variants = enum_decl
+                    .variants()
+                    .filter_map(|variant| {
+                        let arity = variant.fields().map_or(0, |fields| fields.fields().count());
+                        Some((ast::name(&variant.name()?), arity))
+                    })
+                    .collect();
+
+                Some((ast::name(&enum_decl.name()?), variants))
+            })
+            .collect();
+
+        Self(enums)
+    }
+}
+
+/// A pattern reduced to what matters for exhaustiveness
+#[derive(Clone)]
+enum Pattern {
+    Wildcard,
+    Variant {
+        enum_name: String,
+        variant: String,
+        fields: Vec<Pattern>,
+    },
+}
+
+/// Returns the patterns that are not covered by any arm of `match_expr`,
+/// e.g. `Status::Loaded(_)`. Nothing is reported when an arm refers to an
+/// enum or variant that is not declared, as there is nothing to check
+/// the arms against.
+pub(crate) fn missing_patterns(match_expr: &MatchExpr, enums: &Enums) -> Option<Vec<String>> {
+    let rows = match_expr
+        .arms()
+        .map(|arm| Some(vec![lower_pat(arm.pat(), enums)?]))
+        .collect::<Option<Vec<_>>>()?;
+
+    let missing = witnesses(&rows, 1, enums)
+        .into_iter()
+        .map(|mut witness| witness.remove(0))
+        .collect();
+
+    Some(missing)
+}
+
+fn lower_pat(pat: Option<Pat>, enums: &Enums) -> Option<Pattern> {
+    let pat = match pat {
+        Some(Pat::VariantPat(pat)) => pat,
+        _ => return Some(Pattern::Wildcard),
+    };
+
+    let segments: Vec<_> = pat.segments().collect();
+    let (enum_name, variant) = match segments.as_slice() {
+        [enum_name, variant] => (ast::name(enum_name), ast::name(variant)),
+        _ => return None,
+    };
+
+    let arity = enums
+        .0
+        .get(&enum_name)?
+        .iter()
+        .find(|(name, _)| *name == variant)?
+        .1;
+
+    // Missing fields match anything, extra fields are ignored
+    let mut fields = pat
+        .fields()
+        .map(|field| lower_pat(Some(field), enums))
+        .collect::<Option<Vec<_>>>()?;
+    fields.resize(arity, Pattern::Wildcard);
+
+    Some(Pattern::Variant {
+        enum_name,
+        variant,
+        fields,
+    })
+}
+
+/// Computes the rows of `width` patterns that are not matched by any of the
+/// rows of `matrix`, rendered as source text.
+fn witnesses(matrix: &[Vec<Pattern>], width: usize, enums: &Enums) -> Vec<Vec<String>> {
+    if width == 0 {
+        return if matrix.is_empty() {
+            vec![Vec::new()]
+        } else {
+            Vec::new()
+        };
+    }
+
+    let enum_name = matrix.iter().find_map(|row| match &row[0] {
+        Pattern::Variant { enum_name, .. } => Some(enum_name),
+        Pattern::Wildcard => None,
+    });
+
+    let enum_name = match enum_name {
+        Some(enum_name) => enum_name,
+        // Only wildcards in the first column, so it can be dropped
+        None => {
+            let rest: Vec<_> = matrix.iter().map(|row| row[1..].to_vec()).collect();
+            return witnesses(&rest, width - 1, enums)
+                .into_iter()
+                .map(|witness| prepend("_".to_string(), witness))
+                .collect();
+        }
+    };
+
+    let mut missing = Vec::new();
+
+    for (variant, arity) in &enums.0[enum_name] {
+        let specialized: Vec<_> = matrix
+            .iter()
+            .filter_map(|row| {
+                let mut fields = match &row[0] {
+                    Pattern::Wildcard => vec![Pattern::Wildcard; *arity],
+                    Pattern::Variant {
+                        variant: v, fields, ..
+                    } if v == variant => fields.clone(),
+                    Pattern::Variant { .. } => return None,
+                };
+                fields.extend_from_slice(&row[1..]);
+                Some(fields)
+            })
+            .collect();
+
+        for mut witness in witnesses(&specialized, arity + width - 1, enums) {
+            let rest = witness.split_off(*arity);
+            let pattern = if *arity == 0 {
+                format!("{}::{}", enum_name, variant)
+            } else {
+                format!("{}::{}({})", enum_name, variant, witness.join(", "))
+            };
+
+            missing.push(prepend(pattern, rest));
+        }
+    }
+
+    missing
+}
+
+fn prepend(first: String, rest: Vec<String>) -> Vec<String> {
+    let mut row = Vec::with_capacity(rest.len() + 1);
+    row.push(first);
+    row.extend(rest);
+    row
+}
@@ -1,15 +1,50 @@
+mod exhaustiveness;
+
 use std::fmt;
 
-use ast::{Literal, LiteralKind};
-use syntax::SyntaxNode;
+use ast::{AssignExpr, Expr, Literal, LiteralKind, MatchExpr};
+use diagnostics::{Code, Diagnostic, Label};
+use exhaustiveness::Enums;
+use syntax::{syntax_kind::SyntaxKind, trimmed_range, SyntaxNode};
 use text_size::TextRange;
 
 #[derive(Debug, PartialEq)]
-pub struct ValidationError {
+struct ValidationError {
     kind: ValidationErrorKind,
     range: TextRange,
 }
 
+impl ValidationError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match &self.kind {
+            ValidationErrorKind::NumberLiteralTooLarge => Diagnostic::error(
+                Code::NUMBER_LITERAL_TOO_LARGE,
+                "number literal is too large",
+            )
+            .with_label(Label::primary(
+                self.range,
+                "this does not fit in an integer",
+            ))
+            .with_note(format!("integers go from {} to {}", i32::MIN, i32::MAX)),
+            ValidationErrorKind::InvalidAssignmentTarget => {
+                Diagnostic::error(Code::INVALID_ASSIGNMENT_TARGET, self.kind.to_string())
+                    .with_label(Label::primary(
+                        self.range,
+                        "cannot assign to this expression",
+                    ))
+            }
+            ValidationErrorKind::NonExhaustiveMatch { missing } => {
+                Diagnostic::error(Code::NON_EXHAUSTIVE_MATCH, "non-exhaustive match")
+                    .with_label(Label::primary(
+                        self.range,
+                        format!("{} not covered", join_patterns(missing)),
+                    ))
+                    .with_help("add arms for the missing patterns, or a `_` arm to match the rest")
+            }
+        }
+    }
+}
+
 impl fmt::Display for ValidationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -22,9 +57,11 @@ impl fmt::Display for ValidationError {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum ValidationErrorKind {
     NumberLiteralTooLarge,
+    InvalidAssignmentTarget,
+    NonExhaustiveMatch { missing: Vec<String> },
 }
 
 impl fmt::Display for ValidationErrorKind {
@@ -33,28 +70,72 @@ impl fmt::Display for ValidationErrorKind {
             Self::NumberLiteralTooLarge => write!(
                 f,
                 "number literal is larger than an integer's maximum value, {}",
-                u64::MAX,
+                i32::MAX,
             ),
+            Self::InvalidAssignmentTarget => write!(f, "can only assign to a variable"),
+            Self::NonExhaustiveMatch { missing } => {
+                write!(
+                    f,
+                    "non-exhaustive match, missing {}",
+                    join_patterns(missing)
+                )
+            }
         }
     }
 }
 
-pub fn validate(node: &SyntaxNode) -> Vec<ValidationError> {
+/// `a`, `b` and `c`
+fn join_patterns(patterns: &[String]) -> String {
+    let mut joined = String::new();
+
+    for (idx, pattern) in patterns.iter().enumerate() {
+        if idx > 0 && idx == patterns.len() - 1 {
+            joined.push_str(" and ");
+        } else if idx > 0 {
+            joined.push_str(", ");
+        }
+
+        joined.push_str(&format!("`{}`", pattern));
+    }
+
+    joined
+}
+
+pub fn validate(node: &SyntaxNode) -> Vec<Diagnostic> {
     let mut errors = Vec::new();
+    let enums = Enums::collect(node);
 
     for node in node.descendants() {
-        if let Some(literal) = Literal::cast(node) {
-            validate_literal(literal, &mut errors);
+        match Expr::cast(node) {
+            Some(Expr::Literal(literal)) => validate_literal(literal, &mut errors),
+            Some(Expr::AssignExpr(assign)) => validate_assign_expr(assign, &mut errors),
+            Some(Expr::MatchExpr(match_expr)) => {
+                validate_match_expr(match_expr, &enums, &mut errors)
+            }
+            _ => {}
         }
     }
 
-    errors
+    errors.iter().map(ValidationError::to_diagnostic).collect()
 }
 
 fn validate_literal(literal: Literal, errors: &mut Vec<ValidationError>) {
     match literal.kind() {
         LiteralKind::Integer(i) => {
-            if i.parse().is_none() {
+            // The smallest integer is written as a negated literal
+            let is_negated = match literal.syntax().parent().and_then(Expr::cast) {
+                Some(Expr::UnaryExpr(unary)) => {
+                    unary.op().map(|op| op.kind()) == Some(SyntaxKind::Minus)
+                }
+                _ => false,
+            };
+            let largest = if is_negated {
+                i32::MIN.unsigned_abs()
+            } else {
+                i32::MAX.unsigned_abs()
+            };
+
+            if !matches!(i.parse(), Some(value) if value <= u64::from(largest)) {
                 errors.push(ValidationError {
                     kind: ValidationErrorKind::NumberLiteralTooLarge,
                     range: literal.first_token().unwrap().text_range(),
@@ -66,6 +147,33 @@ fn validate_literal(literal: Literal, errors: &mut Vec<ValidationError>) {
     };
 }
 
+fn validate_assign_expr(assign: AssignExpr, errors: &mut Vec<ValidationError>) {
+    match assign.target() {
+        Some(Expr::VariableRef(_)) | None => {}
+        Some(target) => errors.push(ValidationError {
+            kind: ValidationErrorKind::InvalidAssignmentTarget,
+            range: trimmed_range(target.syntax()),
+        }),
+    }
+}
+
+fn validate_match_expr(match_expr: MatchExpr, enums: &Enums, errors: &mut Vec<ValidationError>) {
+    let missing = match exhaustiveness::missing_patterns(&match_expr, enums) {
+        Some(missing) if !missing.is_empty() => missing,
+        _ => return,
+    };
+
+    let range = match match_expr.scrutinee() {
+        Some(scrutinee) => trimmed_range(scrutinee.syntax()),
+        None => trimmed_range(match_expr.syntax()),
+    };
+
+    errors.push(ValidationError {
+        kind: ValidationErrorKind::NonExhaustiveMatch { missing },
+        range,
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,13 +185,14 @@ mod tests {
         let expected_errors: Vec<_> = expected_errors
             .iter()
             .map(|(kind, range)| ValidationError {
-                kind: *kind,
+                kind: kind.clone(),
                 range: {
                     let start = range.start.into();
                     let end = range.end.into();
                     TextRange::new(start, end)
                 },
             })
+            .map(|error| error.to_diagnostic())
             .collect();
 
         assert_eq!(validate(&parse.syntax()), expected_errors);
@@ -94,11 +203,99 @@ mod tests {
         check("123", &[]);
     }
 
+    #[test]
+    fn validate_literal_with_separators() {
+        check("1_000_000", &[]);
+    }
+
     #[test]
     fn validate_to_large_literal() {
         check(
             "99999999999999999999",
             &[(ValidationErrorKind::NumberLiteralTooLarge, (0..20))],
         );
+        check(
+            "2147483648",
+            &[(ValidationErrorKind::NumberLiteralTooLarge, (0..10))],
+        );
+    }
+
+    #[test]
+    fn validate_smallest_integer() {
+        check("-2147483648", &[]);
+        check(
+            "-2147483649",
+            &[(ValidationErrorKind::NumberLiteralTooLarge, (1..11))],
+        );
+        check(
+            "1 - 2147483648",
+            &[(ValidationErrorKind::NumberLiteralTooLarge, (4..14))],
+        );
+    }
+
+    #[test]
+    fn validate_assignment_to_variable() {
+        check("a = 1", &[]);
+    }
+
+    #[test]
+    fn validate_assignment_to_call() {
+        check(
+            "f() = 1",
+            &[(ValidationErrorKind::InvalidAssignmentTarget, (0..3))],
+        );
+    }
+
+    const STATUS: &str = "enum Status { Loading, Loaded(User), Failed(Error) }\n";
+
+    fn missing(patterns: &[&str]) -> ValidationErrorKind {
+        ValidationErrorKind::NonExhaustiveMatch {
+            missing: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn validate_exhaustive_match() {
+        check(
+            &format!(
+                "{}match s {{ Status::Loading => 0, Status::Loaded(u) => 1, Status::Failed(_) => 2 }}",
+                STATUS
+            ),
+            &[],
+        );
+    }
+
+    #[test]
+    fn validate_match_with_wildcard() {
+        check(
+            &format!("{}match s {{ Status::Loading => 0, _ => 1 }}", STATUS),
+            &[],
+        );
+    }
+
+    #[test]
+    fn validate_non_exhaustive_match() {
+        check(
+            &format!("{}match s {{ Status::Loading => 0 }}", STATUS),
+            &[(
+                missing(&["Status::Loaded(_)", "Status::Failed(_)"]),
+                (59..60),
+            )],
+        );
+    }
+
+    #[test]
+    fn validate_non_exhaustive_nested_match() {
+        check(
+            "enum Option { None, Some(Status) }\n\
+             enum Status { Loading, Done }\n\
+             match o { Option::None => 0, Option::Some(Status::Done) => 1 }",
+            &[(missing(&["Option::Some(Status::Loading)"]), (71..72))],
+        );
+    }
+
+    #[test]
+    fn validate_match_on_unknown_enum() {
+        check("match s { Unknown::Variant => 0 }", &[]);
     }
 }
@@ -0,0 +1,358 @@
+use codegen_js::Mapping;
+use diagnostics::Diagnostic;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::iter;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{compile, emit, MessageFormat};
+
+/// The extension of source files, directories are searched for files with it
+const SOURCE_EXTENSION: &str = "w";
+
+pub(crate) struct BuildOptions {
+    /// A source file, or a directory that is searched recursively
+    pub(crate) entry: PathBuf,
+    pub(crate) out_dir: PathBuf,
+    /// Only report diagnostics, without writing any output
+    pub(crate) check: bool,
+    /// Fold constants and remove unused definitions from function bodies
+    pub(crate) optimize: bool,
+    pub(crate) message_format: MessageFormat,
+}
+
+/// Compiles every source file of `options.entry` to a `.js` module in
+/// `options.out_dir`, at the same path relative to the entry, along with a
+/// `.js.map` source map. Nothing is written if any file has errors. Returns
+/// whether the build succeeded.
+pub(crate) fn build(options: &BuildOptions) -> io::Result<bool> {
+    let (root, files) = source_files(&options.entry)?;
+    let mut outputs = Vec::new();
+    let mut succeeded = true;
+
+    for file in &files {
+        let source = fs::read_to_string(file)?;
+
+        match compile(&source, options.optimize) {
+            Ok(compiled) => {
+                report(file, &source, &compiled.warnings, options.message_format)?;
+
+                let relative = file.strip_prefix(&root).unwrap_or(file);
+                let js_path = options.out_dir.join(relative).with_extension("js");
+                outputs.extend(with_source_map(
+                    file,
+                    &source,
+                    js_path,
+                    compiled.js,
+                    &compiled.mappings,
+                ));
+            }
+            Err(diagnostics) => {
+                succeeded = false;
+                report(file, &source, &diagnostics, options.message_format)?;
+            }
+        }
+    }
+
+    if succeeded && !options.check {
+        for (path, js) in outputs {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, js)?;
+        }
+    }
+
+    Ok(succeeded)
+}
+
+/// The JS file, which links to its source map, followed by the source map
+fn with_source_map(
+    source_path: &Path,
+    source: &str,
+    js_path: PathBuf,
+    js: String,
+    mappings: &[Mapping],
+) -> [(PathBuf, String); 2] {
+    let map_path = js_path.with_extension("js.map");
+    let file_name = |path: &Path| path.file_name().unwrap().to_string_lossy().into_owned();
+
+    let js_dir = js_path.parent().unwrap_or(Path::new(""));
+    let map = codegen_js::source_map(
+        &file_name(&js_path),
+        &relative_path(js_dir, source_path),
+        source,
+        &js,
+        mappings,
+    );
+    let js = format!("{}//# sourceMappingURL={}\n", js, file_name(&map_path));
+
+    [(js_path, js), (map_path, map)]
+}
+
+/// The path of `to` relative to the directory `from`, separated by `/` like
+/// the URLs in source maps
+fn relative_path(from: &Path, to: &Path) -> String {
+    let components = |path: &Path| -> Vec<String> {
+        let path = env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf());
+
+        path.components()
+            .filter(|component| *component != Component::CurDir)
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect()
+    };
+    let from = components(from);
+    let to = components(to);
+
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    iter::repeat_n("..".to_string(), from.len() - common)
+        .chain(to[common..].iter().cloned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The source files of `entry`, which is a source file or a directory that
+/// is searched recursively, along with the directory they are relative to.
+/// A missing entry or a directory without any source files is an error, so
+/// that a mistyped path doesn't look like a successful build.
+pub(crate) fn source_files(entry: &Path) -> io::Result<(PathBuf, Vec<PathBuf>)> {
+    if !entry.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{}: No such file or directory", entry.display()),
+        ));
+    }
+
+    if entry.is_dir() {
+        let mut files = Vec::new();
+        collect_source_files(entry, &mut files)?;
+
+        if files.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "{}: no `.{}` source files in this directory",
+                    entry.display(),
+                    SOURCE_EXTENSION
+                ),
+            ));
+        }
+
+        files.sort();
+        Ok((entry.to_path_buf(), files))
+    } else {
+        let root = entry.parent().unwrap_or(Path::new("")).to_path_buf();
+        Ok((root, vec![entry.to_path_buf()]))
+    }
+}
+
+fn collect_source_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_source_files(&path, files)?;
+        } else if path.extension() == Some(OsStr::new(SOURCE_EXTENSION)) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Human readable diagnostics go to stderr, JSON diagnostics go to stdout so
+/// that they can be piped into other tools
+pub(crate) fn report(
+    file: &Path,
+    source: &str,
+    diagnostics: &[Diagnostic],
+    message_format: MessageFormat,
+) -> io::Result<()> {
+    let file_name = file.display().to_string();
+
+    match message_format {
+        MessageFormat::Human => {
+            let mut stderr = io::stderr();
+            let colors = stderr.is_terminal();
+            emit(
+                &mut stderr,
+                colors,
+                &file_name,
+                source,
+                diagnostics,
+                message_format,
+            )
+        }
+        MessageFormat::Json => emit(
+            &mut io::stdout(),
+            false,
+            &file_name,
+            source,
+            diagnostics,
+            message_format,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn options(dir: &TempDir, entry: &str, check: bool) -> BuildOptions {
+        BuildOptions {
+            entry: dir.0.join(entry),
+            out_dir: dir.0.join("out"),
+            check,
+            optimize: false,
+            message_format: MessageFormat::Json,
+        }
+    }
+
+    #[test]
+    fn build_directory_mirrors_source_tree() {
+        let dir = TempDir::new("mirror");
+        dir.write("src/main.w", "let a = 1");
+        dir.write("src/ui/button.w", "let b = 2");
+        dir.write("src/notes.txt", "not a source file");
+
+        assert!(build(&options(&dir, "src", false)).unwrap());
+
+        let out = dir.0.join("out");
+        assert_eq!(
+            fs::read_to_string(out.join("main.js")).unwrap(),
+            format!(
+                "{}//# sourceMappingURL=main.js.map\n",
+                compile("let a = 1", false).unwrap().js
+            ),
+        );
+        assert!(out.join("ui/button.js").exists());
+        assert!(!out.join("notes.js").exists());
+    }
+
+    #[test]
+    fn build_single_file() {
+        let dir = TempDir::new("single");
+        dir.write("main.w", "let a = 1");
+
+        assert!(build(&options(&dir, "main.w", false)).unwrap());
+        assert!(dir.0.join("out/main.js").exists());
+    }
+
+    #[test]
+    fn build_optimized() {
+        let dir = TempDir::new("optimize");
+        dir.write(
+            "main.w",
+            "let a = 1 + 2 * 3\nfunc f() {\n  let unused = 1\n  a\n}\nlog(f())",
+        );
+
+        let options = BuildOptions {
+            optimize: true,
+            ..options(&dir, "main.w", false)
+        };
+        assert!(build(&options).unwrap());
+        assert_eq!(
+            fs::read_to_string(dir.0.join("out/main.js")).unwrap(),
+            "let a = 7;\nfunction f() {\nreturn a;\n}\nlog(f());\nexport { a, f };\n//# sourceMappingURL=main.js.map\n",
+        );
+    }
+
+    #[test]
+    fn build_exports_reserved_names_as_they_are_spelled() {
+        let dir = TempDir::new("exports");
+        dir.write("main.w", "let class = 1\nfunc f() {\n  class\n}");
+
+        assert!(build(&options(&dir, "main.w", false)).unwrap());
+        assert_eq!(
+            fs::read_to_string(dir.0.join("out/main.js")).unwrap(),
+            "let class$ = 1;\nfunction f() {\nreturn class$;\n}\n\
+             export { class$ as class, f };\n//# sourceMappingURL=main.js.map\n",
+        );
+
+        let warnings = compile("let class = 1", false).unwrap().warnings;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, diagnostics::Code::RENAMED_EXPORT);
+        assert_eq!(warnings[0].severity, diagnostics::Severity::Warning);
+        assert_eq!(
+            warnings[0].range(),
+            Some(text_size::TextRange::new(4.into(), 9.into()))
+        );
+    }
+
+    #[test]
+    fn build_writes_source_maps() {
+        let dir = TempDir::new("maps");
+        dir.write("src/ui/button.w", "let b = 2");
+
+        assert!(build(&options(&dir, "src", false)).unwrap());
+
+        let map = fs::read_to_string(dir.0.join("out/ui/button.js.map")).unwrap();
+        let map: serde_json::Value = serde_json::from_str(&map).unwrap();
+        assert_eq!(map["file"], "button.js");
+        assert_eq!(map["sources"][0], "../../src/ui/button.w");
+        assert_eq!(map["sourcesContent"][0], "let b = 2");
+    }
+
+    #[test]
+    fn relative_paths() {
+        let check = |from: &str, to: &str| relative_path(Path::new(from), Path::new(to));
+
+        assert_eq!(check("/a/out", "/a/src/main.w"), "../src/main.w");
+        assert_eq!(check("/a", "/a/main.w"), "main.w");
+        assert_eq!(check("./out/ui", "src/ui/b.w"), "../../src/ui/b.w");
+    }
+
+    #[test]
+    fn build_with_errors_writes_nothing() {
+        let dir = TempDir::new("errors");
+        dir.write("src/ok.w", "let a = 1");
+        dir.write("src/broken.w", "let a =");
+
+        assert!(!build(&options(&dir, "src", false)).unwrap());
+        assert!(!dir.0.join("out").exists());
+    }
+
+    #[test]
+    fn build_directory_without_sources_fails() {
+        let dir = TempDir::new("empty");
+        dir.write("src/main.leg", "let a = 1");
+
+        let error = build(&options(&dir, "src", false)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}: no `.w` source files in this directory",
+                dir.0.join("src").display()
+            ),
+        );
+        assert!(!dir.0.join("out").exists());
+    }
+
+    #[test]
+    fn build_missing_entry_fails() {
+        let dir = TempDir::new("missing");
+
+        let error = build(&options(&dir, "main.w", false)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}: No such file or directory",
+                dir.0.join("main.w").display()
+            ),
+        );
+    }
+
+    #[test]
+    fn check_writes_nothing() {
+        let dir = TempDir::new("check");
+        dir.write("src/main.w", "let a = 1");
+
+        assert!(build(&options(&dir, "src", true)).unwrap());
+        assert!(!dir.0.join("out").exists());
+    }
+}
@@ -0,0 +1,310 @@
+use ast_lowering::Database;
+use diagnostics::Diagnostic;
+use hir::{Param, Stmt, TypeRef};
+use la_arena::Idx;
+use parser::parse;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::build::{report, source_files};
+use crate::MessageFormat;
+
+pub(crate) struct DocOptions {
+    /// A source file, or a directory that is searched recursively
+    pub(crate) entry: PathBuf,
+    pub(crate) out_dir: PathBuf,
+    pub(crate) message_format: MessageFormat,
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 50rem; margin: 2rem auto; padding: 0 1rem; }
+pre, code { font-family: monospace; }
+pre { background: #f4f4f4; padding: 0.5rem; }
+article { margin-bottom: 2rem; }";
+
+/// Writes an `index.html` to `options.out_dir` that documents the top-level
+/// definitions of every source file of `options.entry`, which are the ones
+/// other files can use. Files with syntax errors are reported and left out.
+/// Returns whether every file could be documented.
+pub(crate) fn document(options: &DocOptions) -> io::Result<bool> {
+    let (root, files) = source_files(&options.entry)?;
+    let mut modules = Vec::new();
+    let mut succeeded = true;
+
+    for file in &files {
+        let source = fs::read_to_string(file)?;
+        let module = file
+            .strip_prefix(&root)
+            .unwrap_or(file)
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        match document_source(&module, &source) {
+            Ok(section) => modules.push((module, section)),
+            Err(diagnostics) => {
+                succeeded = false;
+                report(file, &source, &diagnostics, options.message_format)?;
+            }
+        }
+    }
+
+    fs::create_dir_all(&options.out_dir)?;
+    fs::write(options.out_dir.join("index.html"), page(&modules))?;
+
+    Ok(succeeded)
+}
+
+fn page(modules: &[(String, String)]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Documentation</title>\n<style>\n{}\n</style>\n</head>\n<body>\n\
+         <h1>Documentation</h1>\n<nav>\n<ul>\n",
+        STYLE
+    );
+
+    for (module, _) in modules {
+        let module = escape(module);
+        html.push_str(&format!("<li><a href=\"#{0}\">{0}</a></li>\n", module));
+    }
+    html.push_str("</ul>\n</nav>\n");
+
+    for (_, section) in modules {
+        html.push_str(section);
+    }
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+/// The HTML section that documents the definitions of `source`, or its
+/// syntax errors
+fn document_source(module: &str, source: &str) -> Result<String, Vec<Diagnostic>> {
+    let parse = parse(source);
+    let diagnostics = parse.diagnostics();
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
+    let (database, stmts) = ast_lowering::lower_root(ast::Root::cast(parse.syntax()).unwrap());
+    let module = escape(module);
+    let mut html = format!("<section>\n<h2 id=\"{0}\">{0}</h2>\n", module);
+
+    for stmt in &stmts {
+        let (name, signature, docs, members) = match stmt {
+            Stmt::VariableDef { name, ty, docs, .. } => (
+                name,
+                format!("let {}{}", name, annotation(&database, *ty)),
+                docs,
+                Vec::new(),
+            ),
+            Stmt::StateDef { name, ty, docs, .. } => (
+                name,
+                format!("state {}{}", name, annotation(&database, *ty)),
+                docs,
+                Vec::new(),
+            ),
+            Stmt::FunctionDef {
+                name,
+                params,
+                ret_ty,
+                docs,
+                ..
+            } => {
+                let ret = ret_ty.map_or(String::new(), |ty| {
+                    format!(" -> {}", type_ref(&database, ty))
+                });
+                let signature = format!("func {}({}){}", name, params_list(&database, params), ret);
+
+                (name, signature, docs, Vec::new())
+            }
+            Stmt::StructDef(def) => {
+                let def = &database.structs()[*def];
+                let fields = def
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let ty = type_ref(&database, field.ty);
+                        (format!("{}: {}", field.name, ty), &field.docs)
+                    })
+                    .collect();
+
+                (&def.name, format!("struct {}", def.name), &def.docs, fields)
+            }
+            Stmt::EnumDef(def) => {
+                let def = &database.enums()[*def];
+                let variants = def
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let fields = variant
+                            .fields
+                            .iter()
+                            .map(|ty| type_ref(&database, *ty))
+                            .collect::<Vec<_>>();
+                        let signature = if variant.fields.is_empty() {
+                            variant.name.to_string()
+                        } else {
+                            format!("{}({})", variant.name, fields.join(", "))
+                        };
+
+                        (signature, &variant.docs)
+                    })
+                    .collect();
+
+                (&def.name, format!("enum {}", def.name), &def.docs, variants)
+            }
+            Stmt::Expr(_) => continue,
+        };
+
+        html.push_str(&format!(
+            "<article id=\"{}::{}\">\n<pre><code>{}</code></pre>\n{}",
+            module,
+            escape(name),
+            escape(&signature),
+            paragraphs(docs),
+        ));
+
+        if !members.is_empty() {
+            html.push_str("<ul>\n");
+            for (signature, docs) in members {
+                html.push_str(&format!(
+                    "<li><code>{}</code>\n{}</li>\n",
+                    escape(&signature),
+                    paragraphs(docs)
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</article>\n");
+    }
+    html.push_str("</section>\n");
+
+    Ok(html)
+}
+
+/// `: type` if the definition spells out its type
+fn annotation(database: &Database, ty: Option<Idx<TypeRef>>) -> String {
+    ty.map_or(String::new(), |ty| format!(": {}", type_ref(database, ty)))
+}
+
+fn params_list(database: &Database, params: &[Param]) -> String {
+    params
+        .iter()
+        .map(|param| format!("{}{}", param.name, annotation(database, param.ty)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `ty` in the syntax it is written in
+fn type_ref(database: &Database, ty: Idx<TypeRef>) -> String {
+    let list = |types: &[Idx<TypeRef>]| {
+        types
+            .iter()
+            .map(|ty| type_ref(database, *ty))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    match &database.type_refs()[ty] {
+        TypeRef::Missing => "_".to_string(),
+        TypeRef::Path { name } => name.to_string(),
+        TypeRef::Fn { params, ret } => {
+            format!("({}) -> {}", list(params), type_ref(database, *ret))
+        }
+        TypeRef::Tuple { fields } => format!("({})", list(fields)),
+        TypeRef::Array { elem } => format!("[{}]", type_ref(database, *elem)),
+        TypeRef::Optional { inner } => {
+            format!("{}?", type_ref(database, *inner))
+        }
+    }
+}
+
+/// Doc comments as HTML paragraphs, which are separated by blank lines
+fn paragraphs(docs: &Option<String>) -> String {
+    docs.iter()
+        .flat_map(|docs| docs.split("\n\n"))
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| format!("<p>{}</p>\n", escape(paragraph.trim())))
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn options(dir: &TempDir, entry: &str) -> DocOptions {
+        DocOptions {
+            entry: dir.0.join(entry),
+            out_dir: dir.0.join("doc"),
+            message_format: MessageFormat::Json,
+        }
+    }
+
+    #[test]
+    fn document_definitions_with_their_docs() {
+        let html = document_source(
+            "ui/button",
+            "/// Adds `a` and `b`\n///\n/// Never overflows\nfunc add(a: i32, b) -> i32 {\n  a + b\n}\n\
+             /// A <button> label\nlet label: String = \"Ok\"\n\
+             struct User {\n  /// Full name\n  name: String,\n  tags: [String]?\n}\n\
+             enum Status { Loading, Loaded(User, () -> i32) }\n\
+             add(1, 2)",
+        )
+        .unwrap();
+
+        assert_eq!(
+            html,
+            "<section>\n<h2 id=\"ui/button\">ui/button</h2>\n\
+             <article id=\"ui/button::add\">\n<pre><code>func add(a: i32, b) -&gt; i32</code></pre>\n\
+             <p>Adds `a` and `b`</p>\n<p>Never overflows</p>\n</article>\n\
+             <article id=\"ui/button::label\">\n<pre><code>let label: String</code></pre>\n\
+             <p>A &lt;button&gt; label</p>\n</article>\n\
+             <article id=\"ui/button::User\">\n<pre><code>struct User</code></pre>\n<ul>\n\
+             <li><code>name: String</code>\n<p>Full name</p>\n</li>\n\
+             <li><code>tags: [String]?</code>\n</li>\n</ul>\n</article>\n\
+             <article id=\"ui/button::Status\">\n<pre><code>enum Status</code></pre>\n<ul>\n\
+             <li><code>Loading</code>\n</li>\n\
+             <li><code>Loaded(User, () -&gt; i32)</code>\n</li>\n</ul>\n</article>\n\
+             </section>\n",
+        );
+    }
+
+    #[test]
+    fn document_directory() {
+        let dir = TempDir::new("doc");
+        dir.write("src/main.w", "/// The answer\nlet answer = 42");
+        dir.write("src/ui/button.w", "func click() {}");
+
+        assert!(document(&options(&dir, "src")).unwrap());
+
+        let html = fs::read_to_string(dir.0.join("doc/index.html")).unwrap();
+        assert!(html.contains(
+            "<li><a href=\"#main\">main</a></li>\n<li><a href=\"#ui/button\">ui/button</a></li>"
+        ));
+        assert!(html.contains("<p>The answer</p>"));
+        assert!(html.contains("<pre><code>func click()</code></pre>"));
+    }
+
+    #[test]
+    fn leave_out_files_with_syntax_errors() {
+        let dir = TempDir::new("doc-errors");
+        dir.write("src/broken.w", "let a =");
+        dir.write("src/fine.w", "let b = 1");
+
+        assert!(!document(&options(&dir, "src")).unwrap());
+
+        let html = fs::read_to_string(dir.0.join("doc/index.html")).unwrap();
+        assert!(!html.contains("broken"));
+        assert!(html.contains("let b"));
+    }
+}
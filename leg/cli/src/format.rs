@@ -0,0 +1,117 @@
+use diagnostics::Diagnostic;
+use parser::parse;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::build::{report, source_files};
+use crate::MessageFormat;
+
+pub(crate) struct FormatOptions {
+    /// A source file, or a directory that is searched recursively
+    pub(crate) entry: PathBuf,
+    /// Only report the files that are not formatted, without changing them
+    pub(crate) check: bool,
+    pub(crate) message_format: MessageFormat,
+}
+
+/// Formats every source file of `options.entry` in place. Files with syntax
+/// errors are reported and left alone. Returns whether every file could be
+/// formatted, or with `check`, whether every file already was.
+pub(crate) fn format_files(options: &FormatOptions) -> io::Result<bool> {
+    let (_, files) = source_files(&options.entry)?;
+    let mut succeeded = true;
+
+    for file in &files {
+        let source = fs::read_to_string(file)?;
+
+        let formatted = match format_source(&source) {
+            Ok(formatted) => formatted,
+            Err(diagnostics) => {
+                succeeded = false;
+                report(file, &source, &diagnostics, options.message_format)?;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+
+        if options.check {
+            succeeded = false;
+            eprintln!("{} is not formatted", file.display());
+        } else {
+            fs::write(file, formatted)?;
+        }
+    }
+
+    Ok(succeeded)
+}
+
+/// The formatted `source`, or its syntax errors
+fn format_source(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let parse = parse(source);
+    let diagnostics = parse.diagnostics();
+
+    if diagnostics.is_empty() {
+        Ok(fmt::format(&parse.syntax()))
+    } else {
+        Err(diagnostics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn options(dir: &TempDir, entry: &str, check: bool) -> FormatOptions {
+        FormatOptions {
+            entry: dir.0.join(entry),
+            check,
+            message_format: MessageFormat::Json,
+        }
+    }
+
+    #[test]
+    fn format_directory_in_place() {
+        let dir = TempDir::new("format");
+        dir.write("src/main.w", "let  a=1");
+        dir.write("src/ui/button.w", "func f( ) {\na\n}");
+        dir.write("src/notes.txt", "let  a=1");
+
+        assert!(format_files(&options(&dir, "src", false)).unwrap());
+
+        let read = |relative: &str| fs::read_to_string(dir.0.join(relative)).unwrap();
+        assert_eq!(read("src/main.w"), "let a = 1\n");
+        assert_eq!(read("src/ui/button.w"), "func f() {\n    a\n}\n");
+        assert_eq!(read("src/notes.txt"), "let  a=1");
+    }
+
+    #[test]
+    fn check_changes_nothing() {
+        let dir = TempDir::new("format-check");
+        dir.write("formatted.w", "let a = 1\n");
+        dir.write("unformatted.w", "let  a=1");
+
+        assert!(format_files(&options(&dir, "formatted.w", true)).unwrap());
+        assert!(!format_files(&options(&dir, "unformatted.w", true)).unwrap());
+        assert_eq!(
+            fs::read_to_string(dir.0.join("unformatted.w")).unwrap(),
+            "let  a=1",
+        );
+    }
+
+    #[test]
+    fn leave_files_with_syntax_errors_alone() {
+        let dir = TempDir::new("format-errors");
+        dir.write("broken.w", "let  a=");
+
+        assert!(!format_files(&options(&dir, "broken.w", false)).unwrap());
+        assert_eq!(
+            fs::read_to_string(dir.0.join("broken.w")).unwrap(),
+            "let  a="
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use diagnostics::{Diagnostic, LineIndex};
+use serde_json::{json, Value};
+use text_size::{TextRange, TextSize};
+
+/// Converts `diagnostic` into the object printed by `--message-format=json`.
+/// Ranges are byte offsets into the file, lines and columns are one based
+/// with columns counted in characters, like the human readable output.
+pub(crate) fn diagnostic_to_json(
+    file_name: &str,
+    source: &str,
+    line_index: &LineIndex,
+    diagnostic: &Diagnostic,
+) -> Value {
+    let span = |range: TextRange| {
+        let position = |offset: TextSize| {
+            let location = line_index.line_col(source, offset);
+            json!({ "line": location.line + 1, "column": location.col + 1 })
+        };
+
+        json!({
+            "start": u32::from(range.start()),
+            "end": u32::from(range.end()),
+            "start_position": position(range.start()),
+            "end_position": position(range.end()),
+        })
+    };
+
+    let labels: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            json!({
+                "span": span(label.range),
+                "message": label.message,
+                "primary": label.primary,
+            })
+        })
+        .collect();
+
+    let fixes: Vec<_> = diagnostic
+        .fixes
+        .iter()
+        .map(|fix| {
+            json!({
+                "message": fix.message,
+                "span": span(fix.range),
+                "replacement": fix.replacement,
+            })
+        })
+        .collect();
+
+    json!({
+        "code": diagnostic.code.as_str(),
+        "severity": diagnostic.severity.to_string(),
+        "file": file_name,
+        "message": diagnostic.message,
+        "span": diagnostic.range().map(span),
+        "labels": labels,
+        "notes": diagnostic.notes,
+        "help": diagnostic.help,
+        "fixes": fixes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+
+    fn check(source: &str, expected: Expect) {
+        let line_index = LineIndex::new(source);
+        let diagnostics: Vec<_> = parser::parse(source)
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic_to_json("main.w", source, &line_index, diagnostic))
+            .collect();
+
+        expected.assert_eq(&serde_json::to_string_pretty(&diagnostics).unwrap());
+    }
+
+    #[test]
+    fn missing_terminator_with_fix() {
+        check(
+            "let a = 1\nlet b = 2 let c = 3",
+            expect![[r#"
+[
+  {
+    "code": "E0002",
+    "file": "main.w",
+    "fixes": [
+      {
+        "message": "separate the statements with `;`",
+        "replacement": "; ",
+        "span": {
+          "end": 20,
+          "end_position": {
+            "column": 11,
+            "line": 2
+          },
+          "start": 20,
+          "start_position": {
+            "column": 11,
+            "line": 2
+          }
+        }
+      }
+    ],
+    "help": null,
+    "labels": [
+      {
+        "message": "expected `;` or a newline before this",
+        "primary": true,
+        "span": {
+          "end": 23,
+          "end_position": {
+            "column": 14,
+            "line": 2
+          },
+          "start": 20,
+          "start_position": {
+            "column": 11,
+            "line": 2
+          }
+        }
+      }
+    ],
+    "message": "expected `;` or a newline, but found let",
+    "notes": [],
+    "severity": "error",
+    "span": {
+      "end": 23,
+      "end_position": {
+        "column": 14,
+        "line": 2
+      },
+      "start": 20,
+      "start_position": {
+        "column": 11,
+        "line": 2
+      }
+    }
+  }
+]"#]],
+        );
+    }
+
+    #[test]
+    fn unexpected_end_of_input() {
+        check(
+            "let a =",
+            expect![[r#"
+[
+  {
+    "code": "E0001",
+    "file": "main.w",
+    "fixes": [],
+    "help": null,
+    "labels": [
+      {
+        "message": "the input ends here",
+        "primary": true,
+        "span": {
+          "end": 7,
+          "end_position": {
+            "column": 8,
+            "line": 1
+          },
+          "start": 6,
+          "start_position": {
+            "column": 7,
+            "line": 1
+          }
+        }
+      }
+    ],
+    "message": "expected number, string, true, false, identifier, `-`, `!`, `(`, `|`, `||` or match",
+    "notes": [],
+    "severity": "error",
+    "span": {
+      "end": 7,
+      "end_position": {
+        "column": 8,
+        "line": 1
+      },
+      "start": 6,
+      "start_position": {
+        "column": 7,
+        "line": 1
+      }
+    }
+  }
+]"#]],
+        );
+    }
+}
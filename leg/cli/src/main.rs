@@ -1,47 +1,233 @@
+mod build;
+mod doc;
+mod format;
+mod json;
+mod repl;
+#[cfg(test)]
+mod temp_dir;
+
+use build::BuildOptions;
+use codegen_js::{Export, Generator, Mapping};
+use diagnostics::{Code, Diagnostic, Label, Renderer};
+use doc::DocOptions;
+use format::FormatOptions;
 use parser::parse;
+use std::env;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
 
-fn main() -> io::Result<()> {
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
+#[derive(Clone, Copy)]
+pub(crate) enum MessageFormat {
+    Human,
+    /// One JSON object per line, for editors and CI
+    Json,
+}
 
-    let mut input = String::new();
+const USAGE: &str = "\
+usage: wlang [--message-format=human|json]
+       wlang build <entry> [-o <out-dir>] [--check] [--optimize] [--message-format=human|json]
+       wlang fmt <entry> [--check] [--message-format=human|json]
+       wlang doc <entry> [-o <out-dir>] [--message-format=human|json]";
 
-    loop {
-        write!(stdout, "→ ")?;
-        stdout.flush()?;
+enum Command {
+    Repl { message_format: MessageFormat },
+    Build(BuildOptions),
+    Format(FormatOptions),
+    Doc(DocOptions),
+}
 
-        stdin.read_line(&mut input)?;
+fn main() {
+    let command = match parse_args(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
 
-        let parse = parse(&input);
-        input.clear();
+    let result = match command {
+        Command::Repl { message_format } => repl::run(message_format).map(|()| true),
+        Command::Build(options) => build::build(&options),
+        Command::Format(options) => format::format_files(&options),
+        Command::Doc(options) => doc::document(&options),
+    };
 
-        if parse.errors().len() > 0 {
-            println!("Found errors:");
-            for error in parse.errors() {
-                println!("{}", error);
-            }
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        // Like a missing entry, which the message already names
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+}
 
-            continue;
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut message_format = MessageFormat::Human;
+    let mut subcommand = None;
+    let mut entry = None;
+    let mut out_dir = None;
+    let mut check = false;
+    let mut optimize = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--message-format=human" => message_format = MessageFormat::Human,
+            "--message-format=json" => message_format = MessageFormat::Json,
+            "--check" => check = true,
+            "--optimize" => optimize = true,
+            "-o" => match args.next() {
+                Some(dir) => out_dir = Some(PathBuf::from(dir)),
+                None => return Err("`-o` expects a directory".to_string()),
+            },
+            "build" | "fmt" | "doc" if subcommand.is_none() => subcommand = Some(arg),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ if subcommand.is_some() && entry.is_none() => entry = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
         }
+    }
 
-        let syntax = parse.syntax();
-        let ast = ast::Root::cast(syntax.clone());
+    match subcommand.as_deref() {
+        Some("build") => Ok(Command::Build(BuildOptions {
+            entry: entry.ok_or("`build` expects a file or directory")?,
+            out_dir: out_dir.unwrap_or_else(|| PathBuf::from("out")),
+            check,
+            optimize,
+            message_format,
+        })),
+        Some("fmt") => match (out_dir, optimize) {
+            (None, false) => Ok(Command::Format(FormatOptions {
+                entry: entry.ok_or("`fmt` expects a file or directory")?,
+                check,
+                message_format,
+            })),
+            _ => Err("`-o` can only be used with `build` or `doc`, `--optimize` only with `build`".to_string()),
+        },
+        Some(_) => match (check, optimize) {
+            (false, false) => Ok(Command::Doc(DocOptions {
+                entry: entry.ok_or("`doc` expects a file or directory")?,
+                out_dir: out_dir.unwrap_or_else(|| PathBuf::from("doc")),
+                message_format,
+            })),
+            _ => Err("`--check` can only be used with `build` or `fmt`, `--optimize` only with `build`".to_string()),
+        },
+        None => match (out_dir, check, optimize) {
+            (None, false, false) => Ok(Command::Repl { message_format }),
+            _ => Err(
+                "`--check` can only be used with `build` or `fmt`, `-o` with `build` or `doc`, `--optimize` only with `build`"
+                    .to_string(),
+            ),
+        },
+    }
+}
 
-        let errors = ast_validation::validate(&syntax);
-        if errors.len() > 0 {
-            println!("Found errors:");
-            for error in errors {
-                println!("{}", error);
-            }
+/// The output of compiling a source file
+pub(crate) struct Compiled {
+    pub(crate) js: String,
+    /// Where the expressions in `js` came from
+    pub(crate) mappings: Vec<Mapping>,
+    /// Problems that don't stop the file from being compiled
+    pub(crate) warnings: Vec<Diagnostic>,
+}
 
-            continue;
-        }
+/// Runs every phase over `source`, returning the generated JS module, or the
+/// diagnostics of all phases sorted by position. `optimize` runs the HIR
+/// optimizations before generating JS.
+pub(crate) fn compile(source: &str, optimize: bool) -> Result<Compiled, Vec<Diagnostic>> {
+    let parse = parse(source);
+    let syntax = parse.syntax();
 
-        let (database, hir) = ast_lowering::lower_root(ast.unwrap());
+    let mut diagnostics = parse.diagnostics();
+    diagnostics.extend(ast_validation::validate(&syntax));
 
-        let source = codegen_js::generate(hir, database);
+    let root = ast::Root::cast(syntax).unwrap();
+    let (mut database, mut hir) = ast_lowering::lower_root(root.clone());
+    diagnostics.extend_from_slice(database.diagnostics());
 
-        println!("Source: {}", source);
+    if !diagnostics.is_empty() {
+        diagnostics::sort_by_position(&mut diagnostics);
+        return Err(diagnostics);
     }
+
+    if optimize {
+        hir = hir_optimization::optimize(hir, &mut database);
+    }
+
+    let mut generator = Generator::new();
+    let (mut js, mappings) = generator.generate_with_mappings(&hir, &database);
+    let exports = generator.exports(&hir);
+    js.push_str(&generator.export_statement(&exports));
+
+    Ok(Compiled {
+        js,
+        mappings,
+        warnings: export_warnings(&root, &exports),
+    })
+}
+
+/// Warns about the top level names that are exported as they are spelled,
+/// but that the module itself has to call something else in JS
+fn export_warnings(root: &ast::Root, exports: &[Export]) -> Vec<Diagnostic> {
+    let definitions: Vec<_> = root
+        .stmts()
+        .filter_map(|stmt| match stmt {
+            ast::Stmt::VariableDef(def) => def.name(),
+            ast::Stmt::StateDef(def) => def.name(),
+            ast::Stmt::FunctionDecl(decl) => decl.name(),
+            _ => None,
+        })
+        .collect();
+
+    exports
+        .iter()
+        .filter_map(|export| match export {
+            Export::Conflict { name, js_name } => Some((name, js_name)),
+            Export::Preserved { .. } => None,
+        })
+        .map(|(name, js_name)| {
+            let diagnostic = Diagnostic::warning(
+                Code::RENAMED_EXPORT,
+                format!("`{}` is called `{}` in the generated JS", name, js_name),
+            )
+            .with_note(format!(
+                "`{}` is reserved in JS, it is still exported as `{}` but has to be renamed where it is imported",
+                name, name
+            ));
+
+            // The last definition is the one that is exported
+            match definitions.iter().rfind(|token| ast::name(token) == **name) {
+                Some(token) => diagnostic.with_label(Label::primary(
+                    token.text_range(),
+                    "this name is reserved in JS",
+                )),
+                None => diagnostic,
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn emit(
+    out: &mut impl Write,
+    colors: bool,
+    file_name: &str,
+    source: &str,
+    diagnostics: &[Diagnostic],
+    message_format: MessageFormat,
+) -> io::Result<()> {
+    let renderer = Renderer::new(file_name, source).with_colors(colors);
+
+    for diagnostic in diagnostics {
+        match message_format {
+            MessageFormat::Human => writeln!(out, "{}", renderer.render(diagnostic))?,
+            MessageFormat::Json => {
+                let json =
+                    json::diagnostic_to_json(file_name, source, renderer.line_index(), diagnostic);
+                writeln!(out, "{}", json)?
+            }
+        }
+    }
+
+    Ok(())
 }
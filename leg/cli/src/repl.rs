@@ -0,0 +1,295 @@
+use std::io::{self, IsTerminal, Write};
+
+use ast_lowering::Database;
+use codegen_js::Generator;
+use diagnostics::Diagnostic;
+use hir::Stmt;
+use interp::{Interpreter, Value};
+use parser::parse;
+use text_size::TextSize;
+
+use crate::{emit, MessageFormat};
+
+const PROMPT: &str = "→ ";
+const CONTINUATION_PROMPT: &str = "… ";
+
+const HELP: &str = "\
+Enter statements to evaluate them, definitions are kept for later inputs.
+Input that is not finished yet, like an unclosed block, continues on the next
+line, an empty line ends it.
+
+:tree [code]  show the syntax tree of `code`, or of the whole session
+:hir [code]   show the HIR of `code`, or of the whole session
+:js [code]    show the JS of `code`, or of the whole session
+:help         show this message";
+
+/// Everything that was defined by the inputs accepted so far. Inputs with
+/// errors are not added to the session.
+#[derive(Default)]
+pub(crate) struct Session {
+    database: Database,
+    generator: Generator,
+    interpreter: Interpreter,
+    stmts: Vec<Stmt>,
+    /// The source and JS of every accepted input
+    source: String,
+    js: String,
+}
+
+impl Session {
+    /// Compiles `input` in the context of the previous inputs, adds its
+    /// definitions to the session and evaluates it. Inputs that fail at
+    /// runtime are still kept, along with the variables they defined
+    /// before failing.
+    pub(crate) fn eval(&mut self, input: &str) -> Result<Value, Vec<Diagnostic>> {
+        let (database, stmts) = self.lower(input)?;
+        let js = self.generator.generate(&stmts, &database);
+
+        self.database = database;
+        self.source.push_str(input);
+        if !input.ends_with('\n') {
+            self.source.push('\n');
+        }
+        self.js.push_str(&js);
+
+        let result = self.interpreter.run(&stmts, &self.database);
+        self.stmts.extend(stmts);
+
+        result.map_err(|mut error| {
+            // Errors in functions from earlier inputs have ranges into
+            // those inputs, which can't be shown against this one
+            if error
+                .range
+                .is_some_and(|range| range.end() > TextSize::of(input))
+            {
+                error.range = None;
+            }
+            vec![error.to_diagnostic()]
+        })
+    }
+
+    /// Lowers `input` into a copy of the session's database, which is
+    /// returned along with the statements of `input` if it has no errors
+    fn lower(&self, input: &str) -> Result<(Database, Vec<Stmt>), Vec<Diagnostic>> {
+        let parse = parse(input);
+        let syntax = parse.syntax();
+
+        let mut diagnostics = parse.diagnostics();
+        diagnostics.extend(ast_validation::validate(&syntax));
+
+        let mut database = self.database.clone();
+        let stmts = ast::Root::cast(syntax)
+            .unwrap()
+            .stmts()
+            .filter_map(|stmt| database.lower_stmt(stmt))
+            .collect();
+
+        diagnostics.extend_from_slice(database.diagnostics());
+
+        if diagnostics.is_empty() {
+            Ok((database, stmts))
+        } else {
+            diagnostics::sort_by_position(&mut diagnostics);
+            Err(diagnostics)
+        }
+    }
+
+    fn tree(&self, code: &str) -> String {
+        let code = if code.is_empty() { &self.source } else { code };
+        parse(code).debug_tree()
+    }
+
+    fn hir(&self, code: &str) -> Result<String, Vec<Diagnostic>> {
+        if code.is_empty() {
+            return Ok(dump_hir(&self.stmts, &self.database));
+        }
+
+        let (database, stmts) = self.lower(code)?;
+        Ok(dump_hir(&stmts, &database))
+    }
+
+    fn js(&self, code: &str) -> Result<String, Vec<Diagnostic>> {
+        if code.is_empty() {
+            return Ok(self.js.clone());
+        }
+
+        let (database, stmts) = self.lower(code)?;
+        // Generate with a copy so that the states `code` defines are forgotten
+        Ok(self.generator.clone().generate(&stmts, &database))
+    }
+}
+
+/// The statements followed by the expressions of the database they index into
+fn dump_hir(stmts: &[Stmt], database: &Database) -> String {
+    let mut out = format!("{:#?}\n", stmts);
+
+    for (idx, expr) in database.exprs().iter() {
+        out.push_str(&format!("{:?}: {:?}\n", idx, expr));
+    }
+
+    out
+}
+
+pub(crate) fn run(message_format: MessageFormat) -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let colors = stdout.is_terminal();
+
+    let mut session = Session::default();
+    let mut input = String::new();
+
+    loop {
+        let prompt = if input.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        write!(stdout, "{}", prompt)?;
+        stdout.flush()?;
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        if input.is_empty() {
+            if let Some(command) = line.trim().strip_prefix(':') {
+                let (name, code) = command.split_once(' ').unwrap_or((command, ""));
+                let code = code.trim();
+                let output = match name {
+                    "tree" => Ok(session.tree(code)),
+                    "hir" => session.hir(code),
+                    "js" => session.js(code),
+                    "help" => Ok(HELP.to_string()),
+                    _ => Ok(format!("unknown command `:{}`, see `:help`", name)),
+                };
+
+                match output {
+                    Ok(output) => writeln!(stdout, "{}", output.trim_end())?,
+                    Err(diagnostics) => emit(
+                        &mut stdout,
+                        colors,
+                        "<repl>",
+                        code,
+                        &diagnostics,
+                        message_format,
+                    )?,
+                }
+                continue;
+            }
+        }
+
+        let is_blank = line.trim().is_empty();
+        input.push_str(&line);
+
+        // Keep reading while the input could still be completed, an empty
+        // line gives up and reports what is missing
+        if !is_blank && parse(&input).is_incomplete() {
+            continue;
+        }
+
+        if !input.trim().is_empty() {
+            match session.eval(&input) {
+                Ok(Value::Unit) => {}
+                Ok(value) => writeln!(stdout, "{}", value)?,
+                Err(diagnostics) => emit(
+                    &mut stdout,
+                    colors,
+                    "<repl>",
+                    &input,
+                    &diagnostics,
+                    message_format,
+                )?,
+            }
+        }
+
+        input.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text_size::TextRange;
+
+    #[test]
+    fn definitions_are_kept_between_inputs() {
+        let mut session = Session::default();
+
+        assert_eq!(session.eval("state count = 0"), Ok(Value::Unit));
+        assert_eq!(session.eval("count = count + 1"), Ok(Value::Integer(1)));
+        assert_eq!(
+            session.js(""),
+            Ok("const count = $state(0);\ncount.value = count.value + 1 | 0;\n".to_string())
+        );
+    }
+
+    #[test]
+    fn inputs_with_errors_are_not_kept() {
+        let mut session = Session::default();
+
+        assert!(session.eval("state a = ").is_err());
+        assert!(session.eval("let a = 1").is_ok());
+        assert_eq!(session.js(""), Ok("let a = 1;\n".to_string()));
+    }
+
+    #[test]
+    fn multi_line_function() {
+        let mut session = Session::default();
+
+        assert_eq!(
+            session.eval("func add(a: i32, b: i32) {\n  a + b\n}\n"),
+            Ok(Value::Unit)
+        );
+        assert_eq!(session.eval("add(1, 2)"), Ok(Value::Integer(3)));
+        assert_eq!(
+            session.js(""),
+            Ok("function add(a, b) {\nreturn a + b | 0;\n}\nadd(1, 2);\n".to_string())
+        );
+    }
+
+    #[test]
+    fn runtime_errors_are_reported() {
+        let mut session = Session::default();
+        session.eval("let a = 0").unwrap();
+
+        let diagnostics = session.eval("1 / a").unwrap_err();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, diagnostics::Code::DIVISION_BY_ZERO);
+        assert_eq!(
+            diagnostics[0].labels[0].range,
+            TextRange::new(4.into(), 5.into())
+        );
+    }
+
+    #[test]
+    fn stack_overflow_points_at_the_call_in_the_input() {
+        let mut session = Session::default();
+        session.eval("func f(n) {\n  f(n + 1)\n}").unwrap();
+
+        let diagnostics = session.eval("let a = 1\nf(a)").unwrap_err();
+        assert_eq!(diagnostics[0].code, diagnostics::Code::STACK_OVERFLOW);
+        assert_eq!(
+            diagnostics[0].labels[0].range,
+            TextRange::new(10.into(), 14.into())
+        );
+    }
+
+    #[test]
+    fn js_command_does_not_change_session() {
+        let mut session = Session::default();
+        session.eval("state a = 0").unwrap();
+
+        assert_eq!(session.js("a = 1"), Ok("a.value = 1;\n".to_string()));
+        assert_eq!(session.js(""), Ok("const a = $state(0);\n".to_string()));
+    }
+
+    #[test]
+    fn tree_of_session() {
+        let mut session = Session::default();
+        session.eval("let a = 1").unwrap();
+        session.eval("a").unwrap();
+
+        assert_eq!(session.tree(""), parse("let a = 1\na\n").debug_tree());
+    }
+}
@@ -1,43 +1,396 @@
-use std::ops::Index;
+mod mangle;
+mod source_map;
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 use ast_lowering::Database;
-use hir::{BinaryOp, Expr, Literal, Stmt, UnaryOp};
+use hir::{BinaryOp, Expr, Literal, MatchArm, Param, Pat, Stmt, TypeRef, UnaryOp};
+use la_arena::Idx;
+use mangle::{escape, is_reserved, mangle, numbered};
+use text_size::{TextRange, TextSize};
+
+pub use source_map::source_map;
 
 pub fn generate(statements: Vec<Stmt>, database: Database) -> String {
-    let mut generator = Generator {
-        source: String::new(),
-    };
+    Generator::new().generate(&statements, &database)
+}
 
-    for statement in statements {
-        generator.generate_statement(&statement, &database);
-    }
+/// A name defined at the top level of the generated code, which is what the
+/// JS that loads it sees
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Export {
+    /// The name is visible to JS as it is spelled
+    Preserved { name: String },
+    /// The name is a JS reserved word or a global the generated code relies
+    /// on, so JS only sees it as `js_name`
+    Conflict { name: String, js_name: String },
+}
 
-    generator.source
+/// The parameter that holds the scrutinee inside of a generated match
+const SCRUTINEE: &str = "$m";
+
+/// Links the start of the code generated for an expression to the
+/// expression in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mapping {
+    /// Offset into the generated code
+    pub generated: TextSize,
+    pub source: TextRange,
 }
 
+/// Integers are 32 bit and wrap around, like they do in the interpreter.
+/// Arithmetic on them is followed by `| 0`, which JS evaluates by wrapping
+/// the result around to 32 bits, except for multiplication, whose exact
+/// result can be too large for a JS number and that uses `Math.imul`
+/// instead. `+` also joins strings, so an addition whose operands are not
+/// known to be integers or strings checks which they are when it runs.
+///
+/// State variables are emitted as cells created by the runtime's `$state`.
+/// Every read and write goes through the cell's `value` property, which is
+/// what lets the runtime notice writes, including writes made from closures
+/// that captured the state variable.
+///
+/// JS doesn't allow a name to be declared twice in the same scope, so a
+/// definition that shadows another one gets a new name, like `a$1`. Inside
+/// of functions this also goes for definitions that shadow a variable from
+/// outside, whose value may refer to the variable it shadows.
+#[derive(Default, Clone)]
 pub struct Generator {
     source: String,
+    /// The names of the state variables that are currently in scope
+    states: HashSet<String>,
+    /// What JS calls the variables that are currently in scope, for the ones
+    /// defined by a definition
+    js_names: HashMap<String, String>,
+    /// The JS names declared in the current function or at the top level,
+    /// and in the functions around it
+    declared: HashSet<String>,
+    /// The types of the variables in scope that are known, from their
+    /// annotation or their value
+    types: HashMap<String, Type>,
+    mappings: Vec<Mapping>,
 }
 
 impl Generator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generates the code for `statements`. The generator remembers the
+    /// state variables they define, so that code generated by later calls
+    /// can refer to them, like a REPL does with its previous inputs.
+    pub fn generate(&mut self, statements: &[Stmt], database: &Database) -> String {
+        self.generate_with_mappings(statements, database).0
+    }
+
+    /// Like `generate`, but also returns where the generated expressions came
+    /// from, ordered by their position in the generated code
+    pub fn generate_with_mappings(
+        &mut self,
+        statements: &[Stmt],
+        database: &Database,
+    ) -> (String, Vec<Mapping>) {
+        for statement in statements {
+            self.generate_statement(statement, database);
+        }
+
+        let mut mappings = std::mem::take(&mut self.mappings);
+        mappings.sort_by_key(|mapping| mapping.generated);
+        (std::mem::take(&mut self.source), mappings)
+    }
+
+    /// The names `statements` define at the top level, in the order they are
+    /// first defined. This is meant to be called after generating them.
+    pub fn exports(&self, statements: &[Stmt]) -> Vec<Export> {
+        let mut seen = HashSet::new();
+
+        statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Stmt::VariableDef { name, .. }
+                | Stmt::StateDef { name, .. }
+                | Stmt::FunctionDef { name, .. } => Some(name),
+                Stmt::StructDef(_) | Stmt::EnumDef(_) | Stmt::Expr(_) => None,
+            })
+            .filter(|name| seen.insert(name.as_str()))
+            .map(|name| {
+                if is_reserved(name) {
+                    Export::Conflict {
+                        name: name.to_string(),
+                        js_name: self.js_name(name).into_owned(),
+                    }
+                } else {
+                    Export::Preserved {
+                        name: name.to_string(),
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The `export` statement for `exports`, which exports the last
+    /// definition of every name as the name is spelled in the source, also
+    /// when JS knows it by another name. Without any exports there is nothing
+    /// to generate.
+    pub fn export_statement(&self, exports: &[Export]) -> String {
+        if exports.is_empty() {
+            return String::new();
+        }
+
+        let specifiers: Vec<_> = exports
+            .iter()
+            .map(|export| {
+                let name = match export {
+                    Export::Preserved { name } | Export::Conflict { name, .. } => name,
+                };
+
+                // Export names can be reserved words
+                match (self.js_name(name), escape(name)) {
+                    (js_name, name) if js_name == name => name.into_owned(),
+                    (js_name, name) => format!("{} as {}", js_name, name),
+                }
+            })
+            .collect();
+
+        format!("export {{ {} }};\n", specifiers.join(", "))
+    }
+
+    /// What JS calls the variable `name` here
+    fn js_name<'a>(&'a self, name: &'a str) -> Cow<'a, str> {
+        match self.js_names.get(name) {
+            Some(js_name) => Cow::Borrowed(js_name),
+            None => mangle(name),
+        }
+    }
+
+    /// Declares a variable called `name` in the current scope and returns
+    /// what JS calls it. References keep going to the variable it shadows
+    /// until it is bound.
+    fn declare(&mut self, name: &str) -> String {
+        let mangled = mangle(name);
+        let mut js_name = mangled.to_string();
+        let mut number = 0;
+        while self.declared.contains(&js_name) {
+            number += 1;
+            js_name = numbered(&mangled, number);
+        }
+
+        self.declared.insert(js_name.clone());
+        js_name
+    }
+
+    fn bind(&mut self, name: &str, js_name: String) {
+        self.js_names.insert(name.to_string(), js_name);
+    }
+
+    fn set_type(&mut self, name: &str, ty: Option<Type>) {
+        match ty {
+            Some(ty) => self.types.insert(name.to_string(), ty),
+            None => self.types.remove(name),
+        };
+    }
+
+    /// Makes `names` refer to parameters or bindings that are called by
+    /// their mangled name, until what they shadow is restored
+    fn shadow<'n>(&mut self, names: impl Iterator<Item = &'n str>) -> Shadowed {
+        let mut shadowed = Shadowed::default();
+        for name in names {
+            shadowed.states.extend(self.states.take(name));
+            shadowed.js_names.extend(self.js_names.remove_entry(name));
+            shadowed.types.extend(self.types.remove_entry(name));
+        }
+
+        shadowed
+    }
+
+    fn restore(&mut self, shadowed: Shadowed) {
+        self.states.extend(shadowed.states);
+        self.js_names.extend(shadowed.js_names);
+        self.types.extend(shadowed.types);
+    }
+
+    /// Maps the code generated from here on to where `expression` is in the
+    /// source
+    fn map_to(&mut self, expression: Idx<Expr>, database: &Database) {
+        self.map_to_range(database.expr_range(expression));
+    }
+
+    /// Maps the code generated from here on to the start of the `let` or
+    /// `state` definition whose value is `value`, so that a breakpoint on
+    /// the definition stops before its value is evaluated
+    fn map_to_def(&mut self, value: Idx<Expr>, database: &Database) {
+        self.map_to_range(database.def_range(value));
+    }
+
+    fn map_to_range(&mut self, range: Option<TextRange>) {
+        if let Some(range) = range {
+            self.mappings.push(Mapping {
+                generated: TextSize::of(self.source.as_str()),
+                source: range,
+            });
+        }
+    }
+
     fn generate_statement(&mut self, statement: &Stmt, database: &Database) {
         match statement {
-            Stmt::VariableDef { name, value } => {
-                self.source.push_str(&format!("let {} = ", name));
-                self.generate_expression(value, database);
+            Stmt::VariableDef {
+                name, ty, value, ..
+            } => {
+                let ty =
+                    annotated_type(*ty, database).or_else(|| self.known_type(*value, database));
+                let js_name = self.declare(name);
+                self.map_to_def(*value, database);
+                self.source.push_str(&format!("let {} = ", js_name));
+                self.generate_expression(*value, database);
+                self.source.push_str(";\n");
+                self.bind(name, js_name);
+                self.set_type(name, ty);
+                self.states.remove(name.as_str());
+            }
+            Stmt::StateDef {
+                name, ty, value, ..
+            } => {
+                let ty =
+                    annotated_type(*ty, database).or_else(|| self.known_type(*value, database));
+                let js_name = self.declare(name);
+                self.map_to_def(*value, database);
+                self.source
+                    .push_str(&format!("const {} = $state(", js_name));
+                self.generate_expression(*value, database);
+                self.source.push_str(");\n");
+                self.bind(name, js_name);
+                self.set_type(name, ty);
+                self.states.insert(name.to_string());
+            }
+            Stmt::FunctionDef {
+                name, params, body, ..
+            } => {
+                // The function can call itself
+                let js_name = self.declare(name);
+                self.bind(name, js_name.clone());
+                self.types.remove(name.as_str());
+                self.states.remove(name.as_str());
+
+                self.source.push_str(&format!(
+                    "function {}({}) {{\n",
+                    js_name,
+                    param_list(params)
+                ));
+
+                // Anything defined in the body is not visible after it
+                let states = self.states.clone();
+                let js_names = self.js_names.clone();
+                let declared = self.declared.clone();
+                let types = self.types.clone();
+                for param in params {
+                    self.states.remove(param.name.as_str());
+                    self.js_names.remove(param.name.as_str());
+                    self.declared.insert(mangle(&param.name).into_owned());
+                    self.set_type(&param.name, annotated_type(param.ty, database));
+                }
+
+                // The trailing expression is the function's value
+                let (statements, value) = match body.split_last() {
+                    Some((Stmt::Expr(value), statements)) => (statements, Some(*value)),
+                    _ => (body.as_slice(), None),
+                };
+
+                for statement in statements {
+                    self.generate_statement(statement, database);
+                }
+
+                if let Some(value) = value {
+                    self.source.push_str("return ");
+                    self.generate_expression(value, database);
+                    self.source.push_str(";\n");
+                }
+
+                self.states = states;
+                self.js_names = js_names;
+                self.declared = declared;
+                self.types = types;
+                self.source.push_str("}\n");
+            }
+            // Records and enum variants are plain objects, so declaring them
+            // does not produce any code
+            Stmt::StructDef(_) | Stmt::EnumDef(_) => {}
+            Stmt::Expr(expr) => {
+                self.generate_unambiguous_expression(*expr, database);
+                self.source.push_str(";\n");
+            }
+        }
+    }
+
+    /// Generates `expression`, in parentheses if it binds looser than `min`
+    fn generate_operand(&mut self, expression: Idx<Expr>, database: &Database, min: Precedence) {
+        if self.precedence(expression, database) < min {
+            self.source.push('(');
+            self.generate_expression(expression, database);
+            self.source.push(')');
+        } else {
+            self.generate_expression(expression, database);
+        }
+    }
+
+    /// Generates `expression` where a leading `{` would start a block rather
+    /// than an object, like at the start of a statement or of an arrow
+    /// function's body
+    fn generate_unambiguous_expression(&mut self, expression: Idx<Expr>, database: &Database) {
+        let start = self.source.len();
+        self.generate_expression(expression, database);
+        self.parenthesize_if_starts_with(start, '{');
+    }
+
+    fn parenthesize_if_starts_with(&mut self, start: usize, c: char) {
+        if self.source[start..].starts_with(c) {
+            self.source.insert(start, '(');
+            self.source.push(')');
+
+            // Everything generated after `start` moved along
+            let start = TextSize::try_from(start).unwrap();
+            for mapping in &mut self.mappings {
+                if mapping.generated > start {
+                    mapping.generated += TextSize::of('(');
+                }
             }
-            Stmt::Expr(expr) => self.generate_expression(expr, database),
         }
     }
 
-    fn generate_expression(&mut self, expression: &Expr, database: &Database) {
-        match &expression {
+    fn generate_expression(&mut self, expression: Idx<Expr>, database: &Database) {
+        self.map_to(expression, database);
+
+        match &database.exprs()[expression] {
             Expr::Missing => self.source.push_str(""),
+            Expr::Binary {
+                op: BinaryOp::Mul,
+                lhs,
+                rhs,
+            } => {
+                self.source.push_str("Math.imul(");
+                self.generate_operand(*lhs, database, Precedence::Assignment);
+                self.source.push_str(", ");
+                self.generate_operand(*rhs, database, Precedence::Assignment);
+                self.source.push(')');
+            }
+            Expr::Binary {
+                op: BinaryOp::Add,
+                lhs,
+                rhs,
+            } if self.known_type(expression, database).is_none() => {
+                // Whether this adds integers or joins strings is only known
+                // once the operands are
+                self.source
+                    .push_str("((a, b) => typeof a === \"string\" ? a + b : a + b | 0)(");
+                self.generate_operand(*lhs, database, Precedence::Assignment);
+                self.source.push_str(", ");
+                self.generate_operand(*rhs, database, Precedence::Assignment);
+                self.source.push(')');
+            }
             Expr::Binary { op, lhs, rhs } => {
-                let lhs = database.exprs().index(*lhs);
-                let rhs = database.exprs().index(*rhs);
+                let precedence = Precedence::of_binary_op(op);
 
-                self.generate_expression(lhs, database);
+                self.generate_operand(*lhs, database, precedence);
                 self.source.push_str(" ");
                 match op {
                     BinaryOp::Add => self.source.push_str("+"),
@@ -55,7 +408,15 @@ impl Generator {
                     BinaryOp::Ge => self.source.push_str(">="),
                 }
                 self.source.push_str(" ");
-                self.generate_expression(rhs, database);
+                // Binary operators are left associative, so an operand on the
+                // right needs parentheses even at the same precedence
+                self.generate_operand(*rhs, database, precedence.next());
+
+                // Division also rounds towards zero this way, like the
+                // interpreter does
+                if self.wraps_around(expression, database) {
+                    self.source.push_str(" | 0");
+                }
             }
             Expr::Unary { op, expr } => {
                 match op {
@@ -63,10 +424,94 @@ impl Generator {
                     UnaryOp::Not => self.source.push_str("!"),
                 }
 
-                let expr = database.exprs().index(*expr);
-                self.generate_expression(expr, database);
+                let start = self.source.len();
+                self.generate_operand(*expr, database, Precedence::Prefix);
+
+                // `-` followed by another `-` would be read as `--`
+                if *op == UnaryOp::Neg {
+                    self.parenthesize_if_starts_with(start, '-');
+                }
+
+                if self.wraps_around(expression, database) {
+                    self.source.push_str(" | 0");
+                }
+            }
+            Expr::Assign { target, value } => {
+                self.generate_expression(*target, database);
+                self.source.push_str(" = ");
+                self.generate_operand(*value, database, Precedence::Assignment);
+            }
+            Expr::Call { callee, args } => {
+                self.generate_operand(*callee, database, Precedence::Call);
+
+                self.source.push('(');
+                for (idx, arg) in args.iter().enumerate() {
+                    if idx > 0 {
+                        self.source.push_str(", ");
+                    }
+
+                    self.generate_operand(*arg, database, Precedence::Assignment);
+                }
+                self.source.push(')');
+            }
+            Expr::Closure { params, body, .. } => {
+                self.source
+                    .push_str(&format!("({}) => ", param_list(params)));
+
+                let shadowed = self.shadow(params.iter().map(|param| param.name.as_str()));
+                for param in params {
+                    self.set_type(&param.name, annotated_type(param.ty, database));
+                }
+                self.generate_unambiguous_expression(*body, database);
+                self.restore(shadowed);
+            }
+            Expr::VariableRef { var } => {
+                let js_name = self.js_name(var).into_owned();
+                self.source.push_str(&js_name);
+                if self.states.contains(var.as_str()) {
+                    self.source.push_str(".value");
+                }
+            }
+            Expr::Record { fields, .. } => {
+                self.source.push('{');
+                for (idx, (name, value)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        self.source.push(',');
+                    }
+
+                    self.source.push_str(&format!(" {}: ", escape(name)));
+                    self.generate_operand(*value, database, Precedence::Assignment);
+                }
+                self.source.push_str(" }");
+            }
+            Expr::Variant { variant, args, .. } => {
+                self.source.push_str(&format!("{{ tag: \"{}\"", variant));
+
+                if !args.is_empty() {
+                    self.source.push_str(", values: [");
+                    for (idx, arg) in args.iter().enumerate() {
+                        if idx > 0 {
+                            self.source.push_str(", ");
+                        }
+
+                        self.generate_operand(*arg, database, Precedence::Assignment);
+                    }
+                    self.source.push(']');
+                }
+
+                self.source.push_str(" }");
+            }
+            Expr::Match { scrutinee, arms } => {
+                // A match is an arrow function that checks the arms in order
+                // and is immediately called with the scrutinee
+                self.source.push_str(&format!("(({}) => {{", SCRUTINEE));
+                for arm in arms {
+                    self.generate_match_arm(arm, database);
+                }
+                self.source.push_str(" })(");
+                self.generate_operand(*scrutinee, database, Precedence::Assignment);
+                self.source.push(')');
             }
-            Expr::VariableRef { var } => self.source.push_str(var),
             Expr::Literal(kind) => match kind {
                 Literal::Integer { value } => self.source.push_str(&value.unwrap().to_string()),
                 Literal::String { value } => self.source.push_str(value),
@@ -74,4 +519,863 @@ impl Generator {
             },
         }
     }
+
+    /// The type of `expression`, if it is known before the code runs
+    fn known_type(&self, expression: Idx<Expr>, database: &Database) -> Option<Type> {
+        match &database.exprs()[expression] {
+            Expr::Literal(Literal::Integer { .. }) => Some(Type::Integer),
+            Expr::Literal(Literal::String { .. }) => Some(Type::String),
+            Expr::VariableRef { var } => self.types.get(var.as_str()).copied(),
+            Expr::Unary {
+                op: UnaryOp::Neg, ..
+            } => Some(Type::Integer),
+            // Anything else can only be added to an operand of the same type
+            Expr::Binary {
+                op: BinaryOp::Add,
+                lhs,
+                rhs,
+            } => self
+                .known_type(*lhs, database)
+                .or_else(|| self.known_type(*rhs, database)),
+            Expr::Binary {
+                op: BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem,
+                ..
+            } => Some(Type::Integer),
+            _ => None,
+        }
+    }
+
+    /// Whether the JS for `expression` is integer arithmetic followed by
+    /// `| 0`. Negative literals are already in range.
+    fn wraps_around(&self, expression: Idx<Expr>, database: &Database) -> bool {
+        match &database.exprs()[expression] {
+            Expr::Binary {
+                op: BinaryOp::Add, ..
+            } => self.known_type(expression, database) == Some(Type::Integer),
+            Expr::Binary { op, .. } => {
+                matches!(op, BinaryOp::Sub | BinaryOp::Div | BinaryOp::Rem)
+            }
+            Expr::Unary {
+                op: UnaryOp::Neg,
+                expr,
+            } => !matches!(
+                database.exprs()[*expr],
+                Expr::Literal(Literal::Integer { .. })
+            ),
+            _ => false,
+        }
+    }
+
+    /// How tightly the JS generated for `expression` binds
+    fn precedence(&self, expression: Idx<Expr>, database: &Database) -> Precedence {
+        if self.wraps_around(expression, database) {
+            return Precedence::BitwiseOr;
+        }
+
+        match &database.exprs()[expression] {
+            Expr::Binary {
+                op: BinaryOp::Mul, ..
+            } => Precedence::Call,
+            Expr::Binary {
+                op: BinaryOp::Add, ..
+            } if self.known_type(expression, database).is_none() => Precedence::Call,
+            Expr::Binary { op, .. } => Precedence::of_binary_op(op),
+            Expr::Unary { .. } => Precedence::Prefix,
+            Expr::Assign { .. } | Expr::Closure { .. } => Precedence::Assignment,
+            Expr::Call { .. } | Expr::Match { .. } => Precedence::Call,
+            Expr::Missing
+            | Expr::VariableRef { .. }
+            | Expr::Record { .. }
+            | Expr::Variant { .. }
+            | Expr::Literal(_) => Precedence::Primary,
+        }
+    }
+
+    fn generate_match_arm(&mut self, arm: &MatchArm, database: &Database) {
+        let mut conditions = Vec::new();
+        let mut bindings = Vec::new();
+        collect_pat(
+            &arm.pat,
+            SCRUTINEE.to_string(),
+            &mut conditions,
+            &mut bindings,
+        );
+
+        if conditions.is_empty() {
+            self.source.push_str(" {");
+        } else {
+            self.source
+                .push_str(&format!(" if ({}) {{", conditions.join(" && ")));
+        }
+
+        for (name, access) in &bindings {
+            self.source
+                .push_str(&format!(" const {} = {};", mangle(name), access));
+        }
+
+        let shadowed = self.shadow(bindings.iter().map(|(name, _)| name.as_str()));
+        self.source.push_str(" return ");
+        self.generate_expression(arm.expr, database);
+        self.source.push_str("; }");
+        self.restore(shadowed);
+    }
+}
+
+/// The variables that parameters or match bindings shadow
+#[derive(Default)]
+struct Shadowed {
+    states: Vec<String>,
+    js_names: Vec<(String, String)>,
+    types: Vec<(String, Type)>,
+}
+
+fn param_list(params: &[Param]) -> String {
+    params
+        .iter()
+        .map(|param| mangle(&param.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The types that decide whether `+` adds or joins its operands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Integer,
+    String,
+}
+
+/// The type an annotation stands for, if it is an integer or a string
+fn annotated_type(ty: Option<Idx<TypeRef>>, database: &Database) -> Option<Type> {
+    match &database.type_refs()[ty?] {
+        TypeRef::Path { name } if name == "i32" => Some(Type::Integer),
+        TypeRef::Path { name } if name == "String" => Some(Type::String),
+        _ => None,
+    }
+}
+
+/// How tightly the JS generated for an expression binds, from loosest to
+/// tightest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    /// `=` and arrow functions
+    Assignment,
+    Or,
+    And,
+    /// `|`, which wraps integer arithmetic around
+    BitwiseOr,
+    Equality,
+    Relational,
+    Additive,
+    Multiplicative,
+    Prefix,
+    /// Calls, which includes multiplication through `Math.imul`, and
+    /// matches and additions of unknown types through the functions they are
+    /// generated as
+    Call,
+    /// Names, literals and objects
+    Primary,
+}
+
+impl Precedence {
+    /// The precedence of the operator, which for arithmetic is the
+    /// precedence of its operands in front of `| 0`
+    fn of_binary_op(op: &BinaryOp) -> Self {
+        match op {
+            BinaryOp::Or => Self::Or,
+            BinaryOp::And => Self::And,
+            BinaryOp::Eq | BinaryOp::Ne => Self::Equality,
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => Self::Relational,
+            BinaryOp::Add | BinaryOp::Sub => Self::Additive,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => Self::Multiplicative,
+        }
+    }
+
+    /// The next tighter precedence
+    fn next(self) -> Self {
+        match self {
+            Self::Assignment => Self::Or,
+            Self::Or => Self::And,
+            Self::And => Self::BitwiseOr,
+            Self::BitwiseOr => Self::Equality,
+            Self::Equality => Self::Relational,
+            Self::Relational => Self::Additive,
+            Self::Additive => Self::Multiplicative,
+            Self::Multiplicative => Self::Prefix,
+            Self::Prefix => Self::Call,
+            Self::Call | Self::Primary => Self::Primary,
+        }
+    }
+}
+
+/// Collects the checks `pat` makes against the value at `access`, and the
+/// names it binds along with where their values are found
+fn collect_pat(
+    pat: &Pat,
+    access: String,
+    conditions: &mut Vec<String>,
+    bindings: &mut Vec<(String, String)>,
+) {
+    match pat {
+        Pat::Wildcard => {}
+        Pat::Bind { name } => bindings.push((name.to_string(), access)),
+        Pat::Variant {
+            variant, fields, ..
+        } => {
+            conditions.push(format!("{}.tag === \"{}\"", access, variant));
+            for (idx, field) in fields.iter().enumerate() {
+                collect_pat(
+                    field,
+                    format!("{}.values[{}]", access, idx),
+                    conditions,
+                    bindings,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(input: &str, expected_js: &str) {
+        let parse = parser::parse(input);
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (database, stmts) = ast_lowering::lower_root(root);
+
+        assert_eq!(generate(stmts, database), expected_js);
+    }
+
+    #[test]
+    fn generate_variable_def() {
+        check("let a = 1", "let a = 1;\n");
+    }
+
+    #[test]
+    fn generate_function() {
+        check(
+            "func add(a: i32, b: i32) -> i32 {\n  a + b\n}",
+            "function add(a, b) {\nreturn a + b | 0;\n}\n",
+        );
+    }
+
+    #[test]
+    fn function_body_does_not_leak_shadowed_state() {
+        check(
+            "state a = 0\nfunc f() {\n  let a = 1\n}\na",
+            "const a = $state(0);\nfunction f() {\nlet a$1 = 1;\n}\na.value;\n",
+        );
+    }
+
+    #[test]
+    fn rename_definitions_that_shadow_another_one() {
+        check(
+            "state a = 1\nlet a = a + 1\nlet a = 3\na",
+            "const a = $state(1);\nlet a$1 = a.value + 1 | 0;\nlet a$2 = 3;\na$2;\n",
+        );
+        check(
+            "func f() { 1 }\nlet g = f\nfunc f() { f }",
+            "function f() {\nreturn 1;\n}\nlet g = f;\nfunction f$1() {\nreturn f$1;\n}\n",
+        );
+    }
+
+    #[test]
+    fn rename_definitions_that_shadow_a_variable_from_outside_the_function() {
+        check(
+            "let a = 1\nfunc f(b) {\n  let a = a + b\n  let b = a\n  b\n}\na",
+            "let a = 1;\nfunction f(b) {\nlet a$1 = a + b | 0;\nlet b$1 = a$1;\nreturn b$1;\n}\na;\n",
+        );
+    }
+
+    #[test]
+    fn params_and_bindings_shadow_renamed_variables() {
+        check(
+            "let a = 1\nlet a = 2\nlet f = |a| a\nmatch a { a => a }\na",
+            "let a = 1;\nlet a$1 = 2;\nlet f = (a) => a;\n\
+             (($m) => { { const a = $m; return a; } })(a$1);\na$1;\n",
+        );
+    }
+
+    #[test]
+    fn shadowing_names_do_not_collide_with_numbered_names() {
+        check(
+            "let a$1 = 1\nlet a = 2\nlet a = 3\na$1",
+            "let a$1$ = 1;\nlet a = 2;\nlet a$1 = 3;\na$1$;\n",
+        );
+    }
+
+    #[test]
+    fn generate_call() {
+        check("add(1, 2)", "add(1, 2);\n");
+    }
+
+    #[test]
+    fn generate_closure() {
+        check("let inc = |x| x + 1", "let inc = (x) => x + 1 | 0;\n");
+    }
+
+    #[test]
+    fn generate_state_def() {
+        check(
+            "state count = 0\ncount",
+            "const count = $state(0);\ncount.value;\n",
+        );
+    }
+
+    #[test]
+    fn closures_write_to_state_through_its_cell() {
+        check(
+            "state count = 0\nlet increment = || count = count + 1",
+            "const count = $state(0);\nlet increment = () => count.value = count.value + 1 | 0;\n",
+        );
+    }
+
+    #[test]
+    fn closure_params_shadow_state() {
+        check(
+            "state count = 0\nlet f = |count| count\ncount",
+            "const count = $state(0);\nlet f = (count) => count;\ncount.value;\n",
+        );
+    }
+
+    #[test]
+    fn escape_unicode_names() {
+        check(
+            "state größe = 1\nfunc zählen(ä) { |ö| größe + ä + ö }\nlet 名前 = P { ß: 1 }",
+            "const gr\\u00f6\\u00dfe = $state(1);\n\
+             function z\\u00e4hlen(\\u00e4) {\n\
+             return (\\u00f6) => (gr\\u00f6\\u00dfe.value + \\u00e4 | 0) + \\u00f6 | 0;\n}\n\
+             let \\u540d\\u524d = { \\u00df: 1 };\n",
+        );
+    }
+
+    #[test]
+    fn names_in_different_normalization_forms_are_the_same() {
+        check(
+            "let caf\u{e9} = 1\ncafe\u{301}",
+            "let caf\\u00e9 = 1;\ncaf\\u00e9;\n",
+        );
+    }
+
+    #[test]
+    fn rename_reserved_words() {
+        check(
+            "let class = 1\nfunc new(this) { this + class }\nnew(class)",
+            "let class$ = 1;\nfunction new$(this$) {\nreturn this$ + class$ | 0;\n}\nnew$(class$);\n",
+        );
+    }
+
+    #[test]
+    fn renamed_names_do_not_shadow_the_globals_they_avoid() {
+        check(
+            "let $state = 2\nstate Math = $state * 2\nmatch Math { $m => $m }",
+            "let $state$ = 2;\nconst Math$ = $state(Math.imul($state$, 2));\n\
+             (($m) => { { const $m$ = $m; return $m$; } })(Math$.value);\n",
+        );
+    }
+
+    #[test]
+    fn record_fields_keep_reserved_words() {
+        check(
+            "let a = Tag { class: 1, default: 2 }",
+            "let a = { class: 1, default: 2 };\n",
+        );
+    }
+
+    #[test]
+    fn list_exports() {
+        let parse = parser::parse(
+            "let a = 1\nstate class = 2\nstruct S { f: i32 }\nfunc f() {\n  let inner = 3\n}\nlet a = 4\n$m",
+        );
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (database, stmts) = ast_lowering::lower_root(root);
+        let mut generator = Generator::new();
+        generator.generate(&stmts, &database);
+
+        let exports = generator.exports(&stmts);
+        assert_eq!(
+            exports,
+            [
+                Export::Preserved {
+                    name: "a".to_string()
+                },
+                Export::Conflict {
+                    name: "class".to_string(),
+                    js_name: "class$".to_string()
+                },
+                Export::Preserved {
+                    name: "f".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            generator.export_statement(&exports),
+            "export { a$1 as a, class$ as class, f };\n"
+        );
+    }
+
+    #[test]
+    fn export_names_as_they_are_spelled() {
+        let exports = [
+            Export::Preserved {
+                name: "größe".to_string(),
+            },
+            Export::Conflict {
+                name: "class".to_string(),
+                js_name: "class$".to_string(),
+            },
+        ];
+
+        let generator = Generator::new();
+        assert_eq!(
+            generator.export_statement(&exports),
+            "export { gr\\u00f6\\u00dfe, class$ as class };\n"
+        );
+        assert_eq!(generator.export_statement(&[]), "");
+    }
+
+    #[test]
+    fn declarations_generate_nothing() {
+        check("struct User { name: String }\nenum Status { Loading }", "");
+    }
+
+    #[test]
+    fn generate_record() {
+        check(
+            "let u = User { name: \"a\", age: 1 }",
+            "let u = { name: \"a\", age: 1 };\n",
+        );
+    }
+
+    #[test]
+    fn generate_variants() {
+        check(
+            "Status::Loading\nStatus::Loaded(user)",
+            "({ tag: \"Loading\" });\n({ tag: \"Loaded\", values: [user] });\n",
+        );
+    }
+
+    #[test]
+    fn generate_match() {
+        check(
+            "let n = match s { Status::Loading => 0, Status::Loaded(u) => u, _ => 1 }",
+            "let n = (($m) => { if ($m.tag === \"Loading\") { return 0; } \
+             if ($m.tag === \"Loaded\") { const u = $m.values[0]; return u; } \
+             { return 1; } })(s);\n",
+        );
+    }
+
+    #[test]
+    fn generate_nested_match() {
+        check(
+            "match o { Option::Some(Status::Failed(e)) => e }",
+            "(($m) => { if ($m.tag === \"Some\" && $m.values[0].tag === \"Failed\") { \
+             const e = $m.values[0].values[0]; return e; } })(o);\n",
+        );
+    }
+
+    #[test]
+    fn match_bindings_shadow_state() {
+        check(
+            "state s = 0\nmatch s { s => s }",
+            "const s = $state(0);\n(($m) => { { const s = $m; return s; } })(s.value);\n",
+        );
+    }
+
+    #[test]
+    fn parenthesize_looser_operands() {
+        check("(a == b) < 3", "(a == b) < 3;\n");
+        check("!(a && b) || c", "!(a && b) || c;\n");
+        check("a == (b = 1)", "a == (b = 1);\n");
+    }
+
+    #[test]
+    fn omit_parentheses_that_are_not_needed() {
+        check("(1 < 2) == (3 < 4)", "1 < 2 == 3 < 4;\n");
+        check("a || (b && c)", "a || b && c;\n");
+        check("(f(a))", "f(a);\n");
+    }
+
+    #[test]
+    fn parenthesize_right_operand_of_same_precedence() {
+        check("(a < b) < c", "a < b < c;\n");
+        check("a < (b < c)", "a < (b < c);\n");
+        check("a == (b == c)", "a == (b == c);\n");
+    }
+
+    #[test]
+    fn nested_negation_is_not_decrement() {
+        check("- -1", "-(-1) | 0;\n");
+        check("- -a", "-(-a | 0) | 0;\n");
+    }
+
+    #[test]
+    fn integer_arithmetic_wraps_around() {
+        check("a - b - c", "(a - b | 0) - c | 0;\n");
+        check("7 / 2", "7 / 2 | 0;\n");
+        check("a % b", "a % b | 0;\n");
+        check("-a", "-a | 0;\n");
+        check("-2147483648", "-2147483648;\n");
+        check("a - b < c", "(a - b | 0) < c;\n");
+    }
+
+    #[test]
+    fn multiplication_uses_imul() {
+        check("a * b", "Math.imul(a, b);\n");
+        check("(1 + 2) * 3", "Math.imul(1 + 2 | 0, 3);\n");
+        check(
+            "a / (b * c) * d",
+            "Math.imul(a / Math.imul(b, c) | 0, d);\n",
+        );
+    }
+
+    #[test]
+    fn add_or_join_depending_on_the_operands() {
+        check("1 + a", "1 + a | 0;\n");
+        check("a + \"b\"", "a + \"b\";\n");
+        check(
+            "a + b",
+            "((a, b) => typeof a === \"string\" ? a + b : a + b | 0)(a, b);\n",
+        );
+        check(
+            "-(a + b)",
+            "-((a, b) => typeof a === \"string\" ? a + b : a + b | 0)(a, b) | 0;\n",
+        );
+    }
+
+    #[test]
+    fn variables_keep_the_type_of_their_annotation_or_value() {
+        check(
+            "func f(a: String, b: i32, c) {\n  |d| a + c + (b + d)\n}",
+            "function f(a, b, c) {\nreturn (d) => a + c + (b + d | 0);\n}\n",
+        );
+        check(
+            "let a = \"a\"\nstate b = -1\nlet c = a + b + b",
+            "let a = \"a\";\nconst b = $state(-1);\nlet c = a + b.value + b.value;\n",
+        );
+    }
+
+    #[test]
+    fn params_and_redefinitions_forget_the_type() {
+        check(
+            "let a = 1\nlet f = |a| a + a\nmatch x { a => a + a }\nfunc g(a) { a + a }\nlet a = x\na + a",
+            "let a = 1;\n\
+             let f = (a) => ((a, b) => typeof a === \"string\" ? a + b : a + b | 0)(a, a);\n\
+             (($m) => { { const a = $m; return ((a, b) => typeof a === \"string\" ? a + b : a + b | 0)(a, a); } })(x);\n\
+             function g(a) {\nreturn ((a, b) => typeof a === \"string\" ? a + b : a + b | 0)(a, a);\n}\n\
+             let a$1 = x;\n((a, b) => typeof a === \"string\" ? a + b : a + b | 0)(a$1, a$1);\n",
+        );
+    }
+
+    #[test]
+    fn parenthesize_callee() {
+        check("(|x| x)(1)", "((x) => x)(1);\n");
+        check("f(1)(2)", "f(1)(2);\n");
+    }
+
+    #[test]
+    fn closure_returning_object() {
+        check(
+            "let f = || User { name: \"a\" }",
+            "let f = () => ({ name: \"a\" });\n",
+        );
+    }
+
+    mod matches_interpreter {
+        use super::*;
+        use la_arena::Idx;
+        use proptest::prelude::*;
+        use std::process::Command;
+
+        /// An expression that evaluates to an integer
+        #[derive(Debug, Clone)]
+        enum IntTree {
+            Literal(u64),
+            /// One of `NAMES`, if it is defined where the tree is used
+            Var(usize),
+            Neg(Box<IntTree>),
+            Binary(BinaryOp, Box<IntTree>, Box<IntTree>),
+        }
+
+        #[derive(Debug, Clone)]
+        enum BoolTree {
+            Literal(bool),
+            Not(Box<BoolTree>),
+            Logic(BinaryOp, Box<BoolTree>, Box<BoolTree>),
+            Compare(BinaryOp, IntTree, IntTree),
+        }
+
+        /// The names that definitions are made with, few enough that they
+        /// keep shadowing each other
+        const NAMES: &[&str] = &["a", "b"];
+
+        /// Small numbers, and numbers close enough to the largest integer
+        /// that adding them wraps around
+        fn int_tree() -> impl Strategy<Value = IntTree> {
+            let leaf = prop_oneof![
+                (0..10u64).prop_map(IntTree::Literal),
+                (2_147_483_600..=2_147_483_647u64).prop_map(IntTree::Literal),
+                (0..NAMES.len()).prop_map(IntTree::Var),
+            ];
+            let arithmetic = prop_oneof![
+                Just(BinaryOp::Add),
+                Just(BinaryOp::Sub),
+                Just(BinaryOp::Mul),
+                Just(BinaryOp::Div),
+                Just(BinaryOp::Rem),
+            ];
+
+            leaf.prop_recursive(3, 16, 2, move |inner| {
+                prop_oneof![
+                    inner.clone().prop_map(|tree| IntTree::Neg(Box::new(tree))),
+                    (arithmetic.clone(), inner.clone(), inner).prop_map(|(op, lhs, rhs)| {
+                        IntTree::Binary(op, Box::new(lhs), Box::new(rhs))
+                    }),
+                ]
+            })
+        }
+
+        fn bool_tree() -> impl Strategy<Value = BoolTree> {
+            let comparison = prop_oneof![
+                Just(BinaryOp::Eq),
+                Just(BinaryOp::Ne),
+                Just(BinaryOp::Lt),
+                Just(BinaryOp::Le),
+                Just(BinaryOp::Gt),
+                Just(BinaryOp::Ge),
+            ];
+            let logic = prop_oneof![
+                Just(BinaryOp::And),
+                Just(BinaryOp::Or),
+                Just(BinaryOp::Eq),
+                Just(BinaryOp::Ne),
+            ];
+            let leaf = prop_oneof![
+                any::<bool>().prop_map(BoolTree::Literal),
+                (comparison, int_tree(), int_tree())
+                    .prop_map(|(op, lhs, rhs)| BoolTree::Compare(op, lhs, rhs)),
+            ];
+
+            leaf.prop_recursive(3, 16, 2, move |inner| {
+                prop_oneof![
+                    inner.clone().prop_map(|tree| BoolTree::Not(Box::new(tree))),
+                    (logic.clone(), inner.clone(), inner).prop_map(|(op, lhs, rhs)| {
+                        BoolTree::Logic(op, Box::new(lhs), Box::new(rhs))
+                    }),
+                ]
+            })
+        }
+
+        /// Allocates `tree` where the variables in `defined` are in scope,
+        /// with any other variable replaced by a number
+        fn alloc_int(tree: &IntTree, defined: &[&str], database: &mut Database) -> Idx<Expr> {
+            let expr = match tree {
+                IntTree::Literal(value) => Expr::Literal(Literal::Integer {
+                    value: Some(*value),
+                }),
+                IntTree::Var(idx) if defined.contains(&NAMES[*idx]) => Expr::VariableRef {
+                    var: NAMES[*idx].into(),
+                },
+                IntTree::Var(idx) => Expr::Literal(Literal::Integer {
+                    value: Some(*idx as u64),
+                }),
+                IntTree::Neg(tree) => Expr::Unary {
+                    op: UnaryOp::Neg,
+                    expr: alloc_int(tree, defined, database),
+                },
+                IntTree::Binary(op, lhs, rhs) => Expr::Binary {
+                    op: op.clone(),
+                    lhs: alloc_int(lhs, defined, database),
+                    rhs: alloc_int(rhs, defined, database),
+                },
+            };
+            database.exprs_mut().alloc(expr)
+        }
+
+        fn alloc_bool(tree: &BoolTree, database: &mut Database) -> Idx<Expr> {
+            let expr = match tree {
+                BoolTree::Literal(value) => Expr::Literal(Literal::Bool { value: *value }),
+                BoolTree::Not(tree) => Expr::Unary {
+                    op: UnaryOp::Not,
+                    expr: alloc_bool(tree, database),
+                },
+                BoolTree::Logic(op, lhs, rhs) => Expr::Binary {
+                    op: op.clone(),
+                    lhs: alloc_bool(lhs, database),
+                    rhs: alloc_bool(rhs, database),
+                },
+                BoolTree::Compare(op, lhs, rhs) => Expr::Binary {
+                    op: op.clone(),
+                    lhs: alloc_int(lhs, &[], database),
+                    rhs: alloc_int(rhs, &[], database),
+                },
+            };
+            database.exprs_mut().alloc(expr)
+        }
+
+        /// Evaluates `expr` after `defs` with the interpreter and with node,
+        /// skipping expressions that divide by zero, which JS does not fail
+        /// at
+        fn check_same_result(
+            mut defs: Vec<Stmt>,
+            expr: Idx<Expr>,
+            database: &Database,
+        ) -> Result<(), TestCaseError> {
+            let mut program = defs.clone();
+            program.push(Stmt::Expr(expr));
+            let expected = match interp::eval(&program, database) {
+                Ok(value) => value.to_string(),
+                Err(error) if error.kind == interp::RuntimeErrorKind::DivisionByZero => {
+                    return Ok(())
+                }
+                Err(error) => return Err(TestCaseError::fail(format!("{:?}", error.kind))),
+            };
+
+            defs.push(Stmt::VariableDef {
+                name: "result".into(),
+                ty: None,
+                value: expr,
+                docs: None,
+            });
+            // A stand-in for the runtime, which is all that states need
+            // to be read and written
+            let js = format!(
+                "const $state = (value) => ({{ value }});\n{}console.log(String(result));",
+                Generator::new().generate(&defs, database)
+            );
+            let output = match Command::new("node").arg("-e").arg(&js).output() {
+                Ok(output) => output,
+                // Without node there is nothing to compare against
+                Err(_) => return Ok(()),
+            };
+
+            prop_assert!(output.status.success(), "{}", js);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            prop_assert_eq!(stdout.trim(), expected, "{}", js);
+            Ok(())
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(48))]
+
+            #[test]
+            fn integer_expressions(tree in int_tree()) {
+                let mut database = Database::default();
+                let expr = alloc_int(&tree, &[], &mut database);
+                check_same_result(Vec::new(), expr, &database)?;
+            }
+
+            #[test]
+            fn bool_expressions(tree in bool_tree()) {
+                let mut database = Database::default();
+                let expr = alloc_bool(&tree, &mut database);
+                check_same_result(Vec::new(), expr, &database)?;
+            }
+
+            /// `func f(x) { x + tree }` called as `f(arg)`, which only
+            /// agrees if the function returns its trailing expression
+            #[test]
+            fn function_calls(tree in int_tree(), arg in 0..10u64) {
+                let mut database = Database::default();
+                let param = database.exprs_mut().alloc(Expr::VariableRef { var: "x".into() });
+                let tree = alloc_int(&tree, &[], &mut database);
+                let value = database.exprs_mut().alloc(Expr::Binary {
+                    op: BinaryOp::Add,
+                    lhs: param,
+                    rhs: tree,
+                });
+                let def = Stmt::FunctionDef {
+                    name: "f".into(),
+                    params: vec![Param { name: "x".into(), ty: None }],
+                    ret_ty: None,
+                    body: vec![Stmt::Expr(value)],
+                    docs: None,
+                };
+
+                let callee = database.exprs_mut().alloc(Expr::VariableRef { var: "f".into() });
+                let arg = database.exprs_mut().alloc(Expr::Literal(Literal::Integer { value: Some(arg) }));
+                let call = database.exprs_mut().alloc(Expr::Call { callee, args: vec![arg] });
+                check_same_result(vec![def], call, &database)?;
+            }
+
+            /// Definitions that keep reusing the same names, which JS only
+            /// allows if the generated code renames them. In a function they
+            /// also shadow its parameter, which is called `a`.
+            #[test]
+            fn shadowing_definitions(
+                defs in prop::collection::vec((0..NAMES.len(), any::<bool>(), int_tree()), 1..6),
+                result in int_tree(),
+                in_function in any::<bool>(),
+                arg in 0..10u64,
+            ) {
+                let mut database = Database::default();
+                let mut defined = if in_function { vec![NAMES[0]] } else { Vec::new() };
+                let mut stmts = Vec::new();
+                for (name, is_state, tree) in &defs {
+                    let value = alloc_int(tree, &defined, &mut database);
+                    let name = NAMES[*name];
+                    stmts.push(if *is_state {
+                        Stmt::StateDef { name: name.into(), ty: None, value, docs: None }
+                    } else {
+                        Stmt::VariableDef { name: name.into(), ty: None, value, docs: None }
+                    });
+                    if !defined.contains(&name) {
+                        defined.push(name);
+                    }
+                }
+                let result = alloc_int(&result, &defined, &mut database);
+
+                if in_function {
+                    stmts.push(Stmt::Expr(result));
+                    let def = Stmt::FunctionDef {
+                        name: "f".into(),
+                        params: vec![Param { name: NAMES[0].into(), ty: None }],
+                        ret_ty: None,
+                        body: stmts,
+                        docs: None,
+                    };
+
+                    let callee = database.exprs_mut().alloc(Expr::VariableRef { var: "f".into() });
+                    let arg = database.exprs_mut().alloc(Expr::Literal(Literal::Integer { value: Some(arg) }));
+                    let call = database.exprs_mut().alloc(Expr::Call { callee, args: vec![arg] });
+                    check_same_result(vec![def], call, &database)?;
+                } else {
+                    check_same_result(stmts, result, &database)?;
+                }
+            }
+        }
+
+        /// Checks a program whose last statement is the expression whose
+        /// result is compared
+        fn check_program(input: &str) {
+            let parse = parser::parse(input);
+            let root = ast::Root::cast(parse.syntax()).unwrap();
+            let (database, mut stmts) = ast_lowering::lower_root(root);
+            let result = match stmts.pop() {
+                Some(Stmt::Expr(result)) => result,
+                _ => unreachable!(),
+            };
+
+            check_same_result(stmts, result, &database).unwrap();
+        }
+
+        #[test]
+        fn redefined_state_and_variable() {
+            check_program("state a = 1\nlet a = 2\na");
+            check_program("let a = 1\nlet a = a + 1\na");
+            check_program("let a = 1\nlet f = || a\nlet a = 2\nf() * 10 + a");
+        }
+
+        #[test]
+        fn arithmetic_wraps_around() {
+            check_program("2147483647 + 1");
+            check_program("-2147483648 - 1");
+            check_program("65536 * 65536 + 2147483647 * 2147483647");
+            check_program("-2147483648 / -1");
+            check_program("-2147483648 % -1");
+            check_program("let a = -2147483648\n-a");
+            check_program("func f(a, b) { a + b }\nf(2147483647, 1)");
+            check_program("func f(a, b) { a + b }\nf(\"a\", \"b\") == \"ab\"");
+        }
+    }
 }
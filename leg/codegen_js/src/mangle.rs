@@ -0,0 +1,171 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Names that JS does not allow to be bound, in strict mode code as well,
+/// followed by the globals that the generated code relies on
+const RESERVED: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+    "Infinity",
+    "NaN",
+    "undefined",
+    // Used by the generated code
+    "$state",
+    "Math",
+    crate::SCRUTINEE,
+];
+
+/// The name JS knows the variable `name` by. Names that are reserved in JS
+/// get a `$` appended, as do names that are already a reserved word followed
+/// by `$`s, so that `class` and `class$` don't end up as the same name.
+/// Names that end in `$` and a number are renamed the same way, which leaves
+/// those names to definitions that shadow another one. Everything else is
+/// only escaped.
+pub(crate) fn mangle(name: &str) -> Cow<'_, str> {
+    let escaped = escape(name);
+
+    if is_reserved(&escaped) || is_numbered(escaped.trim_end_matches('$')) {
+        Cow::Owned(format!("{}$", escaped))
+    } else {
+        escaped
+    }
+}
+
+/// The name of the `number`th definition that shadows a variable JS calls
+/// `mangled`, which `mangle` never returns
+pub(crate) fn numbered(mangled: &str, number: usize) -> String {
+    format!("{}${}", mangled, number)
+}
+
+fn is_numbered(name: &str) -> bool {
+    match name.rsplit_once('$') {
+        Some((_, number)) => !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// Whether `mangle` has to rename `name`
+pub(crate) fn is_reserved(name: &str) -> bool {
+    RESERVED.contains(&name.trim_end_matches('$'))
+}
+
+/// Identifiers follow UAX #31, which JS accepts as well, but characters
+/// outside of ASCII are written as `\u` escapes, so that the generated code
+/// reads the same in whatever encoding it is loaded. JS treats an escaped
+/// identifier as the identifier it spells, so this can't make two names
+/// collide. Property names are only escaped, since JS allows reserved words
+/// there.
+pub(crate) fn escape(name: &str) -> Cow<'_, str> {
+    if name.is_ascii() {
+        return Cow::Borrowed(name);
+    }
+
+    let mut mangled = String::with_capacity(name.len() * 2);
+    for c in name.chars() {
+        match u32::from(c) {
+            _ if c.is_ascii() => mangled.push(c),
+            code @ 0..=0xFFFF => write!(mangled, "\\u{:04x}", code).unwrap(),
+            code => write!(mangled, "\\u{{{:x}}}", code).unwrap(),
+        }
+    }
+
+    Cow::Owned(mangled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_ascii_names() {
+        assert!(matches!(mangle("a_1$"), Cow::Borrowed("a_1$")));
+    }
+
+    #[test]
+    fn escape_non_ascii_characters() {
+        assert_eq!(mangle("café"), "caf\\u00e9");
+        assert_eq!(mangle("名前"), "\\u540d\\u524d");
+        assert_eq!(mangle("a𝑥"), "a\\u{1d465}");
+    }
+
+    #[test]
+    fn rename_reserved_words_and_globals() {
+        assert_eq!(mangle("class"), "class$");
+        assert_eq!(mangle("this"), "this$");
+        assert_eq!(mangle("undefined"), "undefined$");
+        assert_eq!(mangle("$state"), "$state$");
+        assert_eq!(mangle("$m"), "$m$");
+        assert_eq!(mangle("Math"), "Math$");
+    }
+
+    #[test]
+    fn renamed_names_do_not_collide() {
+        assert_eq!(mangle("new$"), "new$$");
+        assert_eq!(mangle("new$$"), "new$$$");
+        assert!(matches!(mangle("newer"), Cow::Borrowed("newer")));
+        assert!(matches!(mangle("$"), Cow::Borrowed("$")));
+    }
+
+    #[test]
+    fn numbered_names_are_left_to_shadowing_definitions() {
+        assert_eq!(mangle("a$1"), "a$1$");
+        assert_eq!(mangle("a$1$"), "a$1$$");
+        assert_eq!(mangle("\u{e9}$2"), "\\u00e9$2$");
+        assert!(matches!(mangle("a1"), Cow::Borrowed("a1")));
+        assert!(matches!(mangle("a$b"), Cow::Borrowed("a$b")));
+        assert_eq!(numbered("a", 1), "a$1");
+        assert_eq!(numbered("class$", 2), "class$$2");
+    }
+
+    #[test]
+    fn property_names_are_only_escaped() {
+        assert!(matches!(escape("class"), Cow::Borrowed("class")));
+        assert_eq!(escape("ß"), "\\u00df");
+    }
+}
@@ -0,0 +1,283 @@
+use serde_json::json;
+use text_size::TextSize;
+
+use crate::Mapping;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Builds a Source Map v3 for `js`, which was generated from `source` with
+/// the given mappings. `file` is the name of the generated file and
+/// `source_name` the path of the source, relative to the map.
+pub fn source_map(
+    file: &str,
+    source_name: &str,
+    source: &str,
+    js: &str,
+    mappings: &[Mapping],
+) -> String {
+    json!({
+        "version": 3,
+        "file": file,
+        "sources": [source_name],
+        "sourcesContent": [source],
+        "names": [],
+        "mappings": encode_mappings(js, source, mappings),
+    })
+    .to_string()
+}
+
+/// Encodes the mappings as `;` separated lines of generated code, each with
+/// `,` separated segments. A segment is the generated column, the source
+/// index, the source line and the source column, each relative to the same
+/// field of the previous segment. The generated column starts over on every
+/// line.
+fn encode_mappings(js: &str, source: &str, mappings: &[Mapping]) -> String {
+    let generated_lines = Lines::new(js);
+    let source_lines = Lines::new(source);
+
+    let mut encoded = String::new();
+    let mut line = 0;
+    let mut previous_generated_column = 0;
+    let mut previous_source = (0, 0);
+
+    for (idx, mapping) in mappings.iter().enumerate() {
+        // Several expressions that start at the same place, like `a` in
+        // `a + b`, map to the innermost one
+        if let Some(next) = mappings.get(idx + 1) {
+            if next.generated == mapping.generated {
+                continue;
+            }
+        }
+
+        let (generated_line, generated_column) = generated_lines.line_col(mapping.generated);
+        if generated_line > line {
+            for _ in line..generated_line {
+                encoded.push(';');
+            }
+            line = generated_line;
+            previous_generated_column = 0;
+        } else if !encoded.is_empty() && !encoded.ends_with(';') {
+            encoded.push(',');
+        }
+
+        let (source_line, source_column) = source_lines.line_col(mapping.source.start());
+        encode_vlq(generated_column - previous_generated_column, &mut encoded);
+        encode_vlq(0, &mut encoded);
+        encode_vlq(source_line - previous_source.0, &mut encoded);
+        encode_vlq(source_column - previous_source.1, &mut encoded);
+
+        previous_generated_column = generated_column;
+        previous_source = (source_line, source_column);
+    }
+
+    encoded
+}
+
+/// Writes `value` as a base 64 VLQ. The lowest bit of the first digit is the
+/// sign, and every digit holds five bits with the sixth set if more follow.
+fn encode_vlq(value: i64, out: &mut String) {
+    let mut vlq = if value < 0 {
+        (value.unsigned_abs() << 1) | 1
+    } else {
+        (value as u64) << 1
+    };
+
+    loop {
+        let mut digit = vlq & 0b11111;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64[digit as usize] as char);
+
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+/// Converts offsets to zero based lines and columns, with columns counted in
+/// UTF-16 code units as source maps expect
+struct Lines<'a> {
+    text: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+
+        Self { text, starts }
+    }
+
+    fn line_col(&self, offset: TextSize) -> (i64, i64) {
+        let offset = usize::from(offset);
+        let line = match self.starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let column = self.text[self.starts[line]..offset].encode_utf16().count();
+
+        (line as i64, column as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generator;
+
+    /// A decoded segment: the generated line and column, the source index,
+    /// and the source line and column, all absolute
+    type Segment = (i64, i64, i64, i64, i64);
+
+    fn decode_vlq(chars: &mut std::iter::Peekable<std::str::Chars>) -> i64 {
+        let mut value = 0u64;
+        let mut shift = 0;
+
+        loop {
+            let c = chars.next().unwrap() as u8;
+            let digit = BASE64.iter().position(|b| *b == c).unwrap() as u64;
+            value |= (digit & 0b11111) << shift;
+            shift += 5;
+
+            if digit & 0b100000 == 0 {
+                break;
+            }
+        }
+
+        let magnitude = (value >> 1) as i64;
+        if value & 1 == 1 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    fn decode_mappings(mappings: &str) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        let mut source = (0, 0, 0);
+
+        for (line, text) in mappings.split(';').enumerate() {
+            let mut column = 0;
+
+            for segment in text.split(',').filter(|segment| !segment.is_empty()) {
+                let mut chars = segment.chars().peekable();
+                column += decode_vlq(&mut chars);
+                source.0 += decode_vlq(&mut chars);
+                source.1 += decode_vlq(&mut chars);
+                source.2 += decode_vlq(&mut chars);
+                assert!(chars.peek().is_none());
+
+                segments.push((line as i64, column, source.0, source.1, source.2));
+            }
+        }
+
+        segments
+    }
+
+    /// Generates JS for `input`, and returns the text at both ends of every
+    /// decoded segment
+    fn round_trip(input: &str) -> Vec<(String, String)> {
+        let parse = parser::parse(input);
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (database, stmts) = ast_lowering::lower_root(root);
+        let (js, mappings) = Generator::new().generate_with_mappings(&stmts, &database);
+
+        let map: serde_json::Value =
+            serde_json::from_str(&source_map("main.js", "main.leg", input, &js, &mappings))
+                .unwrap();
+        assert_eq!(map["version"], 3);
+        assert_eq!(map["sources"][0], "main.leg");
+
+        let rest_of_line = |text: &str, line: i64, column: i64| {
+            let line = text.lines().nth(line as usize).unwrap();
+            let column = line
+                .char_indices()
+                .scan(0, |units, (idx, c)| {
+                    let start = *units;
+                    *units += c.len_utf16() as i64;
+                    Some((idx, start))
+                })
+                .find(|(_, units)| *units == column)
+                .map_or(line.len(), |(idx, _)| idx);
+            line[column..].to_string()
+        };
+
+        decode_mappings(map["mappings"].as_str().unwrap())
+            .into_iter()
+            .map(|(line, column, source, source_line, source_column)| {
+                assert_eq!(source, 0);
+                (
+                    rest_of_line(&js, line, column),
+                    rest_of_line(input, source_line, source_column),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn vlq_round_trip() {
+        for value in [
+            0,
+            1,
+            -1,
+            15,
+            16,
+            -16,
+            31,
+            32,
+            1000,
+            -123456,
+            i32::MAX as i64,
+        ] {
+            let mut encoded = String::new();
+            encode_vlq(value, &mut encoded);
+            assert_eq!(decode_vlq(&mut encoded.chars().peekable()), value);
+        }
+    }
+
+    #[test]
+    fn encode_known_vlqs() {
+        let encode = |value| {
+            let mut encoded = String::new();
+            encode_vlq(value, &mut encoded);
+            encoded
+        };
+
+        assert_eq!(encode(0), "A");
+        assert_eq!(encode(1), "C");
+        assert_eq!(encode(-1), "D");
+        assert_eq!(encode(16), "gB");
+    }
+
+    #[test]
+    fn map_statements_and_expressions() {
+        assert_eq!(
+            round_trip("let a = 1\nlog(a + 2)"),
+            [
+                ("let a = 1;", "let a = 1"),
+                ("1;", "1"),
+                ("log(a + 2 | 0);", "log(a + 2)"),
+                ("a + 2 | 0);", "a + 2)"),
+                ("2 | 0);", "2)"),
+            ]
+            .map(|(js, source)| (js.to_string(), source.to_string())),
+        );
+    }
+
+    #[test]
+    fn map_function_bodies_across_lines() {
+        let segments = round_trip("func f(a) {\n  a - 2\n}\n\nf(1)");
+        assert!(segments.contains(&("a - 2 | 0;".to_string(), "a - 2".to_string())));
+        assert!(segments.contains(&("f(1);".to_string(), "f(1)".to_string())));
+    }
+
+    #[test]
+    fn map_columns_in_utf16() {
+        let segments = round_trip("let s = \"🦀\" + b");
+        assert!(segments.contains(&("b;".to_string(), "b".to_string())));
+    }
+}
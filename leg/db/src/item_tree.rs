@@ -0,0 +1,84 @@
+use rowan::GreenNode;
+use smol_str::SmolStr;
+use syntax::SyntaxNode;
+use text_size::TextRange;
+
+/// The top-level statements of a file, in order. Each of them is lowered on
+/// its own, so that editing one of them leaves the others alone.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ItemTree {
+    items: Vec<Item>,
+}
+
+impl ItemTree {
+    pub(crate) fn new(root: &SyntaxNode) -> Self {
+        let items = root
+            .children()
+            .filter_map(|node| {
+                let (kind, name) = match ast::Stmt::cast(node.clone())? {
+                    ast::Stmt::VariableDef(def) => (ItemKind::Variable, def.name()),
+                    ast::Stmt::StateDef(def) => (ItemKind::State, def.name()),
+                    ast::Stmt::FunctionDecl(decl) => (ItemKind::Function, decl.name()),
+                    ast::Stmt::StructDecl(decl) => (ItemKind::Struct, decl.name()),
+                    ast::Stmt::EnumDecl(decl) => (ItemKind::Enum, decl.name()),
+                    ast::Stmt::Expr(_) => (ItemKind::Expr, None),
+                };
+
+                Some(Item {
+                    kind,
+                    name: name.map(|name| ast::name(&name).into()),
+                    range: node.text_range(),
+                    green: node.green().into_owned(),
+                })
+            })
+            .collect();
+
+        Self { items }
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    kind: ItemKind,
+    name: Option<SmolStr>,
+    range: TextRange,
+    green: GreenNode,
+}
+
+impl Item {
+    pub fn kind(&self) -> ItemKind {
+        self.kind
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Where the item is in the file, including its trailing trivia
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    /// The item on its own, with ranges relative to its start
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    pub(crate) fn green(&self) -> &GreenNode {
+        &self.green
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemKind {
+    Variable,
+    State,
+    Function,
+    Struct,
+    Enum,
+    Expr,
+}
@@ -0,0 +1,619 @@
+mod item_tree;
+
+pub use item_tree::{Item, ItemKind, ItemTree};
+pub use parser::TextEdit;
+
+use ast_lowering::Database;
+use codegen_js::{Generator, Mapping};
+use diagnostics::Diagnostic;
+use hir::{BindingKind, Stmt};
+use parser::Parse;
+use rowan::GreenNode;
+use smol_str::SmolStr;
+use std::collections::HashMap;
+use std::slice;
+use std::sync::Arc;
+use text_size::TextSize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(u32);
+
+/// The text of every file along with what has been computed from it. Each
+/// query computes its result on demand, from the results of the queries
+/// before it: text, parse, item tree, lowered items, checked items, and then
+/// diagnostics and JS. Results are remembered until the text of their file
+/// changes.
+#[derive(Default)]
+pub struct RootDatabase {
+    files: HashMap<FileId, File>,
+    next_file: u32,
+}
+
+struct File {
+    text: Arc<str>,
+    /// Bumped whenever `text` changes, results of older revisions are stale
+    revision: u64,
+    parse: Option<Memo<Parse>>,
+    item_tree: Option<Memo<ItemTree>>,
+    lowered_items: Option<Memo<Vec<Arc<LoweredItem>>>>,
+    checked_items: Option<Memo<Vec<Arc<CheckedItem>>>>,
+    diagnostics: Option<Memo<Vec<Diagnostic>>>,
+    js: Option<Memo<Js>>,
+}
+
+struct Memo<T> {
+    revision: u64,
+    value: Arc<T>,
+}
+
+impl<T> Memo<T> {
+    fn new(revision: u64, value: T) -> Self {
+        Self {
+            revision,
+            value: Arc::new(value),
+        }
+    }
+}
+
+/// The memoized value, unless it was computed for an older revision
+fn current<T>(memo: &Option<Memo<T>>, revision: u64) -> Option<Arc<T>> {
+    memo.as_ref()
+        .filter(|memo| memo.revision == revision)
+        .map(|memo| Arc::clone(&memo.value))
+}
+
+/// A top-level item lowered on its own. The ranges in its database are
+/// relative to the start of the item.
+#[derive(Debug)]
+pub struct LoweredItem {
+    green: GreenNode,
+    /// The bindings defined by the items before this one, which it can refer to
+    scope: Vec<(SmolStr, BindingKind)>,
+    database: Database,
+    stmt: Option<Stmt>,
+}
+
+impl LoweredItem {
+    fn new(item: &Item, scope: Vec<(SmolStr, BindingKind)>) -> Self {
+        let mut database = Database::default();
+        for (name, kind) in &scope {
+            database.define(name.clone(), *kind);
+        }
+        let stmt = ast::Stmt::cast(item.syntax()).and_then(|stmt| database.lower_stmt(stmt));
+
+        Self {
+            green: item.green().clone(),
+            scope,
+            database,
+            stmt,
+        }
+    }
+
+    pub fn database(&self) -> &Database {
+        &self.database
+    }
+
+    /// The lowered item, unless it is too broken to lower
+    pub fn stmt(&self) -> Option<&Stmt> {
+        self.stmt.as_ref()
+    }
+}
+
+/// A lowered item after type checking, which is where the types of its
+/// definitions and the type errors in it belong. There is no type checker
+/// yet, so checking finds nothing, but it already runs per item and is only
+/// redone for the items that were lowered again.
+#[derive(Debug)]
+pub struct CheckedItem {
+    lowered: Arc<LoweredItem>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl CheckedItem {
+    fn new(lowered: Arc<LoweredItem>) -> Self {
+        Self {
+            lowered,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn lowered(&self) -> &LoweredItem {
+        &self.lowered
+    }
+
+    /// The errors checking found, with ranges relative to the start of the
+    /// item like the ones in its database
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+}
+
+/// The binding a top-level statement makes visible to the ones after it
+fn binding(stmt: &Stmt) -> Option<(SmolStr, BindingKind)> {
+    match stmt {
+        Stmt::VariableDef { name, .. } => Some((name.clone(), BindingKind::Variable)),
+        Stmt::StateDef { name, .. } => Some((name.clone(), BindingKind::State)),
+        Stmt::FunctionDef { name, .. } => Some((name.clone(), BindingKind::Function)),
+        Stmt::StructDef(_) | Stmt::EnumDef(_) | Stmt::Expr(_) => None,
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Js {
+    pub code: String,
+    /// Where the generated expressions came from, ordered by their position
+    /// in `code`
+    pub mappings: Vec<Mapping>,
+}
+
+impl RootDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_file(&mut self, text: impl Into<Arc<str>>) -> FileId {
+        let id = FileId(self.next_file);
+        self.next_file += 1;
+        self.files.insert(
+            id,
+            File {
+                text: text.into(),
+                revision: 0,
+                parse: None,
+                item_tree: None,
+                lowered_items: None,
+                checked_items: None,
+                diagnostics: None,
+                js: None,
+            },
+        );
+
+        id
+    }
+
+    pub fn remove_file(&mut self, file: FileId) {
+        self.files.remove(&file);
+    }
+
+    pub fn file_text(&self, file: FileId) -> Arc<str> {
+        Arc::clone(&self.file(file).text)
+    }
+
+    /// Replaces the text of `file`, which makes the results computed from
+    /// the old text stale unless the text stays the same
+    pub fn set_file_text(&mut self, file: FileId, text: impl Into<Arc<str>>) {
+        let text = text.into();
+        let file = self.file_mut(file);
+        if file.text != text {
+            file.text = text;
+            file.revision += 1;
+        }
+    }
+
+    /// Applies `edit` to the text of `file`. If the old text was parsed, the
+    /// new text is parsed incrementally from it.
+    pub fn edit_file(&mut self, file: FileId, edit: TextEdit) {
+        let file = self.file_mut(file);
+        let old_parse = current(&file.parse, file.revision);
+
+        let mut text = file.text.to_string();
+        edit.apply(&mut text);
+        file.text = text.into();
+        file.revision += 1;
+
+        if let Some(old_parse) = old_parse {
+            file.parse = Some(Memo::new(file.revision, parser::reparse(&old_parse, edit)));
+        }
+    }
+
+    pub fn parse(&mut self, file: FileId) -> Arc<Parse> {
+        let file = self.file_mut(file);
+        if let Some(parse) = current(&file.parse, file.revision) {
+            return parse;
+        }
+
+        let memo = Memo::new(file.revision, parser::parse(&file.text));
+        let parse = Arc::clone(&memo.value);
+        file.parse = Some(memo);
+        parse
+    }
+
+    pub fn item_tree(&mut self, file_id: FileId) -> Arc<ItemTree> {
+        let file = self.file(file_id);
+        if let Some(item_tree) = current(&file.item_tree, file.revision) {
+            return item_tree;
+        }
+
+        let parse = self.parse(file_id);
+        let file = self.file_mut(file_id);
+        let memo = Memo::new(file.revision, ItemTree::new(&parse.syntax()));
+        let item_tree = Arc::clone(&memo.value);
+        file.item_tree = Some(memo);
+        item_tree
+    }
+
+    /// The items of `file` lowered one by one, in the same order as in its
+    /// item tree. An item lowered for an older revision is reused as long as
+    /// neither its text nor the bindings defined before it have changed, so
+    /// that editing the body of a function does not lower the other items
+    /// again.
+    pub fn lowered_items(&mut self, file_id: FileId) -> Arc<Vec<Arc<LoweredItem>>> {
+        let file = self.file(file_id);
+        if let Some(lowered_items) = current(&file.lowered_items, file.revision) {
+            return lowered_items;
+        }
+
+        let item_tree = self.item_tree(file_id);
+        let file = self.file_mut(file_id);
+
+        let mut old_items: HashMap<&GreenNode, Vec<&Arc<LoweredItem>>> = HashMap::new();
+        for old_item in file.lowered_items.iter().flat_map(|memo| memo.value.iter()) {
+            old_items.entry(&old_item.green).or_default().push(old_item);
+        }
+
+        let mut scope = Vec::new();
+        let lowered_items: Vec<_> = item_tree
+            .items()
+            .iter()
+            .map(|item| {
+                let lowered_item = old_items
+                    .get(item.green())
+                    .and_then(|old_items| old_items.iter().find(|old| old.scope == scope))
+                    .map(|old| Arc::clone(old))
+                    .unwrap_or_else(|| Arc::new(LoweredItem::new(item, scope.clone())));
+
+                scope.extend(lowered_item.stmt().and_then(binding));
+                lowered_item
+            })
+            .collect();
+
+        let memo = Memo::new(file.revision, lowered_items);
+        let lowered_items = Arc::clone(&memo.value);
+        file.lowered_items = Some(memo);
+        lowered_items
+    }
+
+    /// The items of `file` after checking, in the same order as its lowered
+    /// items. An item is only checked again if it was lowered again.
+    pub fn checked_items(&mut self, file_id: FileId) -> Arc<Vec<Arc<CheckedItem>>> {
+        let file = self.file(file_id);
+        if let Some(checked_items) = current(&file.checked_items, file.revision) {
+            return checked_items;
+        }
+
+        let lowered_items = self.lowered_items(file_id);
+        let file = self.file_mut(file_id);
+
+        let old_items: Vec<_> = file
+            .checked_items
+            .iter()
+            .flat_map(|memo| memo.value.iter())
+            .collect();
+        let checked_items: Vec<_> = lowered_items
+            .iter()
+            .map(|lowered_item| {
+                old_items
+                    .iter()
+                    .find(|old| Arc::ptr_eq(&old.lowered, lowered_item))
+                    .map(|old| Arc::clone(old))
+                    .unwrap_or_else(|| Arc::new(CheckedItem::new(Arc::clone(lowered_item))))
+            })
+            .collect();
+
+        let memo = Memo::new(file.revision, checked_items);
+        let checked_items = Arc::clone(&memo.value);
+        file.checked_items = Some(memo);
+        checked_items
+    }
+
+    /// The diagnostics of every phase for `file`, sorted by position
+    pub fn diagnostics(&mut self, file_id: FileId) -> Arc<Vec<Diagnostic>> {
+        let file = self.file(file_id);
+        if let Some(diagnostics) = current(&file.diagnostics, file.revision) {
+            return diagnostics;
+        }
+
+        let parse = self.parse(file_id);
+        let item_tree = self.item_tree(file_id);
+        let checked_items = self.checked_items(file_id);
+
+        let mut diagnostics = parse.diagnostics();
+        diagnostics.extend(ast_validation::validate(&parse.syntax()));
+        for (item, checked_item) in item_tree.items().iter().zip(checked_items.iter()) {
+            let offset = item.range().start();
+            diagnostics.extend(
+                checked_item
+                    .lowered()
+                    .database()
+                    .diagnostics()
+                    .iter()
+                    .chain(checked_item.diagnostics())
+                    .map(|diagnostic| shift_diagnostic(diagnostic.clone(), offset)),
+            );
+        }
+        diagnostics::sort_by_position(&mut diagnostics);
+
+        let file = self.file_mut(file_id);
+        let memo = Memo::new(file.revision, diagnostics);
+        let diagnostics = Arc::clone(&memo.value);
+        file.diagnostics = Some(memo);
+        diagnostics
+    }
+
+    /// The JS generated for `file`, or `None` if it has diagnostics, since
+    /// code with errors can't be run
+    pub fn js(&mut self, file_id: FileId) -> Option<Arc<Js>> {
+        if !self.diagnostics(file_id).is_empty() {
+            return None;
+        }
+
+        let file = self.file(file_id);
+        if let Some(js) = current(&file.js, file.revision) {
+            return Some(js);
+        }
+
+        let item_tree = self.item_tree(file_id);
+        let checked_items = self.checked_items(file_id);
+
+        // The generator remembers the states defined by earlier items
+        let mut generator = Generator::new();
+        let mut js = Js::default();
+        for (item, checked_item) in item_tree.items().iter().zip(checked_items.iter()) {
+            let lowered_item = checked_item.lowered();
+            let stmt = match lowered_item.stmt() {
+                Some(stmt) => stmt,
+                None => continue,
+            };
+
+            let (code, mappings) =
+                generator.generate_with_mappings(slice::from_ref(stmt), lowered_item.database());
+            let generated = TextSize::of(js.code.as_str());
+            js.mappings
+                .extend(mappings.into_iter().map(|mapping| Mapping {
+                    generated: mapping.generated + generated,
+                    source: mapping.source + item.range().start(),
+                }));
+            js.code.push_str(&code);
+        }
+
+        let file = self.file_mut(file_id);
+        let memo = Memo::new(file.revision, js);
+        let js = Arc::clone(&memo.value);
+        file.js = Some(memo);
+        Some(js)
+    }
+
+    fn file(&self, file: FileId) -> &File {
+        self.files
+            .get(&file)
+            .expect("the file is not in the database")
+    }
+
+    fn file_mut(&mut self, file: FileId) -> &mut File {
+        self.files
+            .get_mut(&file)
+            .expect("the file is not in the database")
+    }
+}
+
+/// Moves the ranges of `diagnostic` forward by `offset`
+fn shift_diagnostic(mut diagnostic: Diagnostic, offset: TextSize) -> Diagnostic {
+    for label in &mut diagnostic.labels {
+        label.range += offset;
+    }
+    for fix in &mut diagnostic.fixes {
+        fix.range += offset;
+    }
+
+    diagnostic
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text_size::TextRange;
+
+    const PROGRAM: &str = "\
+struct User { name: String }
+enum Status { Loading, Loaded(User) }
+state count = 0
+let increment = || count = count + 1
+func greet(user) {
+  let status = Status::Loaded(user)
+  match status {
+    Status::Loaded(user) => user,
+    Status::Loading => \"loading\",
+  }
+}
+greet(User { name: \"a\" })
+";
+
+    /// Compiles the whole text at once, which is what the database has to
+    /// match
+    fn compile(text: &str) -> Js {
+        let root = ast::Root::cast(parser::parse(text).syntax()).unwrap();
+        let (database, stmts) = ast_lowering::lower_root(root);
+        let (code, mappings) = Generator::new().generate_with_mappings(&stmts, &database);
+
+        Js { code, mappings }
+    }
+
+    fn edit(text: &str, before: &str, after: &str) -> TextEdit {
+        let start = text.find(before).unwrap();
+        TextEdit {
+            delete: TextRange::at(TextSize::try_from(start).unwrap(), TextSize::of(before)),
+            insert: after.to_string(),
+        }
+    }
+
+    /// Whether each item was lowered again since `before`
+    fn relowered(before: &[Arc<LoweredItem>], after: &[Arc<LoweredItem>]) -> Vec<bool> {
+        before
+            .iter()
+            .zip(after)
+            .map(|(before, after)| !Arc::ptr_eq(before, after))
+            .collect()
+    }
+
+    /// The names captured by the closure that `item` defines
+    fn closure_captures(item: &LoweredItem) -> Vec<SmolStr> {
+        match item.stmt() {
+            Some(Stmt::VariableDef { value, .. }) => match &item.database().exprs()[*value] {
+                hir::Expr::Closure { captures, .. } => captures
+                    .iter()
+                    .map(|capture| capture.name.clone())
+                    .collect(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn items() {
+        let mut db = RootDatabase::new();
+        let file = db.add_file(PROGRAM);
+
+        let items: Vec<_> = db
+            .item_tree(file)
+            .items()
+            .iter()
+            .map(|item| (item.kind(), item.name().map(str::to_string)))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                (ItemKind::Struct, Some("User".to_string())),
+                (ItemKind::Enum, Some("Status".to_string())),
+                (ItemKind::State, Some("count".to_string())),
+                (ItemKind::Variable, Some("increment".to_string())),
+                (ItemKind::Function, Some("greet".to_string())),
+                (ItemKind::Expr, None),
+            ],
+        );
+    }
+
+    #[test]
+    fn same_js_as_compiling_the_whole_file() {
+        let mut db = RootDatabase::new();
+        let file = db.add_file(PROGRAM);
+
+        assert_eq!(*db.js(file).unwrap(), compile(PROGRAM));
+    }
+
+    #[test]
+    fn results_are_memoized() {
+        let mut db = RootDatabase::new();
+        let file = db.add_file(PROGRAM);
+
+        let parse = db.parse(file);
+        let js = db.js(file).unwrap();
+        assert!(Arc::ptr_eq(&parse, &db.parse(file)));
+        assert!(Arc::ptr_eq(&js, &db.js(file).unwrap()));
+
+        db.set_file_text(file, PROGRAM);
+        assert!(Arc::ptr_eq(&js, &db.js(file).unwrap()));
+    }
+
+    #[test]
+    fn editing_a_function_body_only_lowers_it_again() {
+        let text = "func a() {\n  1\n}\nfunc b() {\n  2\n}\nlet c = || b()\n";
+        let mut db = RootDatabase::new();
+        let file = db.add_file(text);
+        let before = db.lowered_items(file);
+
+        let edit = edit(text, "1", "10 + a()");
+        let mut new_text = text.to_string();
+        edit.apply(&mut new_text);
+        db.edit_file(file, edit);
+
+        assert_eq!(
+            relowered(&before, &db.lowered_items(file)),
+            [true, false, false]
+        );
+        assert_eq!(*db.js(file).unwrap(), compile(&new_text));
+        assert_eq!(
+            db.parse(file).debug_tree(),
+            parser::parse(&new_text).debug_tree()
+        );
+    }
+
+    #[test]
+    fn only_items_that_were_lowered_again_are_checked_again() {
+        let text = "let a = 1\nlet b = 2\n";
+        let mut db = RootDatabase::new();
+        let file = db.add_file(text);
+        let before = db.checked_items(file);
+
+        db.edit_file(file, edit(text, "2", "3"));
+
+        let after = db.checked_items(file);
+        assert!(Arc::ptr_eq(&before[0], &after[0]));
+        assert!(!Arc::ptr_eq(&before[1], &after[1]));
+        assert!(Arc::ptr_eq(&db.lowered_items(file)[1], &after[1].lowered));
+    }
+
+    #[test]
+    fn renaming_an_item_lowers_the_items_after_it_again() {
+        let mut db = RootDatabase::new();
+        let file = db.add_file("let a = 1\nlet f = || a\nlet g = || f\n");
+        let before = db.lowered_items(file);
+        assert_eq!(closure_captures(&before[1]), ["a"]);
+
+        db.set_file_text(file, "let b = 1\nlet f = || a\nlet g = || f\n");
+
+        let after = db.lowered_items(file);
+        assert_eq!(relowered(&before, &after), [true, true, true]);
+        assert!(closure_captures(&after[1]).is_empty());
+    }
+
+    #[test]
+    fn adding_an_expression_keeps_the_items_around_it() {
+        let mut db = RootDatabase::new();
+        let file = db.add_file("let a = 1\nlet b = 2\n");
+        let before = db.lowered_items(file);
+
+        db.set_file_text(file, "let a = 1\na\nlet b = 2\n");
+
+        let after = db.lowered_items(file);
+        assert!(Arc::ptr_eq(&before[0], &after[0]));
+        assert!(Arc::ptr_eq(&before[1], &after[2]));
+    }
+
+    #[test]
+    fn no_js_for_a_file_with_errors() {
+        let mut db = RootDatabase::new();
+        let file = db.add_file("let a =\n");
+        assert_eq!(db.js(file), None);
+
+        db.set_file_text(file, "let a = 1\n");
+        assert_eq!(db.js(file).unwrap().code, "let a = 1;\n");
+    }
+
+    #[test]
+    fn diagnostics_of_every_phase() {
+        let text = "let a = 99999999999999999999\nlet b =\nlet c = X::Y::Z";
+        let mut db = RootDatabase::new();
+        let file = db.add_file(text);
+
+        let diagnostics: Vec<_> = db
+            .diagnostics(file)
+            .iter()
+            .map(|diagnostic| (diagnostic.code.as_str(), diagnostic.range()))
+            .collect();
+
+        let path = text.find("X::Y::Z").unwrap();
+        assert_eq!(
+            diagnostics[2],
+            (
+                "E0200",
+                Some(TextRange::at(
+                    TextSize::try_from(path).unwrap(),
+                    TextSize::of("X::Y::Z"),
+                )),
+            ),
+        );
+        let codes: Vec<_> = diagnostics.iter().map(|(code, _)| *code).collect();
+        assert_eq!(codes, ["E0100", "E0001", "E0200"]);
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt;
+
+/// A stable identifier for a kind of diagnostic, like `E0001`. Codes are
+/// never reused or renumbered, so tools can match on them instead of on the
+/// message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Code(&'static str);
+
+impl Code {
+    // Parsing, E00xx
+    pub const UNEXPECTED_TOKEN: Self = Self("E0001");
+    pub const MISSING_TERMINATOR: Self = Self("E0002");
+    pub const AMBIGUOUS_CONTINUATION: Self = Self("E0003");
+    pub const UNTERMINATED_COMMENT: Self = Self("E0004");
+    pub const UNTERMINATED_STRING: Self = Self("E0005");
+    pub const INVALID_CHARACTER: Self = Self("E0006");
+    pub const INVALID_NUMBER_SUFFIX: Self = Self("E0007");
+    pub const INVALID_ESCAPE: Self = Self("E0008");
+    pub const UNSUPPORTED_FLOAT: Self = Self("E0009");
+
+    // Validation, E01xx
+    pub const NUMBER_LITERAL_TOO_LARGE: Self = Self("E0100");
+    pub const INVALID_ASSIGNMENT_TARGET: Self = Self("E0101");
+    pub const NON_EXHAUSTIVE_MATCH: Self = Self("E0102");
+
+    // Lowering, E02xx
+    pub const INVALID_PATH: Self = Self("E0200");
+
+    // Evaluation, E03xx
+    pub const DIVISION_BY_ZERO: Self = Self("E0300");
+    pub const INTEGER_OVERFLOW: Self = Self("E0301");
+    pub const INVALID_OPERANDS: Self = Self("E0302");
+    pub const UNBOUND_VARIABLE: Self = Self("E0303");
+    pub const NOT_CALLABLE: Self = Self("E0304");
+    pub const ARITY_MISMATCH: Self = Self("E0305");
+    pub const NO_MATCHING_ARM: Self = Self("E0306");
+    pub const STACK_OVERFLOW: Self = Self("E0307");
+    pub const MISSING_EXPRESSION: Self = Self("E0308");
+
+    // Code generation warnings, W04xx
+    pub const RENAMED_EXPORT: Self = Self("W0400");
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
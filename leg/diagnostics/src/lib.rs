@@ -0,0 +1,171 @@
+mod code;
+mod line_index;
+mod render;
+
+pub use code::Code;
+pub use line_index::{LineCol, LineIndex};
+pub use render::Renderer;
+
+use std::fmt;
+use text_size::{TextRange, TextSize};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A message attached to a range of the source. The primary label points at
+/// the cause of the diagnostic, secondary labels add context to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub range: TextRange,
+    pub message: String,
+    pub primary: bool,
+}
+
+impl Label {
+    pub fn primary(range: TextRange, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+            primary: true,
+        }
+    }
+
+    pub fn secondary(range: TextRange, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+            primary: false,
+        }
+    }
+}
+
+/// A change to the source that resolves a diagnostic, like inserting a
+/// missing `;`. An empty range inserts `replacement` at that offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub message: String,
+    pub range: TextRange,
+    pub replacement: String,
+}
+
+impl Fix {
+    pub fn insert(offset: TextSize, text: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            range: TextRange::empty(offset),
+            replacement: text.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: Code,
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    pub fixes: Vec<Fix>,
+}
+
+impl Diagnostic {
+    pub fn error(code: Code, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Error, message)
+    }
+
+    pub fn warning(code: Code, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Warning, message)
+    }
+
+    fn new(code: Code, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+            fixes: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
+    /// The range of the first primary label, which is where the
+    /// diagnostic is reported
+    pub fn range(&self) -> Option<TextRange> {
+        self.labels
+            .iter()
+            .find(|label| label.primary)
+            .map(|label| label.range)
+    }
+}
+
+/// Sorts diagnostics by where they are reported, diagnostics without a
+/// range come last
+pub fn sort_by_position(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by_key(|diagnostic| match diagnostic.range() {
+        Some(range) => (false, range.start()),
+        None => (true, 0.into()),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(start: u32) -> Diagnostic {
+        Diagnostic::error(Code::UNEXPECTED_TOKEN, "").with_label(Label::primary(
+            TextRange::new(start.into(), (start + 1).into()),
+            "",
+        ))
+    }
+
+    #[test]
+    fn sort_diagnostics_by_position() {
+        let mut diagnostics = vec![
+            error_at(10),
+            Diagnostic::error(Code::UNEXPECTED_TOKEN, ""),
+            error_at(2),
+        ];
+
+        sort_by_position(&mut diagnostics);
+
+        let starts: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.range().map(|range| u32::from(range.start())))
+            .collect();
+        assert_eq!(starts, [Some(2), Some(10), None]);
+    }
+}
@@ -0,0 +1,111 @@
+use text_size::TextSize;
+
+/// Maps offsets in a text to the line and column they are on
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineIndex {
+    /// The offset at which each line starts
+    line_starts: Vec<TextSize>,
+}
+
+/// A zero based line and column, the column is counted in characters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![TextSize::from(0)];
+
+        for (offset, c) in text.char_indices() {
+            if c == '\n' {
+                line_starts.push(TextSize::try_from(offset + 1).unwrap());
+            }
+        }
+
+        Self { line_starts }
+    }
+
+    pub fn line_col(&self, text: &str, offset: TextSize) -> LineCol {
+        let line = self.line(offset);
+        let start = usize::from(self.line_starts[line]);
+        let col = text[start..usize::from(offset)].chars().count();
+
+        LineCol {
+            line: line as u32,
+            col: col as u32,
+        }
+    }
+
+    /// The zero based line that `offset` is on
+    pub fn line(&self, offset: TextSize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        }
+    }
+
+    /// The text of the given line, without its line terminator
+    pub fn line_text<'a>(&self, text: &'a str, line: usize) -> &'a str {
+        let start = usize::from(self.line_starts[line]);
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(text.len(), |end| usize::from(*end));
+
+        text[start..end].trim_end_matches(['\n', '\r'])
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, offset: u32, line: u32, col: u32) {
+        let line_index = LineIndex::new(text);
+        assert_eq!(
+            line_index.line_col(text, offset.into()),
+            LineCol { line, col },
+        );
+    }
+
+    #[test]
+    fn first_line() {
+        check("let a = 1", 4, 0, 4);
+    }
+
+    #[test]
+    fn after_newline() {
+        check("let a = 1\nlet b = 2", 14, 1, 4);
+    }
+
+    #[test]
+    fn at_newline() {
+        check("let a = 1\nlet b = 2", 9, 0, 9);
+    }
+
+    #[test]
+    fn at_end_of_text() {
+        check("a\n", 2, 1, 0);
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        check("\"héllo\" + x", 10, 0, 9);
+    }
+
+    #[test]
+    fn line_text_excludes_line_terminator() {
+        let text = "let a = 1\r\nlet b = 2";
+        let line_index = LineIndex::new(text);
+
+        assert_eq!(line_index.line_text(text, 0), "let a = 1");
+        assert_eq!(line_index.line_text(text, 1), "let b = 2");
+        assert_eq!(line_index.line_count(), 2);
+    }
+}
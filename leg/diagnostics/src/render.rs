@@ -0,0 +1,324 @@
+use std::fmt::Write;
+
+use crate::{Diagnostic, Label, LineIndex, Severity};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// Renders diagnostics for a single file in the style of
+///
+/// ```text
+/// error[E0002]: expected `;` or a newline, but found let
+///  --> main.leg:1:11
+///   |
+/// 1 | let a = 1 let b = a
+///   |           ^^^ expected `;` or a newline before this
+/// ```
+pub struct Renderer<'a> {
+    file_name: &'a str,
+    source: &'a str,
+    line_index: LineIndex,
+    colors: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(file_name: &'a str, source: &'a str) -> Self {
+        Self {
+            file_name,
+            source,
+            line_index: LineIndex::new(source),
+            colors: false,
+        }
+    }
+
+    /// Whether to highlight the output with ANSI escape codes
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+        let severity_color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        writeln!(
+            out,
+            "{}: {}",
+            self.paint(
+                severity_color,
+                &format!("{}[{}]", diagnostic.severity, diagnostic.code)
+            ),
+            self.paint(BOLD, &diagnostic.message),
+        )
+        .unwrap();
+
+        let mut labels: Vec<&Label> = diagnostic.labels.iter().collect();
+        labels.sort_by_key(|label| (label.range.start(), !label.primary));
+
+        let last_line = labels
+            .iter()
+            .map(|label| self.line_index.line(label.range.start()))
+            .max()
+            .unwrap_or(0);
+        let gutter = " ".repeat((last_line + 1).to_string().len());
+
+        if let Some(range) = diagnostic.range() {
+            let location = self.line_index.line_col(self.source, range.start());
+            writeln!(
+                out,
+                "{}{} {}:{}:{}",
+                gutter,
+                self.paint(BLUE, "-->"),
+                self.file_name,
+                location.line + 1,
+                location.col + 1,
+            )
+            .unwrap();
+        }
+
+        if !labels.is_empty() {
+            writeln!(out, "{} {}", gutter, self.paint(BLUE, "|")).unwrap();
+        }
+
+        let mut previous_line = None;
+        let mut idx = 0;
+        while idx < labels.len() {
+            let line = self.line_index.line(labels[idx].range.start());
+
+            if matches!(previous_line, Some(previous) if line > previous + 1) {
+                writeln!(out, "{}", self.paint(BLUE, "...")).unwrap();
+            }
+            previous_line = Some(line);
+
+            let text = self.line_index.line_text(self.source, line);
+            writeln!(
+                out,
+                "{} {} {}",
+                self.paint(BLUE, &format!("{:>width$}", line + 1, width = gutter.len())),
+                self.paint(BLUE, "|"),
+                text,
+            )
+            .unwrap();
+
+            while idx < labels.len() && self.line_index.line(labels[idx].range.start()) == line {
+                self.render_label(&mut out, &gutter, text, labels[idx], severity_color);
+                idx += 1;
+            }
+        }
+
+        for note in &diagnostic.notes {
+            writeln!(
+                out,
+                "{} {} {}: {}",
+                gutter,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "note"),
+                note
+            )
+            .unwrap();
+        }
+
+        if let Some(help) = &diagnostic.help {
+            writeln!(
+                out,
+                "{} {} {}: {}",
+                gutter,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "help"),
+                help
+            )
+            .unwrap();
+        }
+
+        out
+    }
+
+    /// Underlines the part of `text` that `label` covers, labels that span
+    /// several lines are underlined up to the end of their first line.
+    fn render_label(&self, out: &mut String, gutter: &str, text: &str, label: &Label, color: &str) {
+        let start = self.line_index.line_col(self.source, label.range.start());
+        let end = self.line_index.line_col(self.source, label.range.end());
+        let end_col = if end.line == start.line {
+            end.col
+        } else {
+            text.chars().count() as u32
+        };
+
+        // Keep tabs so that the underline lines up with the source line
+        let indent: String = text
+            .chars()
+            .take(start.col as usize)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let (marker, color) = if label.primary {
+            ('^', color)
+        } else {
+            ('-', BLUE)
+        };
+        let width = end_col.saturating_sub(start.col).max(1) as usize;
+        let underline = marker.to_string().repeat(width);
+
+        let mut line = format!(
+            "{} {} {}{}",
+            gutter,
+            self.paint(BLUE, "|"),
+            indent,
+            self.paint(color, &underline)
+        );
+        if !label.message.is_empty() {
+            line.push(' ');
+            line.push_str(&self.paint(color, &label.message));
+        }
+
+        writeln!(out, "{}", line).unwrap();
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.colors {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Code;
+    use expect_test::{expect, Expect};
+    use text_size::TextRange;
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(start.into(), end.into())
+    }
+
+    fn check(source: &str, diagnostic: Diagnostic, expected: Expect) {
+        let renderer = Renderer::new("main.leg", source);
+        expected.assert_eq(&renderer.render(&diagnostic));
+    }
+
+    #[test]
+    fn render_primary_label() {
+        check(
+            "let a = 1 let b = a",
+            Diagnostic::error(
+                Code::MISSING_TERMINATOR,
+                "expected `;` or a newline, but found let",
+            )
+            .with_label(Label::primary(
+                range(10, 13),
+                "expected `;` or a newline before this",
+            )),
+            expect![[r#"
+error[E0002]: expected `;` or a newline, but found let
+ --> main.leg:1:11
+  |
+1 | let a = 1 let b = a
+  |           ^^^ expected `;` or a newline before this
+"#]],
+        );
+    }
+
+    #[test]
+    fn render_labels_on_several_lines() {
+        check(
+            "state count = 0\nlet a = 1\nlet b = 2\ncount = \"a\"",
+            Diagnostic::error(Code::UNEXPECTED_TOKEN, "mismatched types")
+                .with_label(Label::secondary(range(14, 15), "this is a number"))
+                .with_label(Label::primary(range(44, 47), "this is a string")),
+            expect![[r#"
+error[E0001]: mismatched types
+ --> main.leg:4:9
+  |
+1 | state count = 0
+  |               - this is a number
+...
+4 | count = "a"
+  |         ^^^ this is a string
+"#]],
+        );
+    }
+
+    #[test]
+    fn render_notes_and_help() {
+        check(
+            "match s {\n  Status::Loading => 0\n}",
+            Diagnostic::warning(Code::NON_EXHAUSTIVE_MATCH, "non-exhaustive match")
+                .with_label(Label::primary(
+                    range(6, 7),
+                    "`Status::Loaded(_)` not covered",
+                ))
+                .with_note("`s` is a `Status`")
+                .with_help("add a `_` arm"),
+            expect![[r#"
+warning[E0102]: non-exhaustive match
+ --> main.leg:1:7
+  |
+1 | match s {
+  |       ^ `Status::Loaded(_)` not covered
+  = note: `s` is a `Status`
+  = help: add a `_` arm
+"#]],
+        );
+    }
+
+    #[test]
+    fn render_range_spanning_lines() {
+        check(
+            "let a = (1 +\n  2",
+            Diagnostic::error(Code::UNEXPECTED_TOKEN, "unclosed parenthesis")
+                .with_label(Label::primary(range(8, 16), "")),
+            expect![[r#"
+error[E0001]: unclosed parenthesis
+ --> main.leg:1:9
+  |
+1 | let a = (1 +
+  |         ^^^^
+"#]],
+        );
+    }
+
+    #[test]
+    fn render_empty_range() {
+        check(
+            "let a =",
+            Diagnostic::error(Code::UNEXPECTED_TOKEN, "expected an expression")
+                .with_label(Label::primary(range(7, 7), "")),
+            expect![[r#"
+error[E0001]: expected an expression
+ --> main.leg:1:8
+  |
+1 | let a =
+  |        ^
+"#]],
+        );
+    }
+
+    #[test]
+    fn render_with_colors() {
+        let renderer = Renderer::new("main.leg", "a").with_colors(true);
+        let diagnostic = Diagnostic::error(Code::UNEXPECTED_TOKEN, "oops")
+            .with_label(Label::primary(range(0, 1), ""));
+
+        assert_eq!(
+            renderer.render(&diagnostic),
+            "\x1b[1;31merror[E0001]\x1b[0m: \x1b[1moops\x1b[0m\n \
+             \x1b[1;34m-->\x1b[0m main.leg:1:1\n  \
+             \x1b[1;34m|\x1b[0m\n\
+             \x1b[1;34m1\x1b[0m \x1b[1;34m|\x1b[0m a\n  \
+             \x1b[1;34m|\x1b[0m \x1b[1;31m^\x1b[0m\n",
+        );
+    }
+}
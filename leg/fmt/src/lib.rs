@@ -0,0 +1,558 @@
+use syntax::syntax_kind::SyntaxKind;
+use syntax::{SyntaxElement, SyntaxNode, SyntaxToken};
+
+const INDENT: &str = "    ";
+
+/// Nodes with a comma separated list between their brackets
+const COMMA_LISTS: &[SyntaxKind] = &[
+    SyntaxKind::ArgList,
+    SyntaxKind::ParamList,
+    SyntaxKind::StructDecl,
+    SyntaxKind::EnumDecl,
+    SyntaxKind::StructExpr,
+    SyntaxKind::MatchArmList,
+    SyntaxKind::TupleType,
+    SyntaxKind::FnType,
+    SyntaxKind::VariantPat,
+];
+
+/// Formats the file `root` is the root of, which should be free of syntax
+/// errors.
+///
+/// Line breaks stay where they are, since they can end statements, but
+/// blank lines are collapsed into one. Everything else is normalized: lines
+/// are indented by the brackets they are in, tokens are separated by a
+/// single space where they need to be, comments lose their trailing spaces,
+/// and comma separated lists get a trailing comma when their closing
+/// bracket is on its own line, and lose it otherwise.
+pub fn format(root: &SyntaxNode) -> String {
+    let mut formatter = Formatter::default();
+    for token in root
+        .descendants_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+    {
+        formatter.token(token);
+    }
+
+    formatter.finish()
+}
+
+#[derive(Default)]
+struct Formatter {
+    out: String,
+    /// The number of newlines since the previous token
+    newlines: usize,
+    /// The previous token that is not whitespace
+    prev: Option<SyntaxToken>,
+    /// The previous token that is neither whitespace nor a comment, along
+    /// with where it ends in `out`
+    prev_code: Option<(SyntaxToken, usize)>,
+    /// The indentation of the lines the brackets that are still open were
+    /// opened on
+    open: Vec<usize>,
+    /// The indentation of the current line
+    indent: usize,
+}
+
+impl Formatter {
+    fn token(&mut self, token: SyntaxToken) {
+        if token.kind() == SyntaxKind::Whitespace {
+            self.newlines += token.text().matches('\n').count();
+            return;
+        }
+
+        let closed = if is_closing(&token) {
+            self.open.pop()
+        } else {
+            None
+        };
+
+        if self.prev.is_some() {
+            if self.newlines > 0 {
+                self.line_break(&token, closed);
+            } else {
+                self.space(&token);
+            }
+        }
+
+        if matches!(token.kind(), SyntaxKind::Comment | SyntaxKind::DocComment) {
+            self.out.push_str(token.text().trim_end());
+        } else {
+            self.out.push_str(token.text());
+            self.prev_code = Some((token.clone(), self.out.len()));
+        }
+
+        if is_opening(&token) {
+            self.open.push(self.indent);
+        }
+        self.prev = Some(token);
+        self.newlines = 0;
+    }
+
+    /// Starts the line of `token`, which closed the bracket opened on a line
+    /// with the indentation `closed` if it is a closing bracket
+    fn line_break(&mut self, token: &SyntaxToken, closed: Option<usize>) {
+        if is_list_closing(token) {
+            if let Some((prev_code, end)) = &self.prev_code {
+                let is_empty = is_opening(prev_code) && prev_code.parent() == token.parent();
+                if prev_code.kind() != SyntaxKind::Comma && !is_empty {
+                    self.out.insert(*end, ',');
+                }
+            }
+        }
+
+        let after_opening = self.prev.as_ref().is_some_and(is_opening);
+        let newlines = if after_opening || closed.is_some() {
+            1
+        } else {
+            self.newlines.min(2)
+        };
+
+        self.indent = match closed {
+            Some(indent) => indent,
+            None => {
+                let prev_code = self.prev_code.as_ref().map(|(prev_code, _)| prev_code);
+                let continues = is_operator(token) || prev_code.is_some_and(is_operator);
+                self.open.last().map_or(0, |indent| indent + 1) + usize::from(continues)
+            }
+        };
+
+        self.out.push_str(&"\n".repeat(newlines));
+        self.out.push_str(&INDENT.repeat(self.indent));
+    }
+
+    /// Separates `token` from the previous token on the same line
+    fn space(&mut self, token: &SyntaxToken) {
+        let prev = self.prev.as_ref().unwrap();
+
+        if is_list_closing(token)
+            && prev.kind() == SyntaxKind::Comma
+            && prev.parent() == token.parent()
+        {
+            self.out.pop();
+        }
+
+        if needs_space(prev, token) {
+            self.out.push(' ');
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+
+        self.out
+    }
+}
+
+/// Whether `prev` and `next` are separated by a space when they are on the
+/// same line
+fn needs_space(prev: &SyntaxToken, next: &SyntaxToken) -> bool {
+    use SyntaxKind::*;
+
+    if matches!(next.kind(), Comment | DocComment) {
+        return true;
+    }
+
+    if matches!(prev.kind(), LParen | LBracket | ColonColon)
+        || is_opening_bar(prev)
+        || is_prefix_operator(prev)
+    {
+        return false;
+    }
+
+    if matches!(
+        next.kind(),
+        RParen | RBracket | Comma | Semicolon | Colon | ColonColon | Question
+    ) || is_closing_bar(next)
+    {
+        return false;
+    }
+
+    // Calls, and the parameters of functions and fields of variants
+    if next.kind() == LParen && matches!(prev.kind(), Ident | RParen) {
+        return false;
+    }
+
+    !(prev.kind() == LBrace && next.kind() == RBrace)
+}
+
+fn is_opening(token: &SyntaxToken) -> bool {
+    matches!(
+        token.kind(),
+        SyntaxKind::LParen | SyntaxKind::LBracket | SyntaxKind::LBrace
+    ) || is_opening_bar(token)
+}
+
+fn is_closing(token: &SyntaxToken) -> bool {
+    matches!(
+        token.kind(),
+        SyntaxKind::RParen | SyntaxKind::RBracket | SyntaxKind::RBrace
+    ) || is_closing_bar(token)
+}
+
+/// Whether `token` closes a comma separated list
+fn is_list_closing(token: &SyntaxToken) -> bool {
+    is_closing(token)
+        && token
+            .parent()
+            .is_some_and(|parent| COMMA_LISTS.contains(&parent.kind()))
+}
+
+/// The `|` before the parameters of a closure
+fn is_opening_bar(token: &SyntaxToken) -> bool {
+    is_closure_bar(token) && token.prev_sibling_or_token().is_none()
+}
+
+/// The `|` after the parameters of a closure
+fn is_closing_bar(token: &SyntaxToken) -> bool {
+    is_closure_bar(token) && token.prev_sibling_or_token().is_some()
+}
+
+fn is_closure_bar(token: &SyntaxToken) -> bool {
+    token.kind() == SyntaxKind::Bar
+        && token
+            .parent()
+            .is_some_and(|parent| parent.kind() == SyntaxKind::ParamList)
+}
+
+fn is_prefix_operator(token: &SyntaxToken) -> bool {
+    matches!(token.kind(), SyntaxKind::Minus | SyntaxKind::Bang)
+        && token
+            .parent()
+            .is_some_and(|parent| parent.kind() == SyntaxKind::PrefixExpr)
+}
+
+/// Whether `token` is an infix operator, or joins the parts of a
+/// definition or a match arm, which are indented once more when they start
+/// or end a line
+fn is_operator(token: &SyntaxToken) -> bool {
+    use SyntaxKind::*;
+
+    let parent = match token.parent() {
+        Some(parent) => parent.kind(),
+        None => return false,
+    };
+
+    match token.kind() {
+        Equals => matches!(parent, AssignExpr | VariableDef | StateDef),
+        FatArrow | Arrow => true,
+        Plus | Minus | Star | Slash | Percent | EqualsEquals | BangEquals | LessThan
+        | LessThanEqual | GreaterThan | GreaterThanEqual | AmpersandAmpersand | BarBar => {
+            parent == InfixExpr
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+    use proptest::prelude::*;
+
+    fn check(input: &str, expected: Expect) {
+        let parse = parser::parse(input);
+        assert_eq!(parse.diagnostics(), Vec::new(), "{}", input);
+
+        let formatted = format(&parse.syntax());
+        expected.assert_eq(&formatted);
+        assert_eq!(format(&parser::parse(&formatted).syntax()), formatted);
+    }
+
+    /// The tokens that are not trivia or commas, which formatting keeps
+    fn code_tokens(input: &str) -> Vec<(SyntaxKind, String)> {
+        parser::parse(input)
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .filter(|token| !token.kind().is_trivia() && token.kind() != SyntaxKind::Comma)
+            .map(|token| (token.kind(), token.text().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn spaces_around_operators() {
+        check(
+            "let  a=1+2*-3\nlet b:i32=!true&&a>=2||a!=1\na=a%2",
+            expect![[r#"
+                let a = 1 + 2 * -3
+                let b: i32 = !true && a >= 2 || a != 1
+                a = a % 2
+            "#]],
+        );
+    }
+
+    #[test]
+    fn indent_blocks() {
+        check(
+            "func add(a:i32,b:i32)->i32{\nlet c=a+b\n      c\n}",
+            expect![[r#"
+                func add(a: i32, b: i32) -> i32 {
+                    let c = a + b
+                    c
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn indent_nested_blocks() {
+        check(
+            "func outer() {\nfunc inner() {\nmatch a {\nStatus::Loaded(u) => u,\n_ => 0\n}\n}\n}",
+            expect![[r#"
+                func outer() {
+                    func inner() {
+                        match a {
+                            Status::Loaded(u) => u,
+                            _ => 0,
+                        }
+                    }
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn brackets_on_the_same_line_indent_once() {
+        check(
+            "log(f(\n1,\n2\n))",
+            expect![[r#"
+                log(f(
+                    1,
+                    2,
+                ))
+            "#]],
+        );
+    }
+
+    #[test]
+    fn indent_continued_lines() {
+        check(
+            "let total =\n1 +\n2\n+ 3\nlet a = match b {\nc =>\nc\n}",
+            expect![[r#"
+                let total =
+                    1 +
+                    2
+                    + 3
+                let a = match b {
+                    c =>
+                        c,
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn calls_and_closures() {
+        check(
+            "let f = | a , b |a+b\nlet g = || f (1 , 2)\ng ( ) ( )",
+            expect![[r#"
+                let f = |a, b| a + b
+                let g = || f(1, 2)
+                g()()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn declarations() {
+        check(
+            "struct User{name:String,friends:[User],email:String?}\nenum Status{Loading,Loaded(User,i32)}\nlet f:(i32,String)->bool=|a,b|true",
+            expect![[r#"
+                struct User { name: String, friends: [User], email: String? }
+                enum Status { Loading, Loaded(User, i32) }
+                let f: (i32, String) -> bool = |a, b| true
+            "#]],
+        );
+    }
+
+    #[test]
+    fn struct_exprs_and_paths() {
+        check(
+            "let u = User{name:\"a\",age:1}\nlet s = Status :: Loaded( u )\nlet e = User{}",
+            expect![[r#"
+                let u = User { name: "a", age: 1 }
+                let s = Status::Loaded(u)
+                let e = User {}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn add_trailing_commas_to_multiline_lists() {
+        check(
+            "log(\n1,\n2\n)\nstruct User {\nname: String\n}\nenum Status {\nLoading,\nLoaded(User)\n}",
+            expect![[r#"
+                log(
+                    1,
+                    2,
+                )
+                struct User {
+                    name: String,
+                }
+                enum Status {
+                    Loading,
+                    Loaded(User),
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn remove_trailing_commas_from_single_line_lists() {
+        check(
+            "log(1, 2,)\nlet u = User { name: \"a\", }\nmatch a { _ => 0, }",
+            expect![[r#"
+                log(1, 2)
+                let u = User { name: "a" }
+                match a { _ => 0 }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn trailing_comma_goes_before_comment() {
+        check(
+            "log(\n1 // one\n)",
+            expect![[r#"
+                log(
+                    1, // one
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn keep_comments() {
+        check(
+            "// A counter\nstate count = 0   // starts at zero   \nfunc f() {\n// nothing yet\n}\n  // the end",
+            expect![[r#"
+                // A counter
+                state count = 0 // starts at zero
+                func f() {
+                    // nothing yet
+                }
+                // the end
+            "#]],
+        );
+    }
+
+    #[test]
+    fn keep_block_comments() {
+        check(
+            "let a = /* one */1\nf(a,/* b */ 2)\n/* a\n   /* nested */\n*/\nlet b = 2",
+            expect![[r#"
+                let a = /* one */ 1
+                f(a, /* b */ 2)
+                /* a
+                   /* nested */
+                */
+                let b = 2
+            "#]],
+        );
+    }
+
+    #[test]
+    fn keep_doc_comments() {
+        check(
+            "/// Adds one   \nfunc inc(n) {\nn + 1\n}\nstruct User {\n/// Full name\nname: String,\n}",
+            expect![[r#"
+                /// Adds one
+                func inc(n) {
+                    n + 1
+                }
+                struct User {
+                    /// Full name
+                    name: String,
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn collapse_blank_lines() {
+        check(
+            "\n\nlet a = 1\n\n\n\nlet b = 2\nfunc f() {\n\n  a\n\n}\n\n\n",
+            expect![[r#"
+                let a = 1
+
+                let b = 2
+                func f() {
+                    a
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn keep_semicolons() {
+        check(
+            "let a = 1 ;let b = 2;",
+            expect![[r#"
+                let a = 1; let b = 2;
+            "#]],
+        );
+    }
+
+    #[test]
+    fn empty_input() {
+        check("", expect![[""]]);
+        check("\n\n", expect![[""]]);
+    }
+
+    /// Statements without syntax errors, written the way nobody would
+    const STMTS: &[&str] = &[
+        "let  a=1",
+        "state count:i32 =0",
+        "count=count+ 1",
+        "func f( a ,b:i32 )->i32{a*b}",
+        "func g() {\nlet x = 1\n\n\n  x\n}",
+        "struct User{name:String ,age:i32,}",
+        "enum Status {\nLoading ,\n  Loaded(User)}",
+        "let u=User{ name:\"a\" ,age :1 }",
+        "log( 1 ,\n2 )",
+        "let h=|x|x+ 1",
+        "let m = match s {\nStatus::Loaded( u )=> u ,\n_ =>0 }",
+        "0 - 1 - - 2",
+        "!true||false &&true",
+        "// note",
+        "let t:( i32 ,String )->[ User ]? = || f( 1 )",
+        "1 +\n2",
+    ];
+
+    fn source() -> impl Strategy<Value = String> {
+        let separator = prop::sample::select(
+            &[
+                "\n",
+                "\n\n\n",
+                " ;",
+                "\n  \n// comment\n",
+                " /* a\n /* b */ */ ",
+            ][..],
+        );
+        prop::collection::vec((prop::sample::select(STMTS), separator), 0..12).prop_map(|stmts| {
+            stmts
+                .into_iter()
+                .flat_map(|(stmt, separator)| [stmt, separator])
+                .collect()
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn formatting_keeps_code_and_is_idempotent(input in source()) {
+            prop_assume!(parser::parse(&input).diagnostics().is_empty());
+
+            let formatted = format(&parser::parse(&input).syntax());
+            let parse = parser::parse(&formatted);
+
+            prop_assert_eq!(parse.diagnostics(), Vec::new());
+            prop_assert_eq!(code_tokens(&formatted), code_tokens(&input));
+            prop_assert_eq!(format(&parse.syntax()), formatted);
+        }
+    }
+}
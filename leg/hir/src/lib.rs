@@ -1,13 +1,33 @@
 use la_arena::Idx;
 use smol_str::SmolStr;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Stmt {
-    VariableDef { name: SmolStr, value: Expr },
-    Expr(Expr),
+    VariableDef {
+        name: SmolStr,
+        ty: Option<Idx<TypeRef>>,
+        value: Idx<Expr>,
+        docs: Option<String>,
+    },
+    StateDef {
+        name: SmolStr,
+        ty: Option<Idx<TypeRef>>,
+        value: Idx<Expr>,
+        docs: Option<String>,
+    },
+    FunctionDef {
+        name: SmolStr,
+        params: Vec<Param>,
+        ret_ty: Option<Idx<TypeRef>>,
+        body: Vec<Stmt>,
+        docs: Option<String>,
+    },
+    StructDef(Idx<Struct>),
+    EnumDef(Idx<Enum>),
+    Expr(Idx<Expr>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Missing,
     Binary {
@@ -19,13 +39,41 @@ pub enum Expr {
         op: UnaryOp,
         expr: Idx<Self>,
     },
+    Assign {
+        target: Idx<Self>,
+        value: Idx<Self>,
+    },
+    Call {
+        callee: Idx<Self>,
+        args: Vec<Idx<Self>>,
+    },
+    Closure {
+        params: Vec<Param>,
+        body: Idx<Self>,
+        captures: Vec<Capture>,
+    },
     VariableRef {
         var: SmolStr,
     },
+    /// `User { name: "a", age: 1 }`
+    Record {
+        name: SmolStr,
+        fields: Vec<(SmolStr, Idx<Self>)>,
+    },
+    /// `Status::Loading` or `Status::Loaded(user)`
+    Variant {
+        enum_name: SmolStr,
+        variant: SmolStr,
+        args: Vec<Idx<Self>>,
+    },
+    Match {
+        scrutinee: Idx<Self>,
+        arms: Vec<MatchArm>,
+    },
     Literal(Literal),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum BinaryOp {
     /// The `+` operator (addition)
     Add,
@@ -55,7 +103,7 @@ pub enum BinaryOp {
     Ge,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum UnaryOp {
     /// The `-` operator (negation)
     Neg,
@@ -63,9 +111,122 @@ pub enum UnaryOp {
     Not,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     Integer { value: Option<u64> },
     String { value: SmolStr },
     Bool { value: bool },
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: SmolStr,
+    pub ty: Option<Idx<TypeRef>>,
+}
+
+/// `struct User { name: String, age: i32 }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Struct {
+    pub name: SmolStr,
+    pub fields: Vec<Field>,
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: SmolStr,
+    pub ty: Idx<TypeRef>,
+    pub docs: Option<String>,
+}
+
+/// `enum Status { Loading, Loaded(User), Failed(String) }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    pub name: SmolStr,
+    pub variants: Vec<Variant>,
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: SmolStr,
+    pub fields: Vec<Idx<TypeRef>>,
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pat: Pat,
+    pub expr: Idx<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pat {
+    /// `_`, or a pattern that failed to parse
+    Wildcard,
+    /// `user`, binds the matched value
+    Bind { name: SmolStr },
+    /// `Status::Loaded(user)`
+    Variant {
+        enum_name: SmolStr,
+        variant: SmolStr,
+        fields: Vec<Pat>,
+    },
+}
+
+impl Pat {
+    /// The names bound by this pattern, in source order
+    pub fn bindings(&self) -> Vec<SmolStr> {
+        match self {
+            Self::Wildcard => Vec::new(),
+            Self::Bind { name } => vec![name.clone()],
+            Self::Variant { fields, .. } => fields.iter().flat_map(Self::bindings).collect(),
+        }
+    }
+}
+
+/// A type as it was written in the source, before any name resolution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeRef {
+    Missing,
+    /// `i32`, `String`, `User`
+    Path {
+        name: SmolStr,
+    },
+    /// `(A, B) -> R`
+    Fn {
+        params: Vec<Idx<Self>>,
+        ret: Idx<Self>,
+    },
+    /// `(A, B)`, `()` is the unit type
+    Tuple {
+        fields: Vec<Idx<Self>>,
+    },
+    /// `[T]`
+    Array {
+        elem: Idx<Self>,
+    },
+    /// `T?`
+    Optional {
+        inner: Idx<Self>,
+    },
+}
+
+/// A binding from an enclosing scope that is referenced inside a closure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub name: SmolStr,
+    pub kind: BindingKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindingKind {
+    /// A binding introduced by `let`
+    Variable,
+    /// A binding introduced by `state`, writes to it are reactive
+    State,
+    /// A function or closure parameter
+    Param,
+    /// A function introduced by `func`
+    Function,
+}
@@ -0,0 +1,651 @@
+use ast_lowering::Database;
+use hir::{BinaryOp, Expr, Literal, Pat, Stmt, UnaryOp};
+use la_arena::Idx;
+use smol_str::SmolStr;
+
+/// Folds constant expressions and removes the definitions in function bodies
+/// that are never used. Top level definitions are kept, since every one of
+/// them is exported to the JS that loads the module. Expressions are
+/// rewritten in place in `database`, so the returned statements still index
+/// into it.
+///
+/// Folding follows the semantics of the interpreter and leaves anything that
+/// would fail at runtime, like a division by zero, in place. Integer
+/// arithmetic wraps around, as it does in the interpreter.
+pub fn optimize(stmts: Vec<Stmt>, database: &mut Database) -> Vec<Stmt> {
+    let mut folder = Folder { database };
+    let mut stmts: Vec<Stmt> = stmts
+        .into_iter()
+        .map(|stmt| folder.fold_stmt(stmt))
+        .collect();
+
+    for stmt in &mut stmts {
+        if let Stmt::FunctionDef { body, .. } = stmt {
+            remove_unused_defs(body, database);
+        }
+    }
+
+    stmts
+}
+
+/// A value that is known without running the program
+#[derive(Debug, Clone, PartialEq)]
+enum Constant {
+    Integer(i32),
+    Bool(bool),
+    /// The literal as written, with its quotes and escapes
+    String(SmolStr),
+}
+
+struct Folder<'a> {
+    database: &'a mut Database,
+}
+
+impl Folder<'_> {
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        match stmt {
+            Stmt::VariableDef { value, .. } | Stmt::StateDef { value, .. } | Stmt::Expr(value) => {
+                self.fold_idx(value);
+                stmt
+            }
+            Stmt::FunctionDef {
+                name,
+                params,
+                ret_ty,
+                body,
+                docs,
+            } => Stmt::FunctionDef {
+                name,
+                params,
+                ret_ty,
+                body: body.into_iter().map(|stmt| self.fold_stmt(stmt)).collect(),
+                docs,
+            },
+            Stmt::StructDef(_) | Stmt::EnumDef(_) => stmt,
+        }
+    }
+
+    fn fold_idx(&mut self, idx: Idx<Expr>) {
+        let expr = self.database.exprs()[idx].clone();
+        let folded = self.fold_expr(expr);
+        self.database.exprs_mut()[idx] = folded;
+    }
+
+    /// Folds the operands of `expr` before `expr` itself
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match &expr {
+            Expr::Missing | Expr::VariableRef { .. } | Expr::Literal(_) => {}
+            Expr::Binary { lhs, rhs, .. } => {
+                self.fold_idx(*lhs);
+                self.fold_idx(*rhs);
+            }
+            Expr::Unary { expr, .. } => self.fold_idx(*expr),
+            Expr::Assign { target, value } => {
+                self.fold_idx(*target);
+                self.fold_idx(*value);
+            }
+            Expr::Call { callee, args } => {
+                self.fold_idx(*callee);
+                for arg in args {
+                    self.fold_idx(*arg);
+                }
+            }
+            Expr::Closure { body, .. } => self.fold_idx(*body),
+            Expr::Record { fields, .. } => {
+                for (_, value) in fields {
+                    self.fold_idx(*value);
+                }
+            }
+            Expr::Variant { args, .. } => {
+                for arg in args {
+                    self.fold_idx(*arg);
+                }
+            }
+            Expr::Match { scrutinee, arms } => {
+                self.fold_idx(*scrutinee);
+                for arm in arms {
+                    self.fold_idx(arm.expr);
+                }
+            }
+        }
+
+        self.simplify(expr)
+    }
+
+    /// Rewrites `expr`, whose operands are already folded
+    fn simplify(&mut self, expr: Expr) -> Expr {
+        match &expr {
+            Expr::Binary { op, lhs, rhs } => {
+                let lhs_constant = self.constant(*lhs);
+                let rhs_constant = self.constant(*rhs);
+
+                if let (Some(lhs), Some(rhs)) = (&lhs_constant, &rhs_constant) {
+                    if let Some(folded) = fold_binary(op, lhs, rhs) {
+                        return self.constant_expr(folded);
+                    }
+                }
+
+                let (lhs_bool, rhs_bool) = match (&lhs_constant, &rhs_constant) {
+                    (Some(Constant::Bool(lhs)), _) => (Some(*lhs), None),
+                    (_, Some(Constant::Bool(rhs))) => (None, Some(*rhs)),
+                    _ => (None, None),
+                };
+
+                // `x && true` is `x`, and `x && false` is `false` when
+                // dropping `x` is not noticeable. The same goes for `||`
+                // with the booleans swapped.
+                let identity = match op {
+                    BinaryOp::And => true,
+                    BinaryOp::Or => false,
+                    _ => return expr,
+                };
+                match (lhs_bool, rhs_bool) {
+                    (Some(lhs), _) if lhs == identity => self.database.exprs()[*rhs].clone(),
+                    (Some(lhs), _) => Expr::Literal(Literal::Bool { value: lhs }),
+                    (_, Some(rhs)) if rhs == identity => self.database.exprs()[*lhs].clone(),
+                    (_, Some(rhs)) if self.is_pure(*lhs) => {
+                        Expr::Literal(Literal::Bool { value: rhs })
+                    }
+                    _ => expr,
+                }
+            }
+            Expr::Unary { op, expr: operand } => match (op, &self.database.exprs()[*operand]) {
+                (
+                    UnaryOp::Not,
+                    Expr::Unary {
+                        op: UnaryOp::Not,
+                        expr: inner,
+                    },
+                ) => self.database.exprs()[*inner].clone(),
+                (UnaryOp::Not, _) => match self.constant(*operand) {
+                    Some(Constant::Bool(value)) => Expr::Literal(Literal::Bool { value: !value }),
+                    _ => expr,
+                },
+                // A negated literal is already as small as it gets
+                (UnaryOp::Neg, Expr::Literal(_)) => expr,
+                (UnaryOp::Neg, _) => match self.constant(*operand) {
+                    Some(Constant::Integer(value)) => {
+                        self.constant_expr(Constant::Integer(value.wrapping_neg()))
+                    }
+                    _ => expr,
+                },
+            },
+            Expr::Match { scrutinee, arms } => {
+                let (enum_name, variant) = match &self.database.exprs()[*scrutinee] {
+                    Expr::Variant {
+                        enum_name, variant, ..
+                    } => (enum_name, variant),
+                    _ => return expr,
+                };
+
+                // Arms for other variants are never taken, and neither is
+                // anything after an arm that always matches
+                let mut reachable = Vec::new();
+                for arm in arms {
+                    match &arm.pat {
+                        Pat::Variant {
+                            enum_name: pat_enum_name,
+                            variant: pat_variant,
+                            ..
+                        } if pat_enum_name != enum_name || pat_variant != variant => continue,
+                        pat => {
+                            reachable.push(arm.clone());
+                            if is_irrefutable(pat) {
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                match reachable.first() {
+                    None => expr,
+                    Some(arm)
+                        if is_irrefutable(&arm.pat)
+                            && arm.pat.bindings().is_empty()
+                            && self.is_pure(*scrutinee) =>
+                    {
+                        self.database.exprs()[arm.expr].clone()
+                    }
+                    Some(_) => Expr::Match {
+                        scrutinee: *scrutinee,
+                        arms: reachable,
+                    },
+                }
+            }
+            _ => expr,
+        }
+    }
+
+    fn constant(&self, idx: Idx<Expr>) -> Option<Constant> {
+        match &self.database.exprs()[idx] {
+            Expr::Literal(Literal::Integer { value }) => {
+                i32::try_from((*value)?).ok().map(Constant::Integer)
+            }
+            Expr::Literal(Literal::Bool { value }) => Some(Constant::Bool(*value)),
+            Expr::Literal(Literal::String { value }) => Some(Constant::String(value.clone())),
+            // Negative numbers are negated literals, which also covers the
+            // smallest integer, whose literal on its own is too large
+            Expr::Unary {
+                op: UnaryOp::Neg,
+                expr,
+            } => match &self.database.exprs()[*expr] {
+                Expr::Literal(Literal::Integer { value }) => i32::try_from(-i128::from((*value)?))
+                    .ok()
+                    .map(Constant::Integer),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn constant_expr(&mut self, constant: Constant) -> Expr {
+        match constant {
+            Constant::Integer(value) if value < 0 => {
+                let literal = Expr::Literal(Literal::Integer {
+                    value: Some(value.unsigned_abs().into()),
+                });
+                Expr::Unary {
+                    op: UnaryOp::Neg,
+                    expr: self.database.exprs_mut().alloc(literal),
+                }
+            }
+            Constant::Integer(value) => Expr::Literal(Literal::Integer {
+                value: Some(value.unsigned_abs().into()),
+            }),
+            Constant::Bool(value) => Expr::Literal(Literal::Bool { value }),
+            Constant::String(value) => Expr::Literal(Literal::String { value }),
+        }
+    }
+
+    /// Whether evaluating the expression can be skipped without changing
+    /// what the program does. Arithmetic is not pure, as it can fail.
+    fn is_pure(&self, idx: Idx<Expr>) -> bool {
+        is_pure(&self.database.exprs()[idx], self.database)
+    }
+}
+
+fn is_pure(expr: &Expr, database: &Database) -> bool {
+    let pure = |idx: &Idx<Expr>| is_pure(&database.exprs()[*idx], database);
+
+    match expr {
+        Expr::Literal(_) | Expr::VariableRef { .. } | Expr::Closure { .. } => true,
+        Expr::Unary {
+            op: UnaryOp::Not,
+            expr,
+        } => pure(expr),
+        Expr::Unary {
+            op: UnaryOp::Neg,
+            expr,
+        } => matches!(database.exprs()[*expr], Expr::Literal(_)),
+        Expr::Binary { op, lhs, rhs } => {
+            let is_logic = matches!(
+                op,
+                BinaryOp::And
+                    | BinaryOp::Or
+                    | BinaryOp::Eq
+                    | BinaryOp::Ne
+                    | BinaryOp::Lt
+                    | BinaryOp::Le
+                    | BinaryOp::Gt
+                    | BinaryOp::Ge
+            );
+            is_logic && pure(lhs) && pure(rhs)
+        }
+        Expr::Record { fields, .. } => fields.iter().all(|(_, value)| pure(value)),
+        Expr::Variant { args, .. } => args.iter().all(pure),
+        Expr::Missing | Expr::Assign { .. } | Expr::Call { .. } | Expr::Match { .. } => false,
+    }
+}
+
+/// Whether `pat` matches every value of the type it is checked against
+fn is_irrefutable(pat: &Pat) -> bool {
+    match pat {
+        Pat::Wildcard | Pat::Bind { .. } => true,
+        Pat::Variant { fields, .. } => fields.iter().all(is_irrefutable),
+    }
+}
+
+fn fold_binary(op: &BinaryOp, lhs: &Constant, rhs: &Constant) -> Option<Constant> {
+    use Constant::{Bool, Integer};
+
+    let folded = match (lhs, rhs) {
+        (Integer(lhs), Integer(rhs)) => match op {
+            BinaryOp::Add => Integer(lhs.wrapping_add(*rhs)),
+            BinaryOp::Sub => Integer(lhs.wrapping_sub(*rhs)),
+            BinaryOp::Mul => Integer(lhs.wrapping_mul(*rhs)),
+            BinaryOp::Div if *rhs == 0 => return None,
+            BinaryOp::Div => Integer(lhs.wrapping_div(*rhs)),
+            BinaryOp::Rem if *rhs == 0 => return None,
+            BinaryOp::Rem => Integer(lhs.wrapping_rem(*rhs)),
+            BinaryOp::Eq => Bool(lhs == rhs),
+            BinaryOp::Ne => Bool(lhs != rhs),
+            BinaryOp::Lt => Bool(lhs < rhs),
+            BinaryOp::Le => Bool(lhs <= rhs),
+            BinaryOp::Gt => Bool(lhs > rhs),
+            BinaryOp::Ge => Bool(lhs >= rhs),
+            BinaryOp::And | BinaryOp::Or => return None,
+        },
+        (Bool(lhs), Bool(rhs)) => match op {
+            BinaryOp::And => Bool(*lhs && *rhs),
+            BinaryOp::Or => Bool(*lhs || *rhs),
+            BinaryOp::Eq => Bool(lhs == rhs),
+            BinaryOp::Ne => Bool(lhs != rhs),
+            _ => return None,
+        },
+        // Joining the literals as written keeps their escapes intact
+        (Constant::String(lhs), Constant::String(rhs)) if *op == BinaryOp::Add => {
+            let lhs = lhs.strip_suffix('"')?;
+            let rhs = rhs.strip_prefix('"')?;
+            Constant::String(format!("{}{}", lhs, rhs).into())
+        }
+        _ => return None,
+    };
+
+    Some(folded)
+}
+
+/// Removes the variables and functions of a function body that nothing
+/// refers to, until there are none left. A variable whose value is not pure
+/// is turned into an expression statement instead. The last statement is
+/// the body's result, so it is kept.
+fn remove_unused_defs(stmts: &mut Vec<Stmt>, database: &Database) {
+    for stmt in stmts.iter_mut() {
+        if let Stmt::FunctionDef { body, .. } = stmt {
+            remove_unused_defs(body, database);
+        }
+    }
+
+    'removed: loop {
+        for idx in 0..stmts.len() {
+            if idx == stmts.len() - 1 {
+                break;
+            }
+
+            let name = match &stmts[idx] {
+                Stmt::VariableDef { name, .. } | Stmt::FunctionDef { name, .. } => name.clone(),
+                _ => continue,
+            };
+
+            // Names are compared without resolving shadowing, which can only
+            // keep definitions that could have been removed
+            let is_used = stmts
+                .iter()
+                .enumerate()
+                .any(|(other, stmt)| other != idx && stmt_refers_to(stmt, &name, database));
+            if is_used {
+                continue;
+            }
+
+            match stmts.remove(idx) {
+                Stmt::VariableDef { value, .. } if !is_pure(&database.exprs()[value], database) => {
+                    stmts.insert(idx, Stmt::Expr(value));
+                }
+                _ => {}
+            }
+            continue 'removed;
+        }
+
+        break;
+    }
+}
+
+fn stmt_refers_to(stmt: &Stmt, name: &str, database: &Database) -> bool {
+    match stmt {
+        Stmt::VariableDef { value, .. } | Stmt::StateDef { value, .. } | Stmt::Expr(value) => {
+            expr_refers_to(&database.exprs()[*value], name, database)
+        }
+        Stmt::FunctionDef { body, .. } => {
+            body.iter().any(|stmt| stmt_refers_to(stmt, name, database))
+        }
+        Stmt::StructDef(_) | Stmt::EnumDef(_) => false,
+    }
+}
+
+fn expr_refers_to(expr: &Expr, name: &str, database: &Database) -> bool {
+    let refers_to = |idx: &Idx<Expr>| expr_refers_to(&database.exprs()[*idx], name, database);
+
+    match expr {
+        Expr::Missing | Expr::Literal(_) => false,
+        Expr::VariableRef { var } => var == name,
+        Expr::Binary { lhs, rhs, .. } => refers_to(lhs) || refers_to(rhs),
+        Expr::Unary { expr, .. } => refers_to(expr),
+        Expr::Assign { target, value } => refers_to(target) || refers_to(value),
+        Expr::Call { callee, args } => refers_to(callee) || args.iter().any(refers_to),
+        Expr::Closure { body, .. } => refers_to(body),
+        Expr::Record { fields, .. } => fields.iter().any(|(_, value)| refers_to(value)),
+        Expr::Variant { args, .. } => args.iter().any(refers_to),
+        Expr::Match { scrutinee, arms } => {
+            refers_to(scrutinee) || arms.iter().any(|arm| refers_to(&arm.expr))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+
+    fn check(input: &str, expected: Expect) {
+        let parse = parser::parse(input);
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (mut database, stmts) = ast_lowering::lower_root(root);
+
+        let before = codegen_js::Generator::new().generate(&stmts, &database);
+        let stmts = optimize(stmts, &mut database);
+        let after = codegen_js::Generator::new().generate(&stmts, &database);
+
+        expected.assert_eq(&format!("before:\n{}\nafter:\n{}", before, after));
+    }
+
+    #[test]
+    fn fold_arithmetic() {
+        check(
+            "let a = 1 + 2 * 3\nlet b = 10 % 4 - 5\nf(a, b)",
+            expect![[r#"
+before:
+let a = 1 + Math.imul(2, 3) | 0;
+let b = (10 % 4 | 0) - 5 | 0;
+f(a, b);
+
+after:
+let a = 7;
+let b = -3;
+f(a, b);
+"#]],
+        );
+    }
+
+    #[test]
+    fn leave_failing_arithmetic() {
+        check(
+            "let a = 1 / 0\nlet b = 7 / 2\nlet c = 8 / 2\nlet d = 2147483647 + 1\nf(a, b, c, d)",
+            expect![[r#"
+before:
+let a = 1 / 0 | 0;
+let b = 7 / 2 | 0;
+let c = 8 / 2 | 0;
+let d = 2147483647 + 1 | 0;
+f(a, b, c, d);
+
+after:
+let a = 1 / 0 | 0;
+let b = 3;
+let c = 4;
+let d = -2147483648;
+f(a, b, c, d);
+"#]],
+        );
+    }
+
+    #[test]
+    fn fold_comparisons_and_logic() {
+        check(
+            "let a = 1 < 2 && 2 == 3\nlet b = !(3 >= 3) || true != false\nf(a, b)",
+            expect![[r#"
+before:
+let a = 1 < 2 && 2 == 3;
+let b = !(3 >= 3) || true != false;
+f(a, b);
+
+after:
+let a = false;
+let b = true;
+f(a, b);
+"#]],
+        );
+    }
+
+    #[test]
+    fn simplify_logic_with_unknown_operands() {
+        check(
+            "let a = !!x && true\nlet b = false || g()\nlet c = x && false\nlet d = g() || true\nf(a, b, c, d)",
+            expect![[r#"
+before:
+let a = !!x && true;
+let b = false || g();
+let c = x && false;
+let d = g() || true;
+f(a, b, c, d);
+
+after:
+let a = x;
+let b = g();
+let c = false;
+let d = g() || true;
+f(a, b, c, d);
+"#]],
+        );
+    }
+
+    #[test]
+    fn fold_strings() {
+        check(
+            "let a = \"a\\n\" + \"b\"\nf(a)",
+            expect![[r#"
+before:
+let a = "a\n" + "b";
+f(a);
+
+after:
+let a = "a\nb";
+f(a);
+"#]],
+        );
+    }
+
+    #[test]
+    fn keep_unused_top_level_definitions() {
+        check(
+            "let unused = 1\nfunc helper() {\n  1\n}",
+            expect![[r#"
+before:
+let unused = 1;
+function helper() {
+return 1;
+}
+
+after:
+let unused = 1;
+function helper() {
+return 1;
+}
+"#]],
+        );
+    }
+
+    #[test]
+    fn remove_unused_local_definitions() {
+        check(
+            "state count = 0\nfunc main() {\n  let local = 2\n  let called = g()\n  let result = count\n}\nmain()",
+            expect![[r#"
+before:
+const count = $state(0);
+function main() {
+let local = 2;
+let called = g();
+let result = count.value;
+}
+main();
+
+after:
+const count = $state(0);
+function main() {
+g();
+let result = count.value;
+}
+main();
+"#]],
+        );
+    }
+
+    #[test]
+    fn remove_local_definitions_that_become_unused() {
+        check(
+            "func f() {\n  let a = 1\n  let b = |x| x + a\n  func c() {\n    b(1)\n  }\n  2\n}",
+            expect![[r#"
+before:
+function f() {
+let a = 1;
+let b = (x) => x + a | 0;
+function c() {
+return b(1);
+}
+return 2;
+}
+
+after:
+function f() {
+return 2;
+}
+"#]],
+        );
+    }
+
+    #[test]
+    fn keep_local_recursive_function_that_is_called() {
+        check(
+            "func outer() {\n  func f(n) {\n    f(n)\n  }\n  func g() {\n    g()\n  }\n  f(1)\n}",
+            expect![[r#"
+before:
+function outer() {
+function f(n) {
+return f(n);
+}
+function g() {
+return g();
+}
+return f(1);
+}
+
+after:
+function outer() {
+function f(n) {
+return f(n);
+}
+return f(1);
+}
+"#]],
+        );
+    }
+
+    #[test]
+    fn drop_arms_that_can_not_match() {
+        check(
+            "enum Status { Loading, Loaded(i32) }\n\
+             let a = match Status::Loading { Status::Loaded(n) => n, Status::Loading => 1, _ => 2 }\n\
+             let b = match Status::Loaded(1) { Status::Loading => 0, Status::Loaded(n) => n, _ => 2 }\n\
+             f(a, b)",
+            expect![[r#"
+before:
+let a = (($m) => { if ($m.tag === "Loaded") { const n = $m.values[0]; return n; } if ($m.tag === "Loading") { return 1; } { return 2; } })({ tag: "Loading" });
+let b = (($m) => { if ($m.tag === "Loading") { return 0; } if ($m.tag === "Loaded") { const n = $m.values[0]; return n; } { return 2; } })({ tag: "Loaded", values: [1] });
+f(a, b);
+
+after:
+let a = 1;
+let b = (($m) => { if ($m.tag === "Loaded") { const n = $m.values[0]; return n; } })({ tag: "Loaded", values: [1] });
+f(a, b);
+"#]],
+        );
+    }
+}
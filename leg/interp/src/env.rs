@@ -0,0 +1,54 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use smol_str::SmolStr;
+
+use crate::Value;
+
+/// The variables of a scope. Closures keep the environment they were
+/// created in alive and see later writes to it, like closures in JS.
+#[derive(Default)]
+pub(crate) struct Env {
+    vars: RefCell<HashMap<SmolStr, Value>>,
+    parent: Option<Rc<Env>>,
+}
+
+impl Env {
+    pub(crate) fn child(parent: &Rc<Env>) -> Rc<Env> {
+        Rc::new(Self {
+            vars: RefCell::default(),
+            parent: Some(Rc::clone(parent)),
+        })
+    }
+
+    pub(crate) fn define(&self, name: SmolStr, value: Value) {
+        self.vars.borrow_mut().insert(name, value);
+    }
+
+    /// Whether `name` is defined in this scope, rather than in one around it
+    pub(crate) fn defines(&self, name: &str) -> bool {
+        self.vars.borrow().contains_key(name)
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<Value> {
+        match self.vars.borrow().get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref()?.get(name),
+        }
+    }
+
+    /// Overwrites the innermost variable called `name`, returns false if
+    /// there is none
+    pub(crate) fn assign(&self, name: &str, value: Value) -> bool {
+        if let Some(slot) = self.vars.borrow_mut().get_mut(name) {
+            *slot = value;
+            return true;
+        }
+
+        match &self.parent {
+            Some(parent) => parent.assign(name, value),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use diagnostics::{Code, Diagnostic, Label};
+use smol_str::SmolStr;
+use text_size::TextRange;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    /// The expression that failed, missing for expressions that are not in
+    /// the source, like the ones created by optimizations
+    pub range: Option<TextRange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErrorKind {
+    /// `/` or `%` with a right hand side of zero
+    DivisionByZero,
+    /// A literal that does not fit in an integer, which validation reports
+    /// before anything runs. Arithmetic wraps around instead.
+    IntegerOverflow,
+    InvalidOperands {
+        op: &'static str,
+        lhs: &'static str,
+        rhs: Option<&'static str>,
+    },
+    InvalidAssignmentTarget,
+    UnboundVariable {
+        name: SmolStr,
+    },
+    NotCallable {
+        found: &'static str,
+    },
+    ArityMismatch {
+        expected: usize,
+        found: usize,
+    },
+    NoMatchingArm {
+        value: String,
+    },
+    StackOverflow,
+    /// Code that failed to parse was evaluated
+    MissingExpr,
+}
+
+impl RuntimeError {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let code = match self.kind {
+            RuntimeErrorKind::DivisionByZero => Code::DIVISION_BY_ZERO,
+            RuntimeErrorKind::IntegerOverflow => Code::INTEGER_OVERFLOW,
+            RuntimeErrorKind::InvalidOperands { .. } => Code::INVALID_OPERANDS,
+            RuntimeErrorKind::InvalidAssignmentTarget => Code::INVALID_ASSIGNMENT_TARGET,
+            RuntimeErrorKind::UnboundVariable { .. } => Code::UNBOUND_VARIABLE,
+            RuntimeErrorKind::NotCallable { .. } => Code::NOT_CALLABLE,
+            RuntimeErrorKind::ArityMismatch { .. } => Code::ARITY_MISMATCH,
+            RuntimeErrorKind::NoMatchingArm { .. } => Code::NO_MATCHING_ARM,
+            RuntimeErrorKind::StackOverflow => Code::STACK_OVERFLOW,
+            RuntimeErrorKind::MissingExpr => Code::MISSING_EXPRESSION,
+        };
+
+        let diagnostic = Diagnostic::error(code, self.kind.to_string());
+        let label = match self.kind {
+            RuntimeErrorKind::StackOverflow => format!(
+                "this call nests more than {} calls deep",
+                crate::MAX_CALL_DEPTH
+            ),
+            _ => String::new(),
+        };
+        let diagnostic = match self.range {
+            Some(range) => diagnostic.with_label(Label::primary(range, label)),
+            None => diagnostic,
+        };
+
+        match self.kind {
+            RuntimeErrorKind::IntegerOverflow => {
+                diagnostic.with_note(format!("integers range from {} to {}", i32::MIN, i32::MAX))
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::IntegerOverflow => write!(f, "integer overflow"),
+            Self::InvalidOperands {
+                op,
+                lhs,
+                rhs: Some(rhs),
+            } => write!(f, "cannot apply `{}` to {} and {}", op, lhs, rhs),
+            Self::InvalidOperands { op, lhs, rhs: None } => {
+                write!(f, "cannot apply `{}` to {}", op, lhs)
+            }
+            Self::InvalidAssignmentTarget => write!(f, "can only assign to a variable"),
+            Self::UnboundVariable { name } => write!(f, "`{}` is not defined", name),
+            Self::NotCallable { found } => write!(f, "cannot call a value of type {}", found),
+            Self::ArityMismatch { expected, found } => write!(
+                f,
+                "expected {} argument{}, but found {}",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            Self::NoMatchingArm { value } => write!(f, "no match arm matches {}", value),
+            Self::StackOverflow => write!(f, "stack overflow"),
+            Self::MissingExpr => write!(f, "cannot evaluate code that has errors"),
+        }
+    }
+}
@@ -0,0 +1,657 @@
+mod env;
+mod error;
+mod value;
+
+pub use error::{RuntimeError, RuntimeErrorKind};
+pub use value::{Function, Value};
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use ast_lowering::Database;
+use env::Env;
+use hir::{BinaryOp, Expr, Literal, Pat, Stmt, UnaryOp};
+use la_arena::Idx;
+use smol_str::SmolStr;
+use text_size::TextRange;
+use value::Body;
+
+/// Calls nested deeper than this are reported as a stack overflow, before
+/// the interpreter overflows its own stack
+pub(crate) const MAX_CALL_DEPTH: usize = 200;
+
+/// Evaluates HIR directly, without generating JS. Top level definitions are
+/// kept between calls to `run`, which lets the REPL build on earlier inputs.
+#[derive(Default)]
+pub struct Interpreter {
+    globals: Rc<Env>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `stmts`, which were lowered into `database`, and returns the
+    /// value of the last one if it is an expression
+    pub fn run(&mut self, stmts: &[Stmt], database: &Database) -> Result<Value, RuntimeError> {
+        let mut evaluator = Evaluator { database, depth: 0 };
+        evaluator.eval_stmts(stmts, &mut self.globals)
+    }
+}
+
+/// Runs a whole program with a fresh interpreter
+pub fn eval(stmts: &[Stmt], database: &Database) -> Result<Value, RuntimeError> {
+    Interpreter::new().run(stmts, database)
+}
+
+struct Evaluator<'a> {
+    database: &'a Database,
+    /// The number of calls that have not returned yet
+    depth: usize,
+}
+
+fn error(kind: RuntimeErrorKind, range: Option<TextRange>) -> RuntimeError {
+    RuntimeError { kind, range }
+}
+
+impl Evaluator<'_> {
+    /// Runs `stmts` in `env`, which is left as the scope that the last
+    /// statement ran in
+    fn eval_stmts(&mut self, stmts: &[Stmt], env: &mut Rc<Env>) -> Result<Value, RuntimeError> {
+        let mut value = Value::Unit;
+        for stmt in stmts {
+            value = self.eval_stmt(stmt, env)?;
+        }
+
+        Ok(value)
+    }
+
+    fn eval_stmt(&mut self, stmt: &Stmt, env: &mut Rc<Env>) -> Result<Value, RuntimeError> {
+        match stmt {
+            // State variables only differ from variables in the JS runtime,
+            // where writes to them are observed
+            Stmt::VariableDef { name, value, .. } | Stmt::StateDef { name, value, .. } => {
+                let value = self.eval_idx(*value, env)?;
+                make_room(env, name);
+                env.define(name.clone(), value);
+                Ok(Value::Unit)
+            }
+            Stmt::FunctionDef {
+                name, params, body, ..
+            } => {
+                make_room(env, name);
+                let function = Function {
+                    name: Some(name.clone()),
+                    params: params.iter().map(|param| param.name.clone()).collect(),
+                    body: Body::Block(body.clone()),
+                    env: Rc::clone(env),
+                };
+                env.define(name.clone(), Value::Function(Rc::new(function)));
+                Ok(Value::Unit)
+            }
+            Stmt::StructDef(_) | Stmt::EnumDef(_) => Ok(Value::Unit),
+            Stmt::Expr(expr) => self.eval_idx(*expr, env),
+        }
+    }
+
+    fn eval_idx(&mut self, expr: Idx<Expr>, env: &Rc<Env>) -> Result<Value, RuntimeError> {
+        let database = self.database;
+        self.eval_expr(&database.exprs()[expr], database.expr_range(expr), env)
+    }
+
+    fn eval_expr(
+        &mut self,
+        expr: &Expr,
+        range: Option<TextRange>,
+        env: &Rc<Env>,
+    ) -> Result<Value, RuntimeError> {
+        match expr {
+            Expr::Missing => Err(error(RuntimeErrorKind::MissingExpr, range)),
+            Expr::Literal(literal) => eval_literal(literal, range),
+            Expr::VariableRef { var } => env.get(var).ok_or_else(|| {
+                error(
+                    RuntimeErrorKind::UnboundVariable { name: var.clone() },
+                    range,
+                )
+            }),
+            Expr::Binary { op, lhs, rhs } => self.eval_binary(op, *lhs, *rhs, range, env),
+            Expr::Unary { op, expr } => {
+                // The smallest integer is a negated literal that is too large
+                // on its own
+                if let (UnaryOp::Neg, Expr::Literal(Literal::Integer { value })) =
+                    (op, &self.database.exprs()[*expr])
+                {
+                    return value
+                        .and_then(|value| i32::try_from(-i64::try_from(value).ok()?).ok())
+                        .map(Value::Integer)
+                        .ok_or_else(|| error(RuntimeErrorKind::IntegerOverflow, range));
+                }
+
+                let value = self.eval_idx(*expr, env)?;
+                match (op, value) {
+                    (UnaryOp::Neg, Value::Integer(value)) => {
+                        Ok(Value::Integer(value.wrapping_neg()))
+                    }
+                    (UnaryOp::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+                    (op, value) => Err(error(
+                        RuntimeErrorKind::InvalidOperands {
+                            op: unary_op_symbol(op),
+                            lhs: value.type_name(),
+                            rhs: None,
+                        },
+                        range,
+                    )),
+                }
+            }
+            Expr::Assign { target, value } => {
+                let value = self.eval_idx(*value, env)?;
+                let name = match &self.database.exprs()[*target] {
+                    Expr::VariableRef { var } => var,
+                    _ => {
+                        let range = self.database.expr_range(*target);
+                        return Err(error(RuntimeErrorKind::InvalidAssignmentTarget, range));
+                    }
+                };
+
+                // Assignments are expressions that evaluate to the assigned
+                // value, like they are in JS
+                if env.assign(name, value.clone()) {
+                    Ok(value)
+                } else {
+                    let range = self.database.expr_range(*target);
+                    Err(error(
+                        RuntimeErrorKind::UnboundVariable { name: name.clone() },
+                        range,
+                    ))
+                }
+            }
+            Expr::Call { callee, args } => {
+                let callee_range = self.database.expr_range(*callee);
+                let function = self.eval_idx(*callee, env)?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval_idx(*arg, env))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                self.call(function, args, callee_range, range)
+            }
+            Expr::Closure { params, body, .. } => Ok(Value::Function(Rc::new(Function {
+                name: None,
+                params: params.iter().map(|param| param.name.clone()).collect(),
+                body: Body::Expr(*body),
+                env: Rc::clone(env),
+            }))),
+            Expr::Record { name, fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, value)| Ok((field.clone(), self.eval_idx(*value, env)?)))
+                    .collect::<Result<_, RuntimeError>>()?;
+
+                Ok(Value::Record {
+                    name: name.clone(),
+                    fields,
+                })
+            }
+            Expr::Variant {
+                enum_name,
+                variant,
+                args,
+            } => {
+                let values = args
+                    .iter()
+                    .map(|arg| self.eval_idx(*arg, env))
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Value::Variant {
+                    enum_name: enum_name.clone(),
+                    variant: variant.clone(),
+                    values,
+                })
+            }
+            Expr::Match { scrutinee, arms } => {
+                let value = self.eval_idx(*scrutinee, env)?;
+
+                for arm in arms {
+                    let mut bindings = Vec::new();
+                    if match_pat(&arm.pat, &value, &mut bindings) {
+                        let env = Env::child(env);
+                        for (name, value) in bindings {
+                            env.define(name, value);
+                        }
+
+                        return self.eval_idx(arm.expr, &env);
+                    }
+                }
+
+                Err(error(
+                    RuntimeErrorKind::NoMatchingArm {
+                        value: value.to_string(),
+                    },
+                    self.database.expr_range(*scrutinee),
+                ))
+            }
+        }
+    }
+
+    fn eval_binary(
+        &mut self,
+        op: &BinaryOp,
+        lhs: Idx<Expr>,
+        rhs: Idx<Expr>,
+        range: Option<TextRange>,
+        env: &Rc<Env>,
+    ) -> Result<Value, RuntimeError> {
+        let invalid_operands = |lhs: &Value, rhs: Option<&Value>| {
+            error(
+                RuntimeErrorKind::InvalidOperands {
+                    op: binary_op_symbol(op),
+                    lhs: lhs.type_name(),
+                    rhs: rhs.map(Value::type_name),
+                },
+                range,
+            )
+        };
+
+        let lhs_value = self.eval_idx(lhs, env)?;
+
+        // `&&` and `||` only evaluate their right hand side when needed
+        if let BinaryOp::And | BinaryOp::Or = op {
+            let lhs_value = match lhs_value {
+                Value::Bool(value) => value,
+                other => return Err(invalid_operands(&other, None)),
+            };
+
+            if lhs_value == matches!(op, BinaryOp::Or) {
+                return Ok(Value::Bool(lhs_value));
+            }
+
+            return match self.eval_idx(rhs, env)? {
+                Value::Bool(value) => Ok(Value::Bool(value)),
+                other => Err(invalid_operands(&Value::Bool(lhs_value), Some(&other))),
+            };
+        }
+
+        let rhs_value = self.eval_idx(rhs, env)?;
+
+        match (op, &lhs_value, &rhs_value) {
+            (BinaryOp::Eq, lhs, rhs) => Ok(Value::Bool(lhs == rhs)),
+            (BinaryOp::Ne, lhs, rhs) => Ok(Value::Bool(lhs != rhs)),
+            (BinaryOp::Add, Value::String(lhs), Value::String(rhs)) => {
+                Ok(Value::String(format!("{}{}", lhs, rhs).into()))
+            }
+            (BinaryOp::Div | BinaryOp::Rem, Value::Integer(_), Value::Integer(0)) => Err(error(
+                RuntimeErrorKind::DivisionByZero,
+                self.database.expr_range(rhs),
+            )),
+            (_, Value::Integer(lhs), Value::Integer(rhs)) => {
+                let (lhs, rhs) = (*lhs, *rhs);
+                let result = match op {
+                    BinaryOp::Add => lhs.wrapping_add(rhs),
+                    BinaryOp::Sub => lhs.wrapping_sub(rhs),
+                    BinaryOp::Mul => lhs.wrapping_mul(rhs),
+                    // Division truncates towards zero
+                    BinaryOp::Div => lhs.wrapping_div(rhs),
+                    BinaryOp::Rem => lhs.wrapping_rem(rhs),
+                    _ => return Ok(Value::Bool(compare(op, lhs.cmp(&rhs)))),
+                };
+
+                Ok(Value::Integer(result))
+            }
+            (
+                BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge,
+                Value::String(lhs),
+                Value::String(rhs),
+            ) => Ok(Value::Bool(compare(op, lhs.cmp(rhs)))),
+            (_, lhs, rhs) => Err(invalid_operands(lhs, Some(rhs))),
+        }
+    }
+
+    fn call(
+        &mut self,
+        function: Value,
+        args: Vec<Value>,
+        callee_range: Option<TextRange>,
+        range: Option<TextRange>,
+    ) -> Result<Value, RuntimeError> {
+        let function = match function {
+            Value::Function(function) => function,
+            other => {
+                return Err(error(
+                    RuntimeErrorKind::NotCallable {
+                        found: other.type_name(),
+                    },
+                    callee_range,
+                ))
+            }
+        };
+
+        if args.len() != function.params.len() {
+            return Err(error(
+                RuntimeErrorKind::ArityMismatch {
+                    expected: function.params.len(),
+                    found: args.len(),
+                },
+                range,
+            ));
+        }
+
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(error(RuntimeErrorKind::StackOverflow, range));
+        }
+
+        let mut env = Env::child(&function.env);
+        for (param, arg) in function.params.iter().zip(args) {
+            env.define(param.clone(), arg);
+        }
+
+        self.depth += 1;
+        let result = match &function.body {
+            Body::Block(stmts) => self.eval_stmts(stmts, &mut env),
+            Body::Expr(body) => self.eval_idx(*body, &env),
+        };
+        self.depth -= 1;
+
+        // The call that hit the limit is deep inside of the recursion, and
+        // may be in a function from an earlier REPL input. The outermost
+        // call is the one in the code being run that started it.
+        match result {
+            Err(RuntimeError {
+                kind: RuntimeErrorKind::StackOverflow,
+                ..
+            }) if self.depth == 0 => Err(error(RuntimeErrorKind::StackOverflow, range)),
+            result => result,
+        }
+    }
+}
+
+/// Prepares `env` for a definition of `name`. Redefining a variable shadows
+/// it for the statements that follow, which continue in a new scope, so that
+/// closures and functions defined before keep seeing the old variable, like
+/// they do in the generated JS.
+fn make_room(env: &mut Rc<Env>, name: &str) {
+    if env.defines(name) {
+        *env = Env::child(env);
+    }
+}
+
+fn eval_literal(literal: &Literal, range: Option<TextRange>) -> Result<Value, RuntimeError> {
+    match literal {
+        Literal::Integer { value } => value
+            .and_then(|value| i32::try_from(value).ok())
+            .map(Value::Integer)
+            .ok_or_else(|| error(RuntimeErrorKind::IntegerOverflow, range)),
+        Literal::String { value } => Ok(Value::String(value::unquote(value).into())),
+        Literal::Bool { value } => Ok(Value::Bool(*value)),
+    }
+}
+
+/// Collects the values bound by `pat` into `bindings` if `value` matches it
+fn match_pat(pat: &Pat, value: &Value, bindings: &mut Vec<(SmolStr, Value)>) -> bool {
+    match (pat, value) {
+        (Pat::Wildcard, _) => true,
+        (Pat::Bind { name }, value) => {
+            bindings.push((name.clone(), value.clone()));
+            true
+        }
+        (
+            Pat::Variant {
+                enum_name,
+                variant,
+                fields,
+            },
+            Value::Variant {
+                enum_name: value_enum_name,
+                variant: value_variant,
+                values,
+            },
+        ) => {
+            // Missing fields match anything, like they do when checking
+            // exhaustiveness
+            enum_name == value_enum_name
+                && variant == value_variant
+                && fields
+                    .iter()
+                    .zip(values)
+                    .all(|(field, value)| match_pat(field, value, bindings))
+        }
+        (Pat::Variant { .. }, _) => false,
+    }
+}
+
+fn compare(op: &BinaryOp, ordering: Ordering) -> bool {
+    match op {
+        BinaryOp::Lt => ordering.is_lt(),
+        BinaryOp::Le => ordering.is_le(),
+        BinaryOp::Gt => ordering.is_gt(),
+        BinaryOp::Ge => ordering.is_ge(),
+        _ => unreachable!("only called with comparison operators"),
+    }
+}
+
+fn binary_op_symbol(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Rem => "%",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+        BinaryOp::Eq => "==",
+        BinaryOp::Lt => "<",
+        BinaryOp::Le => "<=",
+        BinaryOp::Ne => "!=",
+        BinaryOp::Gt => ">",
+        BinaryOp::Ge => ">=",
+    }
+}
+
+fn unary_op_symbol(op: &UnaryOp) -> &'static str {
+    match op {
+        UnaryOp::Neg => "-",
+        UnaryOp::Not => "!",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str) -> Result<Value, RuntimeError> {
+        let parse = parser::parse(input);
+        assert!(parse.diagnostics().is_empty(), "{}", parse.debug_tree());
+
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (database, stmts) = ast_lowering::lower_root(root);
+        eval(&stmts, &database)
+    }
+
+    fn check(input: &str, expected: &str) {
+        assert_eq!(run(input).unwrap().to_string(), expected);
+    }
+
+    fn check_error(input: &str, kind: RuntimeErrorKind, range: std::ops::Range<u32>) {
+        let error = run(input).unwrap_err();
+        assert_eq!(error.kind, kind);
+        assert_eq!(
+            error.range,
+            Some(TextRange::new(range.start.into(), range.end.into()))
+        );
+    }
+
+    #[test]
+    fn eval_arithmetic() {
+        check("1 + 2 * 3 - 4", "3");
+        check("(1 + 2) * 3", "9");
+        check("-7 / 2", "-3");
+        check("-7 % 2", "-1");
+    }
+
+    #[test]
+    fn eval_comparisons_and_logic() {
+        check("1 < 2 && 2 >= 2", "true");
+        check("1 == 2 || 3 != 3", "false");
+        check("\"a\" < \"b\"", "true");
+        check("\"a\" == \"a\"", "true");
+    }
+
+    #[test]
+    fn logic_short_circuits() {
+        check("false && 1 / 0 == 0", "false");
+        check("true || 1 / 0 == 0", "true");
+    }
+
+    #[test]
+    fn eval_strings() {
+        check("\"foo\" + \"bar\"", "\"foobar\"");
+        check("\"a\\\"b\\n\"", "\"a\\\"b\\n\"");
+    }
+
+    #[test]
+    fn division_by_zero_points_at_divisor() {
+        check_error(
+            "let a = 1\nlet b = a / 0",
+            RuntimeErrorKind::DivisionByZero,
+            22..23,
+        );
+        check_error("5 % (1 - 1)", RuntimeErrorKind::DivisionByZero, 4..11);
+    }
+
+    #[test]
+    fn integer_arithmetic_wraps_around() {
+        check("2147483647 + 1", "-2147483648");
+        check("-2147483648 - 1", "2147483647");
+        check("65536 * 65536", "0");
+        check("-2147483648 / -1", "-2147483648");
+        check("-2147483648 % -1", "0");
+        check("-(-2147483648)", "-2147483648");
+    }
+
+    #[test]
+    fn integer_literals_have_to_fit() {
+        check("-2147483648", "-2147483648");
+        check_error("1 + 2147483648", RuntimeErrorKind::IntegerOverflow, 4..14);
+        check_error("-2147483649", RuntimeErrorKind::IntegerOverflow, 0..11);
+    }
+
+    #[test]
+    fn invalid_operands() {
+        check_error(
+            "1 + true",
+            RuntimeErrorKind::InvalidOperands {
+                op: "+",
+                lhs: "integer",
+                rhs: Some("bool"),
+            },
+            0..8,
+        );
+    }
+
+    #[test]
+    fn variables_and_assignment() {
+        check("let a = 1\na = a + 1\na", "2");
+        check("state count = 0\ncount = 5", "5");
+        check_error(
+            "1 + b",
+            RuntimeErrorKind::UnboundVariable { name: "b".into() },
+            4..5,
+        );
+    }
+
+    #[test]
+    fn functions() {
+        check("func add(a, b) {\n  a + b\n}\nadd(1, 2)", "3");
+        check("func f() {\n  let a = 1\n}\nf()", "()");
+    }
+
+    #[test]
+    fn recursion() {
+        check(
+            "enum Nat { Zero, Succ(Nat) }\n\
+             func count(n) {\n  \
+               match n { Nat::Zero => 0, Nat::Succ(m) => 1 + count(m) }\n\
+             }\n\
+             count(Nat::Succ(Nat::Succ(Nat::Succ(Nat::Zero))))",
+            "3",
+        );
+    }
+
+    #[test]
+    fn closures_capture_by_reference() {
+        check(
+            "let count = 0\nlet inc = || count = count + 1\ninc()\ninc()\ncount",
+            "2",
+        );
+        check("let add = |a| |b| a + b\nadd(1)(2)", "3");
+    }
+
+    #[test]
+    fn redefinitions_shadow_for_what_follows() {
+        check("let a = 1\nlet f = || a\nlet a = 2\nf() * 10 + a", "12");
+        check("let a = 1\nlet a = a + 1\na = a * 5\na", "10");
+        check(
+            "func f() { 1 }\nlet g = f\nfunc f() { 2 }\ng() * 10 + f()",
+            "12",
+        );
+        check("func f(a) {\n  let a = a + 1\n  a\n}\nf(1)", "2");
+    }
+
+    #[test]
+    fn calling_with_wrong_arguments() {
+        check_error(
+            "let f = |a| a\n1 + f(1, 2)",
+            RuntimeErrorKind::ArityMismatch {
+                expected: 1,
+                found: 2,
+            },
+            18..25,
+        );
+        check_error(
+            "let a = 1\na(2)",
+            RuntimeErrorKind::NotCallable { found: "integer" },
+            10..11,
+        );
+    }
+
+    #[test]
+    fn unbounded_recursion_overflows() {
+        let error = run("func f(n) {\n  f(n + 1)\n}\nf(0)").unwrap_err();
+        assert_eq!(error.kind, RuntimeErrorKind::StackOverflow);
+        assert_eq!(error.range, Some(TextRange::new(25.into(), 29.into())));
+    }
+
+    #[test]
+    fn records_and_variants() {
+        check(
+            "struct User { name: String, age: i32 }\nUser { name: \"a\", age: 1 + 1 }",
+            "User { name: \"a\", age: 2 }",
+        );
+        check(
+            "enum Status { Loading, Loaded(i32) }\nStatus::Loaded(1)",
+            "Status::Loaded(1)",
+        );
+    }
+
+    #[test]
+    fn match_binds_fields() {
+        let status = "enum Status { Loading, Loaded(i32) }\n";
+        check(
+            &format!(
+                "{}match Status::Loaded(2) {{ Status::Loading => 0, Status::Loaded(n) => n * 10 }}",
+                status
+            ),
+            "20",
+        );
+        check_error(
+            &format!(
+                "{}match Status::Loading {{ Status::Loaded(_) => 0 }}",
+                status
+            ),
+            RuntimeErrorKind::NoMatchingArm {
+                value: "Status::Loading".to_string(),
+            },
+            43..58,
+        );
+    }
+}
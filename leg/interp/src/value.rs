@@ -0,0 +1,170 @@
+use std::fmt;
+use std::rc::Rc;
+
+use hir::{Expr, Stmt};
+use la_arena::Idx;
+use smol_str::SmolStr;
+
+use crate::env::Env;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// The value of statements and of functions without a result
+    Unit,
+    /// Integers are 32 bit, and arithmetic wraps around like it does in the
+    /// generated JS
+    Integer(i32),
+    Bool(bool),
+    String(Rc<str>),
+    Function(Rc<Function>),
+    Record {
+        name: SmolStr,
+        fields: Vec<(SmolStr, Value)>,
+    },
+    Variant {
+        enum_name: SmolStr,
+        variant: SmolStr,
+        values: Vec<Value>,
+    },
+}
+
+impl Value {
+    /// The kind of value, as used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Unit => "unit",
+            Self::Integer(_) => "integer",
+            Self::Bool(_) => "bool",
+            Self::String(_) => "string",
+            Self::Function(_) => "function",
+            Self::Record { .. } => "record",
+            Self::Variant { .. } => "enum",
+        }
+    }
+}
+
+/// Functions are equal only to themselves, everything else is compared
+/// structurally
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Unit, Self::Unit) => true,
+            (Self::Integer(a), Self::Integer(b)) => a == b,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Function(a), Self::Function(b)) => Rc::ptr_eq(a, b),
+            (
+                Self::Record { name, fields },
+                Self::Record {
+                    name: other_name,
+                    fields: other_fields,
+                },
+            ) => name == other_name && fields == other_fields,
+            (
+                Self::Variant {
+                    enum_name,
+                    variant,
+                    values,
+                },
+                Self::Variant {
+                    enum_name: other_enum_name,
+                    variant: other_variant,
+                    values: other_values,
+                },
+            ) => enum_name == other_enum_name && variant == other_variant && values == other_values,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unit => write!(f, "()"),
+            Self::Integer(value) => write!(f, "{}", value),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::String(value) => write!(f, "{:?}", value),
+            Self::Function(function) => match &function.name {
+                Some(name) => write!(f, "<func {}>", name),
+                None => write!(f, "<closure>"),
+            },
+            Self::Record { name, fields } => {
+                write!(f, "{} {{", name)?;
+                for (idx, (field, value)) in fields.iter().enumerate() {
+                    let separator = if idx == 0 { " " } else { ", " };
+                    write!(f, "{}{}: {}", separator, field, value)?;
+                }
+                if fields.is_empty() {
+                    write!(f, "}}")
+                } else {
+                    write!(f, " }}")
+                }
+            }
+            Self::Variant {
+                enum_name,
+                variant,
+                values,
+            } => {
+                write!(f, "{}::{}", enum_name, variant)?;
+                if !values.is_empty() {
+                    let values: Vec<_> = values.iter().map(ToString::to_string).collect();
+                    write!(f, "({})", values.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A `func` or a closure, along with the environment it was created in
+pub struct Function {
+    pub(crate) name: Option<SmolStr>,
+    pub(crate) params: Vec<SmolStr>,
+    pub(crate) body: Body,
+    pub(crate) env: Rc<Env>,
+}
+
+pub(crate) enum Body {
+    Block(Vec<Stmt>),
+    Expr(Idx<Expr>),
+}
+
+/// The environment is left out, as it usually holds the function itself
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name)
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The contents of a string literal, which is lowered with its quotes and
+/// escapes as written in the source
+pub(crate) fn unquote(literal: &str) -> String {
+    let inner = literal
+        .strip_prefix('"')
+        .and_then(|literal| literal.strip_suffix('"'))
+        .unwrap_or(literal);
+
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some('0') => value.push('\0'),
+            Some(escaped) => value.push(escaped),
+            None => value.push('\\'),
+        }
+    }
+
+    value
+}
@@ -1,19 +1,17 @@
 mod token_kind;
 
-use logos::Logos;
-use std::ops::Range as StdRange;
 use text_size::{TextRange, TextSize};
 pub use token_kind::TokenKind;
+use wlang_lexer::{tokenize, SyntaxErrorKind, SyntaxKind};
 
 pub struct Lexer<'a> {
-    inner: logos::Lexer<'a, TokenKind>,
+    input: &'a str,
+    offset: usize,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Lexer {
-        Lexer {
-            inner: TokenKind::lexer(input),
-        }
+        Lexer { input, offset: 0 }
     }
 }
 
@@ -21,18 +19,48 @@ impl<'a> Iterator for Lexer<'a> {
     type Item = Token<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let kind = self.inner.next()?;
-        let text = self.inner.slice();
-
-        let range = {
-            let StdRange { start, end } = self.inner.span();
-            let start = TextSize::try_from(start).unwrap();
-            let end = TextSize::try_from(end).unwrap();
+        let rest = &self.input[self.offset..];
+        let (token, error) = tokenize(rest).next()?;
+        let text = &rest[..token.len];
+        let start = TextSize::try_from(self.offset).unwrap();
+
+        let mut error = error.map(|error| {
+            let range = error.range_in(&token);
+            let kind = match error {
+                SyntaxErrorKind::UnclosedStringLiteral => LexErrorKind::UnterminatedString,
+                SyntaxErrorKind::UnclosedBlockComment => LexErrorKind::UnterminatedComment,
+                SyntaxErrorKind::InvalidCharacter(c) => LexErrorKind::InvalidCharacter(c),
+                SyntaxErrorKind::InvalidNumberSuffix { .. } => LexErrorKind::InvalidNumberSuffix,
+                SyntaxErrorKind::InvalidEscape { escaped, .. } => {
+                    LexErrorKind::InvalidEscape(escaped)
+                }
+            };
+
+            LexError {
+                kind,
+                range: TextRange::new(
+                    start + TextSize::try_from(range.start).unwrap(),
+                    start + TextSize::try_from(range.end).unwrap(),
+                ),
+            }
+        });
+
+        // A float is an error as a whole, also when its suffix is invalid
+        if token.kind == SyntaxKind::Float {
+            error = Some(LexError {
+                kind: LexErrorKind::UnsupportedFloat,
+                range: TextRange::at(start, TextSize::of(text)),
+            });
+        }
 
-            TextRange::new(start, end)
-        };
+        self.offset += token.len;
 
-        Some(Self::Item { kind, text, range })
+        Some(Self::Item {
+            kind: token.kind.into(),
+            text,
+            range: TextRange::at(start, TextSize::of(text)),
+            error,
+        })
     }
 }
 
@@ -41,6 +69,32 @@ pub struct Token<'a> {
     pub kind: TokenKind,
     pub text: &'a str,
     pub range: TextRange,
+    /// Tokens with an error are still lexed as well as possible, like an
+    /// unterminated string as a `String` that runs to the end of the input
+    pub error: Option<LexError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LexErrorKind {
+    /// The input ends inside of a string, the range is its opening `"`
+    UnterminatedString,
+    /// The input ends inside of a `/*` comment, the range is its `/*`
+    UnterminatedComment,
+    /// A character that does not start any token
+    InvalidCharacter(char),
+    /// Letters right after the digits of a number, the range is the letters
+    InvalidNumberSuffix,
+    /// A `\` in a string followed by a character that can't be escaped
+    InvalidEscape(char),
+    /// A number with a fraction or an exponent, which is lexed so that it
+    /// can be reported as a whole
+    UnsupportedFloat,
 }
 
 #[cfg(test)]
@@ -80,6 +134,41 @@ mod tests {
         check(":", TokenKind::Colon);
     }
 
+    #[test]
+    fn lex_question() {
+        check("?", TokenKind::Question);
+    }
+
+    #[test]
+    fn lex_colon_colon() {
+        check("::", TokenKind::ColonColon);
+    }
+
+    #[test]
+    fn lex_fat_arrow() {
+        check("=>", TokenKind::FatArrow);
+    }
+
+    #[test]
+    fn lex_underscore() {
+        check("_", TokenKind::Underscore);
+    }
+
+    #[test]
+    fn lex_struct() {
+        check("struct", TokenKind::StructKeyword);
+    }
+
+    #[test]
+    fn lex_enum() {
+        check("enum", TokenKind::EnumKeyword);
+    }
+
+    #[test]
+    fn lex_match() {
+        check("match", TokenKind::MatchKeyword);
+    }
+
     #[test]
     fn lex_func() {
         check("func", TokenKind::FuncKeyword);
@@ -115,6 +204,49 @@ mod tests {
         check("__abc123", TokenKind::Ident);
     }
 
+    #[test]
+    fn lex_tab_as_whitespace() {
+        check(" \t\n", TokenKind::Whitespace);
+    }
+
+    #[test]
+    fn lex_carriage_return_as_whitespace() {
+        check("\r\n", TokenKind::Whitespace);
+    }
+
+    #[test]
+    fn lex_unicode_whitespace() {
+        check("\u{a0}\u{2003}\u{3000}", TokenKind::Whitespace);
+    }
+
+    #[test]
+    fn lex_unicode_identifier() {
+        check("größe", TokenKind::Ident);
+        check("名前", TokenKind::Ident);
+        check("_ß1", TokenKind::Ident);
+        check("cafe\u{301}", TokenKind::Ident);
+    }
+
+    #[test]
+    fn lex_keyword_prefix_as_identifier() {
+        check("letter", TokenKind::Ident);
+    }
+
+    #[test]
+    fn lex_number_with_separators() {
+        check("1_000", TokenKind::Integer);
+    }
+
+    #[test]
+    fn lex_float_as_error() {
+        check("1.5", TokenKind::Error);
+    }
+
+    #[test]
+    fn lex_unterminated_string() {
+        check("\"abc", TokenKind::String);
+    }
+
     #[test]
     fn lex_string_literal() {
         check("\"hello world\"", TokenKind::String);
@@ -135,6 +267,11 @@ mod tests {
         check("-", TokenKind::Minus);
     }
 
+    #[test]
+    fn lex_arrow() {
+        check("->", TokenKind::Arrow);
+    }
+
     #[test]
     fn lex_star() {
         check("*", TokenKind::Star);
@@ -190,6 +327,11 @@ mod tests {
         check("||", TokenKind::BarBar);
     }
 
+    #[test]
+    fn lex_bar() {
+        check("|", TokenKind::Bar);
+    }
+
     #[test]
     fn lex_left_paren() {
         check("(", TokenKind::LParen);
@@ -244,4 +386,148 @@ mod tests {
     fn lex_comment() {
         check("// foo", TokenKind::Comment);
     }
+
+    #[test]
+    fn lex_doc_comment() {
+        check("/// foo", TokenKind::DocComment);
+        check("///", TokenKind::DocComment);
+    }
+
+    #[test]
+    fn lex_four_slashes_as_comment() {
+        check("//// foo", TokenKind::Comment);
+    }
+
+    #[test]
+    fn lex_block_comment() {
+        check("/* foo\nbar */", TokenKind::Comment);
+        check("/**/", TokenKind::Comment);
+    }
+
+    #[test]
+    fn lex_nested_block_comment() {
+        check("/* a /* b */ c */", TokenKind::Comment);
+    }
+
+    #[test]
+    fn lex_code_after_block_comment() {
+        let kinds: Vec<_> = Lexer::new("/* a */+/* b */")
+            .map(|token| (token.kind, token.text))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            [
+                (TokenKind::Comment, "/* a */"),
+                (TokenKind::Plus, "+"),
+                (TokenKind::Comment, "/* b */"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lex_ranges() {
+        let ranges: Vec<_> = Lexer::new("let ab")
+            .map(|token| (token.kind, token.range))
+            .collect();
+
+        assert_eq!(
+            ranges,
+            [
+                (TokenKind::LetKeyword, TextRange::new(0.into(), 3.into())),
+                (TokenKind::Whitespace, TextRange::new(3.into(), 4.into())),
+                (TokenKind::Ident, TextRange::new(4.into(), 6.into())),
+            ],
+        );
+    }
+
+    fn error(input: &str) -> Option<LexError> {
+        Lexer::new(input).find_map(|token| token.error)
+    }
+
+    fn range(range: std::ops::Range<u32>) -> TextRange {
+        TextRange::new(range.start.into(), range.end.into())
+    }
+
+    #[test]
+    fn lex_unterminated_block_comment() {
+        let token = Lexer::new("/* a /* b */ c").next().unwrap();
+
+        assert_eq!(token.kind, TokenKind::Comment);
+        assert_eq!(token.text, "/* a /* b */ c");
+        assert_eq!(
+            token.error,
+            Some(LexError {
+                kind: LexErrorKind::UnterminatedComment,
+                range: range(0..2),
+            }),
+        );
+        assert_eq!(error("/* a */"), None);
+        assert_eq!(error("// /*"), None);
+    }
+
+    #[test]
+    fn lex_unterminated_string_error() {
+        assert_eq!(
+            error("a \"b\\\""),
+            Some(LexError {
+                kind: LexErrorKind::UnterminatedString,
+                range: range(2..3),
+            }),
+        );
+    }
+
+    #[test]
+    fn lex_invalid_character_error() {
+        assert_eq!(
+            error("a # b"),
+            Some(LexError {
+                kind: LexErrorKind::InvalidCharacter('#'),
+                range: range(2..3),
+            }),
+        );
+        assert_eq!(error("a && b || c"), None);
+    }
+
+    #[test]
+    fn lex_invalid_number_suffix_error() {
+        let token = Lexer::new("12px").next().unwrap();
+
+        assert_eq!(token.kind, TokenKind::Integer);
+        assert_eq!(
+            token.error,
+            Some(LexError {
+                kind: LexErrorKind::InvalidNumberSuffix,
+                range: range(2..4),
+            }),
+        );
+    }
+
+    #[test]
+    fn lex_float_error() {
+        for input in ["1.5", "1e10", "1.5px"] {
+            let token = Lexer::new(input).next().unwrap();
+
+            assert_eq!(token.kind, TokenKind::Error);
+            assert_eq!(
+                token.error,
+                Some(LexError {
+                    kind: LexErrorKind::UnsupportedFloat,
+                    range: range(0..input.len() as u32),
+                }),
+            );
+        }
+    }
+
+    #[test]
+    fn lex_invalid_escape_error() {
+        assert_eq!(
+            error("\"a\\n\\é\""),
+            Some(LexError {
+                kind: LexErrorKind::InvalidEscape('é'),
+                range: range(4..7),
+            }),
+        );
+        assert_eq!(error(r#""\\ \" \n \t \r \0""#), None);
+    }
 }
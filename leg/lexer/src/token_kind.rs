@@ -1,136 +1,69 @@
-use logos::Logos;
 use std::fmt;
 use syntax::syntax_kind::SyntaxKind;
+use wlang_lexer::SyntaxKind as Kind;
 
-#[derive(Debug, Copy, Clone, PartialEq, Logos)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum TokenKind {
-    #[regex("[ \n]+")]
     Whitespace,
-
-    #[token(";")]
     Semicolon,
-
-    #[token(":")]
     Colon,
-
-    #[token(",")]
+    ColonColon,
+    Question,
     Comma,
-
-    #[regex("//.*")]
     Comment,
-
-    #[regex("[a-zA-Z_$][a-zA-Z0-9_$]*")]
+    /// `///` documents the item that follows it, `////` is a plain comment
+    DocComment,
     Ident,
-
-    #[regex("[0-9]+")]
+    Underscore,
     Integer,
-
-    #[regex("\"(\\\\.|[^\"\\\\])*\"")]
     String,
-
-    #[token("true")]
     True,
-
-    #[token("false")]
     False,
-
-    #[token("(")]
     LParen,
-
-    #[token(")")]
     RParen,
-
-    #[token("{")]
     LBrace,
-
-    #[token("}")]
     RBrace,
-
-    #[token("[")]
     LBracket,
-
-    #[token("]")]
     RBracket,
-
-    #[token("/")]
     Slash,
-
-    #[token("+")]
     Plus,
-
-    #[token("-")]
     Minus,
-
-    #[token("*")]
+    Arrow,
     Star,
-
-    #[token("%")]
     Percent,
-
-    #[token("=")]
     Equals,
-
-    #[token("==")]
+    FatArrow,
     EqualsEquals,
-
-    #[token("!=")]
     BangEquals,
-
-    #[token("!")]
     Bang,
-
-    #[token(">")]
     GreaterThan,
-
-    #[token(">=")]
     GreaterThanEqual,
-
-    #[token("<")]
     LessThan,
-
-    #[token("<=")]
     LessThanEqual,
-
-    #[token("&&")]
     AmpersandAmpersand,
-
-    #[token("||")]
     BarBar,
-
-    #[token("func")]
+    Bar,
     FuncKeyword,
-
-    #[token("comp")]
     CompKeyword,
-
-    #[token("let")]
     LetKeyword,
-
-    #[token("state")]
     StateKeyword,
-
-    #[token("mut")]
     MutKeyword,
-
-    #[token("effect")]
+    StructKeyword,
+    EnumKeyword,
+    MatchKeyword,
     EffectKeyword,
-
-    #[token("onMount")]
     OnMountKeyword,
-
-    #[token("onUpdate")]
     OnUpdateKeyword,
-
-    #[token("onDestroy")]
     OnDestroyKeyword,
-
-    #[error]
     Error,
 }
 
 impl TokenKind {
     pub fn is_trivia(self) -> bool {
-        matches!(self, TokenKind::Whitespace | TokenKind::Comment)
+        matches!(
+            self,
+            TokenKind::Whitespace | TokenKind::Comment | TokenKind::DocComment
+        )
     }
 }
 
@@ -140,9 +73,13 @@ impl From<TokenKind> for SyntaxKind {
             TokenKind::Whitespace => SyntaxKind::Whitespace,
             TokenKind::Semicolon => SyntaxKind::Semicolon,
             TokenKind::Colon => SyntaxKind::Colon,
+            TokenKind::ColonColon => SyntaxKind::ColonColon,
+            TokenKind::Question => SyntaxKind::Question,
             TokenKind::Comma => SyntaxKind::Comma,
             TokenKind::Comment => SyntaxKind::Comment,
+            TokenKind::DocComment => SyntaxKind::DocComment,
             TokenKind::Ident => SyntaxKind::Ident,
+            TokenKind::Underscore => SyntaxKind::Underscore,
             TokenKind::Integer => SyntaxKind::Integer,
             TokenKind::String => SyntaxKind::String,
             TokenKind::True => SyntaxKind::True,
@@ -156,9 +93,11 @@ impl From<TokenKind> for SyntaxKind {
             TokenKind::Slash => SyntaxKind::Slash,
             TokenKind::Plus => SyntaxKind::Plus,
             TokenKind::Minus => SyntaxKind::Minus,
+            TokenKind::Arrow => SyntaxKind::Arrow,
             TokenKind::Star => SyntaxKind::Star,
             TokenKind::Percent => SyntaxKind::Percent,
             TokenKind::Equals => SyntaxKind::Equals,
+            TokenKind::FatArrow => SyntaxKind::FatArrow,
             TokenKind::EqualsEquals => SyntaxKind::EqualsEquals,
             TokenKind::BangEquals => SyntaxKind::BangEquals,
             TokenKind::Bang => SyntaxKind::Bang,
@@ -168,11 +107,15 @@ impl From<TokenKind> for SyntaxKind {
             TokenKind::LessThanEqual => SyntaxKind::LessThanEqual,
             TokenKind::AmpersandAmpersand => SyntaxKind::AmpersandAmpersand,
             TokenKind::BarBar => SyntaxKind::BarBar,
+            TokenKind::Bar => SyntaxKind::Bar,
             TokenKind::FuncKeyword => SyntaxKind::FuncKeyword,
             TokenKind::CompKeyword => SyntaxKind::CompKeyword,
             TokenKind::LetKeyword => SyntaxKind::LetKeyword,
             TokenKind::StateKeyword => SyntaxKind::StateKeyword,
             TokenKind::MutKeyword => SyntaxKind::MutKeyword,
+            TokenKind::StructKeyword => SyntaxKind::StructKeyword,
+            TokenKind::EnumKeyword => SyntaxKind::EnumKeyword,
+            TokenKind::MatchKeyword => SyntaxKind::MatchKeyword,
             TokenKind::EffectKeyword => SyntaxKind::EffectKeyword,
             TokenKind::OnMountKeyword => SyntaxKind::OnMountKeyword,
             TokenKind::OnUpdateKeyword => SyntaxKind::OnUpdateKeyword,
@@ -182,22 +125,91 @@ impl From<TokenKind> for SyntaxKind {
     }
 }
 
+impl From<Kind> for TokenKind {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Whitespace => TokenKind::Whitespace,
+            Kind::Semicolon => TokenKind::Semicolon,
+            Kind::Colon => TokenKind::Colon,
+            Kind::ColonColon => TokenKind::ColonColon,
+            Kind::Question => TokenKind::Question,
+            Kind::Comma => TokenKind::Comma,
+            Kind::Comment => TokenKind::Comment,
+            Kind::DocComment => TokenKind::DocComment,
+            Kind::Identifier => TokenKind::Ident,
+            Kind::Underscore => TokenKind::Underscore,
+            Kind::Integer => TokenKind::Integer,
+            Kind::String => TokenKind::String,
+            Kind::True => TokenKind::True,
+            Kind::False => TokenKind::False,
+            Kind::LeftParen => TokenKind::LParen,
+            Kind::RightParen => TokenKind::RParen,
+            Kind::LeftBrace => TokenKind::LBrace,
+            Kind::RightBrace => TokenKind::RBrace,
+            Kind::LeftBracket => TokenKind::LBracket,
+            Kind::RightBracket => TokenKind::RBracket,
+            Kind::Slash => TokenKind::Slash,
+            Kind::Plus => TokenKind::Plus,
+            Kind::Minus => TokenKind::Minus,
+            Kind::Arrow => TokenKind::Arrow,
+            Kind::Star => TokenKind::Star,
+            Kind::Percent => TokenKind::Percent,
+            Kind::Equals => TokenKind::Equals,
+            Kind::FatArrow => TokenKind::FatArrow,
+            Kind::EqualsEquals => TokenKind::EqualsEquals,
+            Kind::BangEquals => TokenKind::BangEquals,
+            Kind::Bang => TokenKind::Bang,
+            Kind::GreaterThan => TokenKind::GreaterThan,
+            Kind::GreaterThanEqual => TokenKind::GreaterThanEqual,
+            Kind::LessThan => TokenKind::LessThan,
+            Kind::LessThanEqual => TokenKind::LessThanEqual,
+            Kind::AmpersandAmpersand => TokenKind::AmpersandAmpersand,
+            Kind::BarBar => TokenKind::BarBar,
+            Kind::Bar => TokenKind::Bar,
+            Kind::FuncKeyword => TokenKind::FuncKeyword,
+            Kind::CompKeyword => TokenKind::CompKeyword,
+            Kind::LetKeyword => TokenKind::LetKeyword,
+            Kind::StateKeyword => TokenKind::StateKeyword,
+            Kind::MutKeyword => TokenKind::MutKeyword,
+            Kind::StructKeyword => TokenKind::StructKeyword,
+            Kind::EnumKeyword => TokenKind::EnumKeyword,
+            Kind::MatchKeyword => TokenKind::MatchKeyword,
+            Kind::EffectKeyword => TokenKind::EffectKeyword,
+            Kind::OnMountKeyword => TokenKind::OnMountKeyword,
+            Kind::OnUpdateKeyword => TokenKind::OnUpdateKeyword,
+            Kind::OnDestroyKeyword => TokenKind::OnDestroyKeyword,
+            // There are no floats in the language yet, the lexer reports
+            // them as errors
+            Kind::Float | Kind::Error => TokenKind::Error,
+            Kind::Tombstone | Kind::EndOfFile | Kind::__LAST => {
+                unreachable!("`{:?}` is not a token", kind)
+            }
+        }
+    }
+}
+
 impl fmt::Display for TokenKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
             Self::EqualsEquals => "`==`",
             Self::AmpersandAmpersand => "`&&`",
+            Self::Arrow => "`->`",
+            Self::Bar => "`|`",
             Self::Bang => "`!`",
             Self::BangEquals => "`!=`",
             Self::BarBar => "`||`",
             Self::Colon => "`:`",
+            Self::ColonColon => "`::`",
             Self::Comma => "`,`",
             Self::Comment => "comment",
+            Self::DocComment => "doc comment",
             Self::CompKeyword => "Comp",
             Self::EffectKeyword => "effect",
+            Self::EnumKeyword => "enum",
             Self::Equals => "`=`",
             Self::Error => "an unrecognized token",
             Self::False => "false",
+            Self::FatArrow => "`=>`",
             Self::FuncKeyword => "func",
             Self::GreaterThan => "`>`",
             Self::GreaterThanEqual => "`>=`",
@@ -209,6 +221,7 @@ impl fmt::Display for TokenKind {
             Self::LessThanEqual => "`<=`",
             Self::LetKeyword => "let",
             Self::LParen => "`(`",
+            Self::MatchKeyword => "match",
             Self::Minus => "`-`",
             Self::MutKeyword => "mut",
             Self::OnDestroyKeyword => "onDestroy",
@@ -216,6 +229,7 @@ impl fmt::Display for TokenKind {
             Self::OnUpdateKeyword => "onUpdate",
             Self::Percent => "`%`",
             Self::Plus => "`+`",
+            Self::Question => "`?`",
             Self::RBrace => "`}`",
             Self::RBracket => "`]`",
             Self::RParen => "`)`",
@@ -223,8 +237,10 @@ impl fmt::Display for TokenKind {
             Self::Slash => "`/`",
             Self::Star => "`*`",
             Self::StateKeyword => "state",
+            Self::StructKeyword => "struct",
             Self::String => "string",
             Self::True => "true",
+            Self::Underscore => "`_`",
             Self::Whitespace => "whitespace",
         })
     }
@@ -0,0 +1,533 @@
+use syntax::{syntax_kind::SyntaxKind, trimmed_range, SyntaxElement, SyntaxNode, SyntaxToken};
+use text_size::{TextRange, TextSize};
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Hover {
+    pub(crate) text: String,
+    /// The doc comments of the definition
+    pub(crate) docs: Option<String>,
+    pub(crate) range: TextRange,
+}
+
+/// Describes what is under the cursor: the definition a name refers to,
+/// the type of a literal, or else the kind of the innermost node
+pub(crate) fn hover(root: &SyntaxNode, offset: TextSize) -> Option<Hover> {
+    let token = token_at(root, offset)?;
+
+    if let Some(definition) = definition(&token) {
+        return Some(Hover {
+            text: describe(&definition),
+            docs: docs(&definition.node),
+            range: token.text_range(),
+        });
+    }
+
+    let ty = match token.kind() {
+        kind if kind.is_trivia() => return None,
+        SyntaxKind::Integer => "i32",
+        SyntaxKind::String => "String",
+        SyntaxKind::True | SyntaxKind::False => "bool",
+        _ => {
+            let node = token.parent()?;
+            return Some(Hover {
+                text: format!("{:?}", node.kind()),
+                docs: None,
+                range: trimmed_range(&node),
+            });
+        }
+    };
+
+    Some(Hover {
+        text: ty.to_string(),
+        docs: None,
+        range: token.text_range(),
+    })
+}
+
+/// Where the binding under the cursor is defined
+pub(crate) fn goto_definition(root: &SyntaxNode, offset: TextSize) -> Option<TextRange> {
+    let token = token_at(root, offset)?;
+    definition(&token).map(|definition| definition.name.text_range())
+}
+
+#[derive(Debug)]
+pub(crate) struct Symbol {
+    pub(crate) name: String,
+    pub(crate) kind: SymbolKind,
+    /// The whole declaration
+    pub(crate) range: TextRange,
+    /// Just the name
+    pub(crate) selection_range: TextRange,
+    pub(crate) children: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SymbolKind {
+    Function,
+    Struct,
+    Field,
+    Enum,
+    Variant,
+}
+
+impl SymbolKind {
+    /// The number the protocol uses for this kind of symbol
+    pub(crate) fn to_lsp(self) -> u32 {
+        match self {
+            Self::Field => 8,
+            Self::Enum => 10,
+            Self::Function => 12,
+            Self::Variant => 22,
+            Self::Struct => 23,
+        }
+    }
+}
+
+/// The functions, structs and enums declared in `node`, with the functions
+/// declared in their bodies, and their fields and variants
+pub(crate) fn symbols(node: &SyntaxNode) -> Vec<Symbol> {
+    node.children()
+        .filter_map(|child| {
+            let (kind, children) = match child.kind() {
+                SyntaxKind::FunctionDecl => (
+                    SymbolKind::Function,
+                    child
+                        .children()
+                        .filter(|body| body.kind() == SyntaxKind::BlockStmt)
+                        .flat_map(|body| symbols(&body))
+                        .collect(),
+                ),
+                SyntaxKind::StructDecl => (
+                    SymbolKind::Struct,
+                    members(&child, SyntaxKind::FieldDecl, SymbolKind::Field),
+                ),
+                SyntaxKind::EnumDecl => (
+                    SymbolKind::Enum,
+                    members(&child, SyntaxKind::VariantDecl, SymbolKind::Variant),
+                ),
+                _ => return None,
+            };
+
+            symbol(&child, kind, children)
+        })
+        .collect()
+}
+
+fn members(node: &SyntaxNode, member: SyntaxKind, kind: SymbolKind) -> Vec<Symbol> {
+    node.children()
+        .filter(|child| child.kind() == member)
+        .filter_map(|child| symbol(&child, kind, Vec::new()))
+        .collect()
+}
+
+fn symbol(node: &SyntaxNode, kind: SymbolKind, children: Vec<Symbol>) -> Option<Symbol> {
+    let name = name(node)?;
+
+    Some(Symbol {
+        name: name.text().to_string(),
+        kind,
+        range: trimmed_range(node),
+        selection_range: name.text_range(),
+        children,
+    })
+}
+
+/// The token under the cursor. Between two tokens, names win over other
+/// tokens, which win over trivia.
+fn token_at(root: &SyntaxNode, offset: TextSize) -> Option<SyntaxToken> {
+    if offset > root.text_range().end() {
+        return None;
+    }
+
+    root.token_at_offset(offset)
+        .max_by_key(|token| match token.kind() {
+            SyntaxKind::Ident => 2,
+            kind if kind.is_trivia() => 0,
+            _ => 1,
+        })
+}
+
+/// A node that introduces a name, and the token of that name
+struct Definition {
+    node: SyntaxNode,
+    name: SyntaxToken,
+}
+
+/// The definition `token` names or refers to
+fn definition(token: &SyntaxToken) -> Option<Definition> {
+    if token.kind() != SyntaxKind::Ident {
+        return None;
+    }
+
+    let parent = token.parent()?;
+    if parent.kind() == SyntaxKind::VariableRef {
+        // The name of a function is a `VariableRef` too
+        match parent.parent() {
+            Some(function) if function.kind() == SyntaxKind::FunctionDecl => {
+                named(function, &ast::name(token))
+            }
+            _ => resolve(&parent, &ast::name(token)),
+        }
+    } else if name(&parent).as_ref() == Some(token) {
+        named(parent, &ast::name(token))
+    } else {
+        None
+    }
+}
+
+/// Finds the binding `name` refers to from `reference`, looking through
+/// the scopes that enclose it from the innermost out. Like in lowering,
+/// statements only see the definitions that come before them.
+fn resolve(reference: &SyntaxNode, name: &str) -> Option<Definition> {
+    let mut child = reference.clone();
+
+    for node in reference.ancestors().skip(1) {
+        let found = match node.kind() {
+            SyntaxKind::Root | SyntaxKind::BlockStmt => node
+                .children()
+                .take_while(|stmt| *stmt != child)
+                .filter(|stmt| {
+                    matches!(
+                        stmt.kind(),
+                        SyntaxKind::VariableDef | SyntaxKind::StateDef | SyntaxKind::FunctionDecl
+                    )
+                })
+                .filter_map(|stmt| named(stmt, name))
+                .last(),
+            // Functions are visible in their own body to allow recursion
+            SyntaxKind::FunctionDecl => param(&node, name).or_else(|| named(node.clone(), name)),
+            SyntaxKind::ClosureExpr => param(&node, name),
+            SyntaxKind::MatchArm => node
+                .children()
+                .filter(|pat| ast::Pat::cast(pat.clone()).is_some())
+                .flat_map(|pat| pat.descendants())
+                .filter(|pat| pat.kind() == SyntaxKind::IdentPat)
+                .filter_map(|pat| named(pat, name))
+                .last(),
+            _ => None,
+        };
+
+        if found.is_some() {
+            return found;
+        }
+
+        child = node;
+    }
+
+    None
+}
+
+fn param(node: &SyntaxNode, name: &str) -> Option<Definition> {
+    node.children()
+        .filter(|params| params.kind() == SyntaxKind::ParamList)
+        .flat_map(|params| params.children())
+        .filter_map(|param| named(param, name))
+        .last()
+}
+
+fn named(node: SyntaxNode, name: &str) -> Option<Definition> {
+    let token = self::name(&node)?;
+    (ast::name(&token) == name).then_some(Definition { node, name: token })
+}
+
+/// The token that names the definition `node`
+fn name(node: &SyntaxNode) -> Option<SyntaxToken> {
+    match node.kind() {
+        SyntaxKind::VariableDef
+        | SyntaxKind::StateDef
+        | SyntaxKind::FunctionDecl
+        | SyntaxKind::StructDecl
+        | SyntaxKind::EnumDecl => match ast::Stmt::cast(node.clone())? {
+            ast::Stmt::VariableDef(def) => def.name(),
+            ast::Stmt::StateDef(def) => def.name(),
+            ast::Stmt::FunctionDecl(decl) => decl.name(),
+            ast::Stmt::StructDecl(decl) => decl.name(),
+            ast::Stmt::EnumDecl(decl) => decl.name(),
+            ast::Stmt::Expr(_) => None,
+        },
+        SyntaxKind::Param => ast::Param::cast(node.clone())?.name(),
+        SyntaxKind::IdentPat => node.first_token(),
+        SyntaxKind::FieldDecl => ast::FieldDecl::cast(node.clone())?.name(),
+        SyntaxKind::VariantDecl => ast::VariantDecl::cast(node.clone())?.name(),
+        _ => None,
+    }
+}
+
+/// A one line summary of `definition`, in the syntax it was written in
+fn describe(definition: &Definition) -> String {
+    let name = definition.name.text();
+    let node = &definition.node;
+
+    match node.kind() {
+        SyntaxKind::VariableDef => format!("let {}{}", name, declared_type(node)),
+        SyntaxKind::StateDef => format!("state {}{}", name, declared_type(node)),
+        // The signature, without the body
+        SyntaxKind::FunctionDecl => code(node, |element| element.kind() != SyntaxKind::BlockStmt),
+        SyntaxKind::Param => format!("(parameter) {}", node.text().to_string().trim_end()),
+        SyntaxKind::IdentPat => format!("(binding) {}", name),
+        SyntaxKind::StructDecl => format!("struct {}", name),
+        SyntaxKind::EnumDecl => format!("enum {}", name),
+        SyntaxKind::FieldDecl => format!("(field) {}", code(node, |_| true)),
+        SyntaxKind::VariantDecl => format!("(variant) {}", code(node, |_| true)),
+        _ => name.to_string(),
+    }
+}
+
+/// The text of `node` without the doc comments before it and the trivia
+/// after it, up to the first child that is not `included`
+fn code(node: &SyntaxNode, included: impl Fn(&SyntaxElement) -> bool) -> String {
+    node.children_with_tokens()
+        .skip_while(|element| element.kind().is_trivia())
+        .take_while(included)
+        .map(|element| element.to_string())
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// The doc comments written before the declaration `node`
+fn docs(node: &SyntaxNode) -> Option<String> {
+    match node.kind() {
+        SyntaxKind::FieldDecl => ast::FieldDecl::cast(node.clone())?.docs(),
+        SyntaxKind::VariantDecl => ast::VariantDecl::cast(node.clone())?.docs(),
+        _ => match ast::Stmt::cast(node.clone())? {
+            ast::Stmt::VariableDef(def) => def.docs(),
+            ast::Stmt::StateDef(def) => def.docs(),
+            ast::Stmt::FunctionDecl(decl) => decl.docs(),
+            ast::Stmt::StructDecl(decl) => decl.docs(),
+            ast::Stmt::EnumDecl(decl) => decl.docs(),
+            ast::Stmt::Expr(_) => None,
+        },
+    }
+}
+
+/// `: type` for a definition that spells out its type or is initialized
+/// with a literal, or nothing if the type is not known without inference
+fn declared_type(node: &SyntaxNode) -> String {
+    if let Some(ty) = node.children().find_map(ast::Type::cast) {
+        return format!(": {}", ty.syntax().text().to_string().trim_end());
+    }
+
+    let ty = node
+        .children()
+        .find_map(ast::Expr::cast)
+        .and_then(|value| match value {
+            ast::Expr::Literal(literal) => Some(match literal.kind() {
+                ast::LiteralKind::Integer(_) => "i32",
+                ast::LiteralKind::String(_) => "String",
+                ast::LiteralKind::Bool(_) => "bool",
+            }),
+            _ => None,
+        });
+
+    ty.map(|ty| format!(": {}", ty)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+
+    /// Parses `input` with the cursor at `$0` removed
+    fn parse_with_cursor(input: &str) -> (SyntaxNode, TextSize) {
+        let offset = input.find("$0").unwrap();
+        let text = input.replace("$0", "");
+        let root = parser::parse(&text).syntax();
+
+        (root, TextSize::try_from(offset).unwrap())
+    }
+
+    fn check_hover(input: &str, expected: Option<&str>) {
+        let (root, offset) = parse_with_cursor(input);
+        let hover = hover(&root, offset);
+
+        assert_eq!(hover.map(|hover| hover.text).as_deref(), expected);
+    }
+
+    /// Checks that the definition of what is under `$0` is the name
+    /// written right before `$1`
+    fn check_definition(input: &str, expected: Option<&str>) {
+        let (root, offset) = parse_with_cursor(&input.replace("$1", ""));
+        let definition = goto_definition(&root, offset);
+
+        let text = input.replace("$0", "");
+        let expected = expected.map(|name| {
+            let end = text.find("$1").unwrap();
+            TextRange::at(
+                TextSize::try_from(end - name.len()).unwrap(),
+                TextSize::of(name),
+            )
+        });
+        assert_eq!(definition, expected);
+    }
+
+    fn check_symbols(input: &str, expected: Expect) {
+        fn render(symbols: &[Symbol], depth: usize, out: &mut String) {
+            for symbol in symbols {
+                out.push_str(&format!(
+                    "{}{:?} {} {:?} {:?}\n",
+                    "  ".repeat(depth),
+                    symbol.kind,
+                    symbol.name,
+                    symbol.range,
+                    symbol.selection_range,
+                ));
+                render(&symbol.children, depth + 1, out);
+            }
+        }
+
+        let mut out = String::new();
+        render(&symbols(&parser::parse(input).syntax()), 0, &mut out);
+        expected.assert_eq(&out);
+    }
+
+    #[test]
+    fn hover_variable() {
+        check_hover("let count = 1\ncount$0 + 1", Some("let count: i32"));
+        check_hover(
+            "let name: String = greet()\nna$0me",
+            Some("let name: String"),
+        );
+        check_hover(
+            "state open = true\nopen$0 = false",
+            Some("state open: bool"),
+        );
+        check_hover("let a = f()\na$0", Some("let a"));
+    }
+
+    #[test]
+    fn hover_definition_name() {
+        check_hover("let cou$0nt = 1", Some("let count: i32"));
+    }
+
+    #[test]
+    fn hover_function() {
+        check_hover(
+            "func add(a: i32, b: i32) -> i32 {\n  a + b\n}\nad$0d(1, 2)",
+            Some("func add(a: i32, b: i32) -> i32"),
+        );
+    }
+
+    #[test]
+    fn hover_param_and_binding() {
+        check_hover("func f(a: i32) {\n  a$0\n}", Some("(parameter) a: i32"));
+        check_hover("let f = |x| x$0", Some("(parameter) x"));
+        check_hover(
+            "match s { Status::Loaded(user) => user$0 }",
+            Some("(binding) user"),
+        );
+    }
+
+    #[test]
+    fn hover_docs() {
+        let check_docs = |input: &str, expected: Option<&str>| {
+            let (root, offset) = parse_with_cursor(input);
+            let hover = hover(&root, offset).unwrap();
+            assert_eq!(hover.docs.as_deref(), expected);
+        };
+
+        check_docs(
+            "/// Adds one\nfunc inc(n) {\n  n + 1\n}\ninc$0(1)",
+            Some("Adds one"),
+        );
+        check_docs(
+            "/// The user\n/// to greet\nlet name = \"a\"\nna$0me",
+            Some("The user\nto greet"),
+        );
+        check_docs(
+            "struct User {\n  /// Full name\n  na$0me: String\n}",
+            Some("Full name"),
+        );
+        check_docs("// Not a doc comment\nlet a = 1\na$0", None);
+        check_hover(
+            "/// Adds one\nfunc inc(n) {}\ninc$0(1)",
+            Some("func inc(n)"),
+        );
+        check_hover(
+            "enum Status {\n  /// Not loaded yet\n  Load$0ing\n}",
+            Some("(variant) Loading"),
+        );
+    }
+
+    #[test]
+    fn hover_literal() {
+        check_hover("1$0", Some("i32"));
+        check_hover("\"hi$0\"", Some("String"));
+        check_hover("let a = fal$0se", Some("bool"));
+    }
+
+    #[test]
+    fn hover_node_kind() {
+        check_hover("1 +$0 2", Some("InfixExpr"));
+        check_hover("unknown$0", Some("VariableRef"));
+    }
+
+    #[test]
+    fn hover_nothing() {
+        check_hover("1 + 2 // sum$0", None);
+        check_hover("/// doc$0\nlet a = 1", None);
+        check_hover("$0", None);
+    }
+
+    #[test]
+    fn definition_of_variable() {
+        check_definition("let a$1 = 1\nlet b = a$0 + 1", Some("a"));
+    }
+
+    #[test]
+    fn definition_of_shadowed_variable() {
+        check_definition("let a = 1\nlet a$1 = a + 1\na$0", Some("a"));
+        check_definition("let a$1 = 1\nlet a = a$0 + 1", Some("a"));
+    }
+
+    #[test]
+    fn definition_must_come_first() {
+        check_definition("a$0\nlet a = 1", None);
+    }
+
+    #[test]
+    fn definition_of_param() {
+        check_definition("let a = 1\nfunc f(a$1: i32) {\n  a$0\n}", Some("a"));
+        check_definition("let a = 1\nlet f = |a$1| a$0", Some("a"));
+    }
+
+    #[test]
+    fn definition_of_outer_variable() {
+        check_definition("state n$1 = 0\nfunc f() {\n  n$0 = 1\n}", Some("n"));
+    }
+
+    #[test]
+    fn definition_of_recursive_function() {
+        check_definition("func f$1(n) {\n  f$0(n)\n}", Some("f"));
+    }
+
+    #[test]
+    fn definition_of_match_binding() {
+        check_definition("let x = 1\nmatch s { Some::Value(x$1) => x$0 }", Some("x"));
+        check_definition("let x$1 = 1\nmatch s { Some::Value(y) => x$0 }", Some("x"));
+    }
+
+    #[test]
+    fn definition_is_local_to_block() {
+        check_definition("func f() {\n  let a = 1\n}\na$0", None);
+    }
+
+    #[test]
+    fn symbols_of_declarations() {
+        check_symbols(
+            "struct User { name: String }\n\
+             enum Status { Loading, Loaded(User) }\n\
+             let unlisted = 1\n\
+             func main() {\n  func helper() {}\n}",
+            expect![[r#"
+Struct User 0..28 7..11
+  Field name 14..26 14..18
+Enum Status 29..66 34..40
+  Variant Loading 43..50 43..50
+  Variant Loaded 52..64 52..58
+Function main 84..118 89..93
+  Function helper 100..116 105..111
+"#]],
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use serde_json::{json, Value};
+use text_size::{TextRange, TextSize};
+
+/// An open file. Positions in the protocol are zero based lines and
+/// columns, with columns counted in UTF-16 code units.
+pub(crate) struct Document {
+    pub(crate) text: String,
+    /// The offset at which each line starts
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub(crate) fn new(text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+
+        Self { text, line_starts }
+    }
+
+    pub(crate) fn position(&self, offset: TextSize) -> Value {
+        let offset = usize::from(offset);
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let character = self.text[self.line_starts[line]..offset]
+            .encode_utf16()
+            .count();
+
+        json!({ "line": line, "character": character })
+    }
+
+    pub(crate) fn range(&self, range: TextRange) -> Value {
+        json!({
+            "start": self.position(range.start()),
+            "end": self.position(range.end()),
+        })
+    }
+
+    /// The offset of `position`. Columns past the end of their line point
+    /// at the end of it, like the protocol asks for.
+    pub(crate) fn offset(&self, position: &Value) -> Option<TextSize> {
+        let line = position["line"].as_u64()? as usize;
+        let character = position["character"].as_u64()? as usize;
+
+        let start = *self.line_starts.get(line)?;
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.text.len(), |end| end - 1);
+
+        let mut units = 0;
+        let offset = self.text[start..end]
+            .char_indices()
+            .find(|(_, c)| {
+                units += c.len_utf16();
+                units > character
+            })
+            .map_or(end, |(idx, _)| start + idx);
+
+        TextSize::try_from(offset).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, offset: u32, line: u32, character: u32) {
+        let document = Document::new(text.to_string());
+        let position = json!({ "line": line, "character": character });
+
+        assert_eq!(document.position(offset.into()), position);
+        assert_eq!(document.offset(&position), Some(offset.into()));
+    }
+
+    #[test]
+    fn start_of_text() {
+        check("let a = 1", 0, 0, 0);
+    }
+
+    #[test]
+    fn after_newline() {
+        check("let a = 1\nlog(a)", 14, 1, 4);
+    }
+
+    #[test]
+    fn end_of_text() {
+        check("a\nb", 3, 1, 1);
+    }
+
+    #[test]
+    fn columns_count_utf16_code_units() {
+        check("\"🦀\" + b", 9, 0, 7);
+    }
+
+    #[test]
+    fn clamp_columns_to_end_of_line() {
+        let document = Document::new("ab\ncd".to_string());
+        assert_eq!(
+            document.offset(&json!({ "line": 0, "character": 10 })),
+            Some(2.into()),
+        );
+        assert_eq!(document.offset(&json!({ "line": 2, "character": 0 })), None);
+    }
+}
@@ -0,0 +1,13 @@
+mod analysis;
+mod document;
+mod rpc;
+mod server;
+
+use std::io;
+use std::process;
+
+/// Speaks the Language Server Protocol over stdin and stdout
+fn main() -> io::Result<()> {
+    let clean_exit = server::run(io::stdin().lock(), io::stdout().lock())?;
+    process::exit(if clean_exit { 0 } else { 1 })
+}
@@ -0,0 +1,84 @@
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// Reads the body of the next message, which is preceded by headers of
+/// which only `Content-Length` matters. Returns `None` at the end of the
+/// input.
+pub(crate) fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing `Content-Length` header",
+        )
+    })?;
+
+    let mut body = vec![0; content_length];
+    input.read_exact(&mut body)?;
+
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+pub(crate) fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trip() {
+        let mut framed = Vec::new();
+        write_message(&mut framed, &json!({ "text": "🦀" })).unwrap();
+        write_message(&mut framed, &json!(1)).unwrap();
+
+        let mut input = framed.as_slice();
+        assert_eq!(
+            read_message(&mut input).unwrap().as_deref(),
+            Some(r#"{"text":"🦀"}"#),
+        );
+        assert_eq!(read_message(&mut input).unwrap().as_deref(), Some("1"));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn ignore_other_headers() {
+        let mut input =
+            "Content-Type: application/vscode-jsonrpc\r\ncontent-length: 2\r\n\r\n{}".as_bytes();
+        assert_eq!(read_message(&mut input).unwrap().as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn missing_content_length() {
+        let mut input = "Content-Type: text/plain\r\n\r\n{}".as_bytes();
+        assert_eq!(
+            read_message(&mut input).unwrap_err().kind(),
+            io::ErrorKind::InvalidData,
+        );
+    }
+}
@@ -0,0 +1,556 @@
+use crate::analysis::{self, Symbol};
+use crate::document::Document;
+use crate::rpc::{read_message, write_message};
+use db::{FileId, RootDatabase};
+use diagnostics::{Diagnostic, Severity};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use syntax::SyntaxNode;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Answers requests from `input` on `output` until the client asks the
+/// server to exit. Returns whether the client asked it to shut down first,
+/// which is what the exit code reports.
+pub(crate) fn run(mut input: impl BufRead, mut output: impl Write) -> io::Result<bool> {
+    let mut server = Server::default();
+
+    while let Some(body) = read_message(&mut input)? {
+        let outgoing = match serde_json::from_str(&body) {
+            Ok(message) => server.handle(message),
+            Err(error) => vec![error_response(Value::Null, PARSE_ERROR, error.to_string())],
+        };
+
+        for message in &outgoing {
+            write_message(&mut output, message)?;
+        }
+
+        if server.has_exited {
+            return Ok(server.is_shut_down);
+        }
+    }
+
+    Ok(false)
+}
+
+#[derive(Default)]
+struct Server {
+    /// The open documents by URI
+    documents: HashMap<String, Document>,
+    /// The files of the open documents in `db`, by URI
+    files: HashMap<String, FileId>,
+    db: RootDatabase,
+    is_shut_down: bool,
+    has_exited: bool,
+}
+
+impl Server {
+    /// Handles one message, returning the messages to send back
+    fn handle(&mut self, message: Value) -> Vec<Value> {
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            // Responses to requests we never send
+            None if message.get("id").is_some() => return Vec::new(),
+            None => {
+                return vec![error_response(
+                    Value::Null,
+                    INVALID_REQUEST,
+                    "expected a method".to_string(),
+                )]
+            }
+        };
+        let params = &message["params"];
+
+        match message.get("id") {
+            Some(id) => {
+                let response = match self.handle_request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => error_response(id.clone(), code, message),
+                };
+                vec![response]
+            }
+            None => self.handle_notification(method, params),
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    // Changes send the whole document
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "wlang" },
+            })),
+            "shutdown" => {
+                self.is_shut_down = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => {
+                let (_, document, root) = self.document(params)?;
+                let hover = document
+                    .offset(&params["position"])
+                    .and_then(|offset| analysis::hover(&root, offset));
+
+                Ok(hover.map_or(Value::Null, |hover| {
+                    let mut value = format!("```wlang\n{}\n```", hover.text);
+                    if let Some(docs) = &hover.docs {
+                        value.push_str("\n\n");
+                        value.push_str(docs);
+                    }
+
+                    json!({
+                        "contents": {
+                            "kind": "markdown",
+                            "value": value,
+                        },
+                        "range": document.range(hover.range),
+                    })
+                }))
+            }
+            "textDocument/definition" => {
+                let (uri, document, root) = self.document(params)?;
+                let definition = document
+                    .offset(&params["position"])
+                    .and_then(|offset| analysis::goto_definition(&root, offset));
+
+                Ok(definition.map_or(
+                    Value::Null,
+                    |range| json!({ "uri": uri, "range": document.range(range) }),
+                ))
+            }
+            "textDocument/documentSymbol" => {
+                let (_, document, root) = self.document(params)?;
+
+                Ok(symbols_to_json(document, &analysis::symbols(&root)))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method `{}`", method))),
+        }
+    }
+
+    /// The document a request is about, along with its syntax tree
+    fn document(&mut self, params: &Value) -> Result<(&str, &Document, SyntaxNode), (i64, String)> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .ok_or((INVALID_PARAMS, "expected a text document".to_string()))?;
+
+        let (uri, document) = self
+            .documents
+            .get_key_value(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("`{}` is not open", uri)))?;
+        let root = self.db.parse(self.files[uri]).syntax();
+
+        Ok((uri.as_str(), document, root))
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().map(str::to_string);
+
+        match (method, uri) {
+            ("exit", _) => {
+                self.has_exited = true;
+                Vec::new()
+            }
+            ("textDocument/didOpen", Some(uri)) => match params["textDocument"]["text"].as_str() {
+                Some(text) => self.update(uri, text.to_string()),
+                None => Vec::new(),
+            },
+            // Only the last change matters, as every change is the whole text
+            ("textDocument/didChange", Some(uri)) => {
+                match params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    Some(text) => self.update(uri, text.to_string()),
+                    None => Vec::new(),
+                }
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+                if let Some(file) = self.files.remove(&uri) {
+                    self.db.remove_file(file);
+                }
+                vec![publish_diagnostics(&uri, Vec::new())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn update(&mut self, uri: String, text: String) -> Vec<Value> {
+        let file = match self.files.get(&uri) {
+            Some(&file) => {
+                self.db.set_file_text(file, text.as_str());
+                file
+            }
+            None => {
+                let file = self.db.add_file(text.as_str());
+                self.files.insert(uri.clone(), file);
+                file
+            }
+        };
+
+        let document = Document::new(text);
+        let diagnostics = self
+            .db
+            .diagnostics(file)
+            .iter()
+            .map(|diagnostic| diagnostic_to_json(&uri, &document, diagnostic))
+            .collect();
+
+        let notification = publish_diagnostics(&uri, diagnostics);
+        self.documents.insert(uri, document);
+
+        vec![notification]
+    }
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Secondary labels become related information. Diagnostics without a
+/// range are reported at the start of the document.
+fn diagnostic_to_json(uri: &str, document: &Document, diagnostic: &Diagnostic) -> Value {
+    let range = diagnostic.range().unwrap_or_default();
+    let related: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .filter(|label| !label.primary)
+        .map(|label| {
+            json!({
+                "location": { "uri": uri, "range": document.range(label.range) },
+                "message": label.message,
+            })
+        })
+        .collect();
+
+    let mut message = diagnostic.message.clone();
+    for note in &diagnostic.notes {
+        message.push_str(&format!("\nnote: {}", note));
+    }
+    if let Some(help) = &diagnostic.help {
+        message.push_str(&format!("\nhelp: {}", help));
+    }
+
+    json!({
+        "range": document.range(range),
+        "severity": match diagnostic.severity {
+            Severity::Error => 1,
+            Severity::Warning => 2,
+        },
+        "code": diagnostic.code.as_str(),
+        "source": "wlang",
+        "message": message,
+        "relatedInformation": related,
+    })
+}
+
+fn symbols_to_json(document: &Document, symbols: &[Symbol]) -> Value {
+    symbols
+        .iter()
+        .map(|symbol| {
+            json!({
+                "name": symbol.name,
+                "kind": symbol.kind.to_lsp(),
+                "range": document.range(symbol.range),
+                "selectionRange": document.range(symbol.selection_range),
+                "children": symbols_to_json(document, &symbol.children),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Talks to a server running in the same process. Messages are queued
+    /// until `finish`, which runs the server over all of them.
+    struct Client {
+        input: Vec<u8>,
+        next_id: u64,
+    }
+
+    impl Client {
+        fn new() -> Self {
+            let mut client = Self {
+                input: Vec::new(),
+                next_id: 0,
+            };
+            client.request("initialize", json!({ "capabilities": {} }));
+            client.notify("initialized", json!({}));
+            client
+        }
+
+        /// Queues a request, returning its id
+        fn request(&mut self, method: &str, params: Value) -> u64 {
+            let id = self.next_id;
+            self.next_id += 1;
+            self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+            id
+        }
+
+        fn notify(&mut self, method: &str, params: Value) {
+            self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+        }
+
+        fn send(&mut self, message: Value) {
+            write_message(&mut self.input, &message).unwrap();
+        }
+
+        fn open(&mut self, text: &str) {
+            self.notify(
+                "textDocument/didOpen",
+                json!({
+                    "textDocument": {
+                        "uri": URI,
+                        "languageId": "wlang",
+                        "version": 1,
+                        "text": text,
+                    },
+                }),
+            );
+        }
+
+        fn at(&mut self, method: &str, line: u32, character: u32) -> u64 {
+            self.request(
+                method,
+                json!({
+                    "textDocument": { "uri": URI },
+                    "position": { "line": line, "character": character },
+                }),
+            )
+        }
+
+        /// Shuts the server down, and returns whether it exited cleanly and
+        /// everything it sent
+        fn finish(mut self) -> Messages {
+            self.request("shutdown", Value::Null);
+            self.notify("exit", Value::Null);
+
+            let mut output = Vec::new();
+            let clean_exit = run(self.input.as_slice(), &mut output).unwrap();
+            assert!(clean_exit);
+
+            let mut output = output.as_slice();
+            let mut messages = Vec::new();
+            while let Some(body) = read_message(&mut output).unwrap() {
+                messages.push(serde_json::from_str(&body).unwrap());
+            }
+
+            Messages(messages)
+        }
+    }
+
+    struct Messages(Vec<Value>);
+
+    impl Messages {
+        fn response(&self, id: u64) -> &Value {
+            self.0.iter().find(|message| message["id"] == id).unwrap()
+        }
+
+        fn result(&self, id: u64) -> &Value {
+            &self.response(id)["result"]
+        }
+
+        /// The diagnostics of every publish, in order
+        fn diagnostics(&self) -> Vec<&Value> {
+            self.0
+                .iter()
+                .filter(|message| message["method"] == "textDocument/publishDiagnostics")
+                .map(|message| &message["params"]["diagnostics"])
+                .collect()
+        }
+    }
+
+    const URI: &str = "file:///project/main.w";
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> Value {
+        json!({
+            "start": { "line": start.0, "character": start.1 },
+            "end": { "line": end.0, "character": end.1 },
+        })
+    }
+
+    #[test]
+    fn initialize() {
+        let messages = Client::new().finish();
+        let capabilities = &messages.result(0)["capabilities"];
+
+        assert_eq!(capabilities["textDocumentSync"], 1);
+        assert_eq!(capabilities["hoverProvider"], true);
+        assert_eq!(capabilities["definitionProvider"], true);
+        assert_eq!(capabilities["documentSymbolProvider"], true);
+    }
+
+    #[test]
+    fn publish_diagnostics_on_open_and_change() {
+        let mut client = Client::new();
+        client.open("let s = \"🦀\"\nlet a = 1 +");
+        client.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": URI, "version": 2 },
+                "contentChanges": [{ "text": "let a = 1 +" }, { "text": "let a = 1 + 2" }],
+            }),
+        );
+        client.notify(
+            "textDocument/didClose",
+            json!({ "textDocument": { "uri": URI } }),
+        );
+        let messages = client.finish();
+
+        let diagnostics = messages.diagnostics();
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0][0]["code"], "E0001");
+        assert_eq!(diagnostics[0][0]["severity"], 1);
+        assert_eq!(diagnostics[0][0]["source"], "wlang");
+        assert_eq!(diagnostics[0][0]["range"], range((1, 10), (1, 11)));
+        assert_eq!(diagnostics[1], &json!([]));
+        assert_eq!(diagnostics[2], &json!([]));
+    }
+
+    #[test]
+    fn hover() {
+        let mut client = Client::new();
+        client.open("let name = \"🦀\" // crab\nlet greeting = name");
+        let hover = client.at("textDocument/hover", 1, 16);
+        let nothing = client.at("textDocument/hover", 0, 20);
+        let messages = client.finish();
+
+        assert_eq!(
+            messages.result(hover),
+            &json!({
+                "contents": { "kind": "markdown", "value": "```wlang\nlet name: String\n```" },
+                "range": range((1, 15), (1, 19)),
+            }),
+        );
+        assert_eq!(messages.result(nothing), &Value::Null);
+    }
+
+    #[test]
+    fn hover_with_docs() {
+        let mut client = Client::new();
+        client.open("/// Says hi\nfunc greet() {}\ngreet()");
+        let hover = client.at("textDocument/hover", 2, 1);
+        let messages = client.finish();
+
+        assert_eq!(
+            messages.result(hover)["contents"]["value"],
+            "```wlang\nfunc greet()\n```\n\nSays hi",
+        );
+    }
+
+    #[test]
+    fn goto_definition() {
+        let mut client = Client::new();
+        client.open("let a = 1\nfunc f(b) {\n  a + b\n}");
+        let a = client.at("textDocument/definition", 2, 2);
+        let b = client.at("textDocument/definition", 2, 6);
+        let messages = client.finish();
+
+        assert_eq!(
+            messages.result(a),
+            &json!({ "uri": URI, "range": range((0, 4), (0, 5)) }),
+        );
+        assert_eq!(
+            messages.result(b),
+            &json!({ "uri": URI, "range": range((1, 7), (1, 8)) }),
+        );
+    }
+
+    #[test]
+    fn document_symbols() {
+        let mut client = Client::new();
+        client.open("func main() {\n  func helper() {}\n}\nenum E { A }");
+        let symbols = client.request(
+            "textDocument/documentSymbol",
+            json!({ "textDocument": { "uri": URI } }),
+        );
+        let messages = client.finish();
+
+        assert_eq!(
+            messages.result(symbols),
+            &json!([
+                {
+                    "name": "main",
+                    "kind": 12,
+                    "range": range((0, 0), (2, 1)),
+                    "selectionRange": range((0, 5), (0, 9)),
+                    "children": [{
+                        "name": "helper",
+                        "kind": 12,
+                        "range": range((1, 2), (1, 18)),
+                        "selectionRange": range((1, 7), (1, 13)),
+                        "children": [],
+                    }],
+                },
+                {
+                    "name": "E",
+                    "kind": 10,
+                    "range": range((3, 0), (3, 12)),
+                    "selectionRange": range((3, 5), (3, 6)),
+                    "children": [{
+                        "name": "A",
+                        "kind": 22,
+                        "range": range((3, 9), (3, 10)),
+                        "selectionRange": range((3, 9), (3, 10)),
+                        "children": [],
+                    }],
+                },
+            ]),
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut client = Client::new();
+        let unknown = client.request("textDocument/rename", json!({}));
+        let not_open = client.at("textDocument/hover", 0, 0);
+        client.send(json!({ "jsonrpc": "2.0", "id": 99, "result": null }));
+        let messages = client.finish();
+
+        assert_eq!(
+            messages.response(unknown)["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(messages.response(not_open)["error"]["code"], INVALID_PARAMS);
+        assert!(messages.0.iter().all(|message| message["id"] != 99));
+    }
+
+    #[test]
+    fn malformed_message() {
+        let mut input = Vec::new();
+        input.extend_from_slice(b"Content-Length: 5\r\n\r\n{oops");
+        write_message(&mut input, &json!({ "jsonrpc": "2.0", "method": "exit" })).unwrap();
+
+        let mut output = Vec::new();
+        let clean_exit = run(input.as_slice(), &mut output).unwrap();
+        assert!(!clean_exit);
+
+        let body = read_message(&mut output.as_slice()).unwrap().unwrap();
+        let response: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+    }
+}
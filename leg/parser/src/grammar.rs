@@ -1,15 +1,39 @@
 mod expr;
+mod pat;
 mod stmt;
+mod ty;
 
 use crate::parser::marker::CompletedMarker;
 use crate::parser::Parser;
 use lexer::TokenKind;
 use syntax::syntax_kind::SyntaxKind;
 
+/// Tokens that start a statement, an error never skips over these
+const STMT_RECOVERY: &[TokenKind] = &[
+    TokenKind::LetKeyword,
+    TokenKind::StateKeyword,
+    TokenKind::FuncKeyword,
+    TokenKind::StructKeyword,
+    TokenKind::EnumKeyword,
+];
+
 pub(super) fn root(parser: &mut Parser) -> CompletedMarker {
     let marker = parser.start();
+    parser.push_recovery(STMT_RECOVERY);
+
     while !parser.at_end() {
         stmt::parse_statement(parser);
     }
+
+    parser.pop_recovery();
     marker.complete(parser, SyntaxKind::Root)
 }
+
+/// Parses a block on its own, like it is parsed as the body of a function,
+/// which is the only place blocks appear in
+pub(super) fn block(parser: &mut Parser) -> CompletedMarker {
+    parser.push_recovery(STMT_RECOVERY);
+    let cm = stmt::parse_block_stmt(parser);
+    parser.pop_recovery();
+    cm
+}
@@ -1,18 +1,23 @@
+use super::pat::parse_pattern;
+use super::stmt::parse_params;
 use super::*;
 
 enum UnaryOp {
     Negate,
+    Not,
 }
 
 impl UnaryOp {
     pub fn binding_power(&self) -> ((), u8) {
         match self {
-            UnaryOp::Negate => ((), 7),
+            UnaryOp::Negate | UnaryOp::Not => ((), 9),
         }
     }
 }
 
 pub enum BinaryOp {
+    /// The `=` operator (assignment)
+    Assign,
     /// The `+` operator (addition)
     Add,
     /// The `-` operator (subtraction)
@@ -44,12 +49,14 @@ pub enum BinaryOp {
 impl BinaryOp {
     pub fn binding_power(&self) -> (u8, u8) {
         match self {
-            Self::Or => (1, 2),
-            Self::And => (2, 3),
-            Self::Eq | Self::Ne => (3, 4),
-            Self::Le | Self::Lt | Self::Ge | Self::Gt => (4, 5),
-            Self::Add | Self::Sub => (5, 6),
-            Self::Mul | Self::Div | Self::Rem => (6, 7),
+            // Assignment is right associative, `a = b = c` is `a = (b = c)`
+            Self::Assign => (2, 1),
+            Self::Or => (3, 4),
+            Self::And => (4, 5),
+            Self::Eq | Self::Ne => (5, 6),
+            Self::Le | Self::Lt | Self::Ge | Self::Gt => (6, 7),
+            Self::Add | Self::Sub => (7, 8),
+            Self::Mul | Self::Div | Self::Rem => (8, 9),
         }
     }
 }
@@ -69,6 +76,12 @@ fn parse_expression_bp(parser: &mut Parser, minimum_binding_power: u8) -> Option
             None => break,
         };
 
+        // An operator at the start of a line continues the expression, unless
+        // it could also start a new one. Then the newline ends the statement.
+        if parser.at_line_break() && matches!(op, BinaryOp::Sub | BinaryOp::Or) {
+            break;
+        }
+
         let (left_binding_power, right_binding_power) = op.binding_power();
 
         if left_binding_power < minimum_binding_power {
@@ -78,9 +91,14 @@ fn parse_expression_bp(parser: &mut Parser, minimum_binding_power: u8) -> Option
         // Eat the operator's token.
         parser.bump();
 
+        let kind = match op {
+            BinaryOp::Assign => SyntaxKind::AssignExpr,
+            _ => SyntaxKind::InfixExpr,
+        };
+
         let marker = lhs.precede(parser);
         let parsed_rhs = parse_expression_bp(parser, right_binding_power).is_some();
-        lhs = marker.complete(parser, SyntaxKind::InfixExpr);
+        lhs = marker.complete(parser, kind);
 
         if !parsed_rhs {
             break;
@@ -117,6 +135,8 @@ fn parse_binary_operator(parser: &mut Parser) -> Option<BinaryOp> {
         Some(BinaryOp::Eq)
     } else if parser.at(TokenKind::BangEquals) {
         Some(BinaryOp::Ne)
+    } else if parser.at(TokenKind::Equals) {
+        Some(BinaryOp::Assign)
     } else {
         None
     }
@@ -124,26 +144,36 @@ fn parse_binary_operator(parser: &mut Parser) -> Option<BinaryOp> {
 
 /// Parses the left hand side of an expression
 fn parse_left_hand_side(parser: &mut Parser) -> Option<CompletedMarker> {
-    let cm = if parser.at(TokenKind::Integer) {
-        parse_literal(parser)
-    } else if parser.at(TokenKind::String) {
-        parse_literal(parser)
-    } else if parser.at(TokenKind::True) {
-        parse_literal(parser)
-    } else if parser.at(TokenKind::False) {
+    let cm = if parser.at_set(LITERAL_FIRST) {
         parse_literal(parser)
     } else if parser.at(TokenKind::Ident) {
-        parse_variable_ref(parser)
-    } else if parser.at(TokenKind::Minus) {
+        parse_name_expression(parser)
+    } else if parser.at_set(&[TokenKind::Minus, TokenKind::Bang]) {
         parse_prefix_expression(parser)
     } else if parser.at(TokenKind::LParen) {
         parse_paren_expression(parser)
+    } else if parser.at_set(&[TokenKind::Bar, TokenKind::BarBar]) {
+        parse_closure_expression(parser)
+    } else if parser.at(TokenKind::MatchKeyword) {
+        parse_match_expression(parser)
     } else {
         parser.error();
         return None;
     };
 
-    Some(cm)
+    Some(parse_postfix_expression(parser, cm))
+}
+
+/// Parses any number of calls following an expression
+/// foo(1)(2)
+fn parse_postfix_expression(parser: &mut Parser, mut lhs: CompletedMarker) -> CompletedMarker {
+    while parser.at(TokenKind::LParen) && !parser.at_line_break() {
+        let marker = lhs.precede(parser);
+        parse_arg_list(parser);
+        lhs = marker.complete(parser, SyntaxKind::CallExpr);
+    }
+
+    lhs
 }
 
 const LITERAL_FIRST: &[TokenKind] = &[
@@ -162,6 +192,54 @@ pub(crate) fn parse_literal(parser: &mut Parser) -> CompletedMarker {
     marker.complete(parser, SyntaxKind::Literal)
 }
 
+/// Parses an expression that starts with a name
+/// count, Status::Loading, User { name: "a" }
+fn parse_name_expression(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::Ident));
+    let marker = parser.start();
+    parser.bump();
+
+    if parser.at(TokenKind::ColonColon) {
+        while parser.at(TokenKind::ColonColon) {
+            parser.bump();
+            parser.expect(TokenKind::Ident);
+        }
+
+        marker.complete(parser, SyntaxKind::PathExpr)
+    } else if parser.struct_exprs_allowed() && parser.at(TokenKind::LBrace) {
+        parse_struct_expr_fields(parser);
+        marker.complete(parser, SyntaxKind::StructExpr)
+    } else {
+        marker.complete(parser, SyntaxKind::VariableRef)
+    }
+}
+
+/// { name: "a", age: 1 }
+fn parse_struct_expr_fields(parser: &mut Parser) {
+    assert!(parser.at(TokenKind::LBrace));
+    parser.bump();
+    let significant = parser.set_newlines_significant(false);
+    parser.push_recovery(&[TokenKind::RBrace]);
+
+    while parser.at(TokenKind::Ident) {
+        let marker = parser.start();
+        parser.bump();
+        parser.expect(TokenKind::Colon);
+        parse_expression(parser);
+        marker.complete(parser, SyntaxKind::StructExprField);
+
+        if parser.at(TokenKind::Comma) {
+            parser.bump();
+        } else {
+            break;
+        }
+    }
+
+    parser.expect(TokenKind::RBrace);
+    parser.pop_recovery();
+    parser.set_newlines_significant(significant);
+}
+
 pub(super) fn parse_variable_ref(parser: &mut Parser) -> CompletedMarker {
     assert!(parser.at(TokenKind::Ident));
     let marker = parser.start();
@@ -170,11 +248,15 @@ pub(super) fn parse_variable_ref(parser: &mut Parser) -> CompletedMarker {
 }
 
 pub(crate) fn parse_prefix_expression(parser: &mut Parser) -> CompletedMarker {
-    assert!(parser.at(TokenKind::Minus));
+    let op = if parser.at(TokenKind::Minus) {
+        UnaryOp::Negate
+    } else {
+        assert!(parser.at(TokenKind::Bang));
+        UnaryOp::Not
+    };
 
     let marker = parser.start();
 
-    let op = UnaryOp::Negate;
     let ((), right_binding_power) = op.binding_power();
 
     parser.bump();
@@ -190,12 +272,122 @@ pub(crate) fn parse_paren_expression(parser: &mut Parser) -> CompletedMarker {
     let marker = parser.start();
 
     parser.bump();
+    let allowed = parser.set_struct_exprs_allowed(true);
+    let significant = parser.set_newlines_significant(false);
+    parser.push_recovery(&[TokenKind::RParen]);
     parse_expression_bp(parser, 0);
     parser.expect(TokenKind::RParen);
+    parser.pop_recovery();
+    parser.set_struct_exprs_allowed(allowed);
+    parser.set_newlines_significant(significant);
 
     marker.complete(parser, SyntaxKind::ParenExpr)
 }
 
+pub(crate) fn parse_arg_list(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::LParen));
+
+    let marker = parser.start();
+    parser.bump();
+    let allowed = parser.set_struct_exprs_allowed(true);
+    let significant = parser.set_newlines_significant(false);
+    parser.push_recovery(&[TokenKind::RParen]);
+
+    while !parser.at(TokenKind::RParen) && !parser.at_end() {
+        if parse_expression(parser).is_none() {
+            break;
+        }
+
+        if parser.at(TokenKind::Comma) {
+            parser.bump();
+        } else {
+            break;
+        }
+    }
+
+    parser.expect(TokenKind::RParen);
+    parser.pop_recovery();
+    parser.set_struct_exprs_allowed(allowed);
+    parser.set_newlines_significant(significant);
+    marker.complete(parser, SyntaxKind::ArgList)
+}
+
+/// Parses a closure expression
+/// |a, b| a + b
+/// || count = count + 1
+pub(crate) fn parse_closure_expression(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at_set(&[TokenKind::Bar, TokenKind::BarBar]));
+
+    let marker = parser.start();
+
+    let params = parser.start();
+    if parser.at(TokenKind::BarBar) {
+        parser.bump();
+    } else {
+        parser.bump();
+        parse_params(parser, TokenKind::Bar);
+    }
+    params.complete(parser, SyntaxKind::ParamList);
+
+    // The body extends as far to the right as possible
+    parse_expression_bp(parser, 0);
+
+    marker.complete(parser, SyntaxKind::ClosureExpr)
+}
+
+/// Parses a match expression
+/// match status { Status::Loading => 0, _ => 1 }
+pub(crate) fn parse_match_expression(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::MatchKeyword));
+    let marker = parser.start();
+    parser.bump();
+
+    // `match user { ... }` must not be parsed as a struct expression
+    let allowed = parser.set_struct_exprs_allowed(false);
+    parse_expression(parser);
+    parser.set_struct_exprs_allowed(allowed);
+
+    if parser.at(TokenKind::LBrace) {
+        parse_match_arm_list(parser);
+    } else {
+        parser.error();
+    }
+
+    marker.complete(parser, SyntaxKind::MatchExpr)
+}
+
+fn parse_match_arm_list(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::LBrace));
+    let marker = parser.start();
+    parser.bump();
+    let significant = parser.set_newlines_significant(false);
+    parser.push_recovery(&[TokenKind::RBrace]);
+
+    while !parser.at(TokenKind::RBrace) && !parser.at_end() {
+        parse_match_arm(parser);
+
+        if parser.at(TokenKind::Comma) {
+            parser.bump();
+        } else {
+            break;
+        }
+    }
+
+    parser.expect(TokenKind::RBrace);
+    parser.pop_recovery();
+    parser.set_newlines_significant(significant);
+    marker.complete(parser, SyntaxKind::MatchArmList)
+}
+
+/// Status::Loaded(user) => user
+fn parse_match_arm(parser: &mut Parser) -> CompletedMarker {
+    let marker = parser.start();
+    parse_pattern(parser);
+    parser.expect(TokenKind::FatArrow);
+    parse_expression(parser);
+    marker.complete(parser, SyntaxKind::MatchArm)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::check;
@@ -437,6 +629,27 @@ Root@0..3
         )
     }
 
+    #[test]
+    fn parse_not() {
+        check(
+            "!!a && b",
+            expect![[r#"
+Root@0..8
+  InfixExpr@0..8
+    PrefixExpr@0..4
+      Bang@0..1 "!"
+      PrefixExpr@1..4
+        Bang@1..2 "!"
+        VariableRef@2..4
+          Ident@2..3 "a"
+          Whitespace@3..4 " "
+    AmpersandAmpersand@4..6 "&&"
+    Whitespace@6..7 " "
+    VariableRef@7..8
+      Ident@7..8 "b""#]],
+        )
+    }
+
     #[test]
     fn negation_has_higher_binding_power_than_infix_operators() {
         check(
@@ -509,7 +722,7 @@ Root@0..4
     LParen@0..1 "("
     VariableRef@1..4
       Ident@1..4 "foo"
-error at 1..4: expected `+`, `-`, `/`, `*`, `%`, `&&`, `||`, `<`, `<=`, `>`, `>=`, `==`, `!=` or `)`"#]],
+error at 1..4: expected `::`, `{`, `(`, `+`, `-`, `/`, `*`, `%`, `&&`, `||`, `<`, `<=`, `>`, `>=`, `==`, `!=`, `=` or `)`"#]],
         );
     }
 
@@ -525,8 +738,486 @@ Root@0..3
       Literal@1..2
         Integer@1..2 "1"
       Plus@2..3 "+"
-error at 2..3: expected number, string, true, false, identifier, `-` or `(`
+error at 2..3: expected number, string, true, false, identifier, `-`, `!`, `(`, `|`, `||` or match
 error at 2..3: expected `)`"#]],
         );
     }
+
+    #[test]
+    fn parse_assignment() {
+        check(
+            "count = count + 1",
+            expect![[r#"
+Root@0..17
+  AssignExpr@0..17
+    VariableRef@0..6
+      Ident@0..5 "count"
+      Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    InfixExpr@8..17
+      VariableRef@8..14
+        Ident@8..13 "count"
+        Whitespace@13..14 " "
+      Plus@14..15 "+"
+      Whitespace@15..16 " "
+      Literal@16..17
+        Integer@16..17 "1""#]],
+        );
+    }
+
+    #[test]
+    fn assignment_is_right_associative() {
+        check(
+            "a = b = c",
+            expect![[r#"
+Root@0..9
+  AssignExpr@0..9
+    VariableRef@0..2
+      Ident@0..1 "a"
+      Whitespace@1..2 " "
+    Equals@2..3 "="
+    Whitespace@3..4 " "
+    AssignExpr@4..9
+      VariableRef@4..6
+        Ident@4..5 "b"
+        Whitespace@5..6 " "
+      Equals@6..7 "="
+      Whitespace@7..8 " "
+      VariableRef@8..9
+        Ident@8..9 "c""#]],
+        );
+    }
+
+    #[test]
+    fn parse_call() {
+        check(
+            "add(1, 2)",
+            expect![[r#"
+Root@0..9
+  CallExpr@0..9
+    VariableRef@0..3
+      Ident@0..3 "add"
+    ArgList@3..9
+      LParen@3..4 "("
+      Literal@4..5
+        Integer@4..5 "1"
+      Comma@5..6 ","
+      Whitespace@6..7 " "
+      Literal@7..8
+        Integer@7..8 "2"
+      RParen@8..9 ")""#]],
+        );
+    }
+
+    #[test]
+    fn parse_call_without_args() {
+        check(
+            "increment()",
+            expect![[r#"
+Root@0..11
+  CallExpr@0..11
+    VariableRef@0..9
+      Ident@0..9 "increment"
+    ArgList@9..11
+      LParen@9..10 "("
+      RParen@10..11 ")""#]],
+        );
+    }
+
+    #[test]
+    fn parse_chained_calls() {
+        check(
+            "f(1)(2)",
+            expect![[r#"
+Root@0..7
+  CallExpr@0..7
+    CallExpr@0..4
+      VariableRef@0..1
+        Ident@0..1 "f"
+      ArgList@1..4
+        LParen@1..2 "("
+        Literal@2..3
+          Integer@2..3 "1"
+        RParen@3..4 ")"
+    ArgList@4..7
+      LParen@4..5 "("
+      Literal@5..6
+        Integer@5..6 "2"
+      RParen@6..7 ")""#]],
+        );
+    }
+
+    #[test]
+    fn call_has_higher_binding_power_than_negation() {
+        check(
+            "-f(1)",
+            expect![[r#"
+Root@0..5
+  PrefixExpr@0..5
+    Minus@0..1 "-"
+    CallExpr@1..5
+      VariableRef@1..2
+        Ident@1..2 "f"
+      ArgList@2..5
+        LParen@2..3 "("
+        Literal@3..4
+          Integer@3..4 "1"
+        RParen@4..5 ")""#]],
+        );
+    }
+
+    #[test]
+    fn parse_closure() {
+        check(
+            "|x| x + 1",
+            expect![[r#"
+Root@0..9
+  ClosureExpr@0..9
+    ParamList@0..4
+      Bar@0..1 "|"
+      Param@1..2
+        Ident@1..2 "x"
+      Bar@2..3 "|"
+      Whitespace@3..4 " "
+    InfixExpr@4..9
+      VariableRef@4..6
+        Ident@4..5 "x"
+        Whitespace@5..6 " "
+      Plus@6..7 "+"
+      Whitespace@7..8 " "
+      Literal@8..9
+        Integer@8..9 "1""#]],
+        );
+    }
+
+    #[test]
+    fn parse_closure_without_params() {
+        check(
+            "|| count = count + 1",
+            expect![[r#"
+Root@0..20
+  ClosureExpr@0..20
+    ParamList@0..3
+      BarBar@0..2 "||"
+      Whitespace@2..3 " "
+    AssignExpr@3..20
+      VariableRef@3..9
+        Ident@3..8 "count"
+        Whitespace@8..9 " "
+      Equals@9..10 "="
+      Whitespace@10..11 " "
+      InfixExpr@11..20
+        VariableRef@11..17
+          Ident@11..16 "count"
+          Whitespace@16..17 " "
+        Plus@17..18 "+"
+        Whitespace@18..19 " "
+        Literal@19..20
+          Integer@19..20 "1""#]],
+        );
+    }
+
+    #[test]
+    fn parse_closure_with_typed_params() {
+        check(
+            "|a: i32, b: i32| a * b",
+            expect![[r#"
+Root@0..22
+  ClosureExpr@0..22
+    ParamList@0..17
+      Bar@0..1 "|"
+      Param@1..7
+        Ident@1..2 "a"
+        Colon@2..3 ":"
+        Whitespace@3..4 " "
+        PathType@4..7
+          Ident@4..7 "i32"
+      Comma@7..8 ","
+      Whitespace@8..9 " "
+      Param@9..15
+        Ident@9..10 "b"
+        Colon@10..11 ":"
+        Whitespace@11..12 " "
+        PathType@12..15
+          Ident@12..15 "i32"
+      Bar@15..16 "|"
+      Whitespace@16..17 " "
+    InfixExpr@17..22
+      VariableRef@17..19
+        Ident@17..18 "a"
+        Whitespace@18..19 " "
+      Star@19..20 "*"
+      Whitespace@20..21 " "
+      VariableRef@21..22
+        Ident@21..22 "b""#]],
+        );
+    }
+
+    #[test]
+    fn parse_closure_as_argument() {
+        check(
+            "map(|x| x, 1)",
+            expect![[r#"
+Root@0..13
+  CallExpr@0..13
+    VariableRef@0..3
+      Ident@0..3 "map"
+    ArgList@3..13
+      LParen@3..4 "("
+      ClosureExpr@4..9
+        ParamList@4..8
+          Bar@4..5 "|"
+          Param@5..6
+            Ident@5..6 "x"
+          Bar@6..7 "|"
+          Whitespace@7..8 " "
+        VariableRef@8..9
+          Ident@8..9 "x"
+      Comma@9..10 ","
+      Whitespace@10..11 " "
+      Literal@11..12
+        Integer@11..12 "1"
+      RParen@12..13 ")""#]],
+        );
+    }
+
+    #[test]
+    fn parse_or_is_not_a_closure() {
+        check(
+            "a || b",
+            expect![[r#"
+Root@0..6
+  InfixExpr@0..6
+    VariableRef@0..2
+      Ident@0..1 "a"
+      Whitespace@1..2 " "
+    BarBar@2..4 "||"
+    Whitespace@4..5 " "
+    VariableRef@5..6
+      Ident@5..6 "b""#]],
+        );
+    }
+
+    #[test]
+    fn parse_unclosed_closure_params() {
+        check(
+            "|a b",
+            expect![[r#"
+Root@0..4
+  ClosureExpr@0..4
+    ParamList@0..4
+      Bar@0..1 "|"
+      Param@1..3
+        Ident@1..2 "a"
+        Whitespace@2..3 " "
+      Param@3..4
+        Ident@3..4 "b"
+error at 3..4: expected `:`, `,` or `|`, but found identifier
+error at 3..4: expected `:`, `,` or `|`
+error at 3..4: expected number, string, true, false, identifier, `-`, `!`, `(`, `|`, `||` or match"#]],
+        );
+    }
+
+    #[test]
+    fn parse_path_expr() {
+        check(
+            "Status::Loading",
+            expect![[r#"
+Root@0..15
+  PathExpr@0..15
+    Ident@0..6 "Status"
+    ColonColon@6..8 "::"
+    Ident@8..15 "Loading""#]],
+        );
+    }
+
+    #[test]
+    fn parse_variant_constructor() {
+        check(
+            "Status::Loaded(user)",
+            expect![[r#"
+Root@0..20
+  CallExpr@0..20
+    PathExpr@0..14
+      Ident@0..6 "Status"
+      ColonColon@6..8 "::"
+      Ident@8..14 "Loaded"
+    ArgList@14..20
+      LParen@14..15 "("
+      VariableRef@15..19
+        Ident@15..19 "user"
+      RParen@19..20 ")""#]],
+        );
+    }
+
+    #[test]
+    fn parse_struct_expr() {
+        check(
+            "User { name: \"a\", age: 1 }",
+            expect![[r#"
+Root@0..26
+  StructExpr@0..26
+    Ident@0..4 "User"
+    Whitespace@4..5 " "
+    LBrace@5..6 "{"
+    Whitespace@6..7 " "
+    StructExprField@7..16
+      Ident@7..11 "name"
+      Colon@11..12 ":"
+      Whitespace@12..13 " "
+      Literal@13..16
+        String@13..16 "\"a\""
+    Comma@16..17 ","
+    Whitespace@17..18 " "
+    StructExprField@18..25
+      Ident@18..21 "age"
+      Colon@21..22 ":"
+      Whitespace@22..23 " "
+      Literal@23..25
+        Integer@23..24 "1"
+        Whitespace@24..25 " "
+    RBrace@25..26 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_match() {
+        check(
+            "match status {\n  Status::Loading => 0,\n  Status::Loaded(user) => user,\n}",
+            expect![[r#"
+Root@0..72
+  MatchExpr@0..72
+    MatchKeyword@0..5 "match"
+    Whitespace@5..6 " "
+    VariableRef@6..13
+      Ident@6..12 "status"
+      Whitespace@12..13 " "
+    MatchArmList@13..72
+      LBrace@13..14 "{"
+      Whitespace@14..17 "\n  "
+      MatchArm@17..37
+        VariantPat@17..33
+          Ident@17..23 "Status"
+          ColonColon@23..25 "::"
+          Ident@25..32 "Loading"
+          Whitespace@32..33 " "
+        FatArrow@33..35 "=>"
+        Whitespace@35..36 " "
+        Literal@36..37
+          Integer@36..37 "0"
+      Comma@37..38 ","
+      Whitespace@38..41 "\n  "
+      MatchArm@41..69
+        VariantPat@41..62
+          Ident@41..47 "Status"
+          ColonColon@47..49 "::"
+          Ident@49..55 "Loaded"
+          LParen@55..56 "("
+          IdentPat@56..60
+            Ident@56..60 "user"
+          RParen@60..61 ")"
+          Whitespace@61..62 " "
+        FatArrow@62..64 "=>"
+        Whitespace@64..65 " "
+        VariableRef@65..69
+          Ident@65..69 "user"
+      Comma@69..70 ","
+      Whitespace@70..71 "\n"
+      RBrace@71..72 "}""#]],
+        );
+    }
+
+    #[test]
+    fn match_scrutinee_is_not_a_struct_expr() {
+        check(
+            "match user { u => u }",
+            expect![[r#"
+Root@0..21
+  MatchExpr@0..21
+    MatchKeyword@0..5 "match"
+    Whitespace@5..6 " "
+    VariableRef@6..11
+      Ident@6..10 "user"
+      Whitespace@10..11 " "
+    MatchArmList@11..21
+      LBrace@11..12 "{"
+      Whitespace@12..13 " "
+      MatchArm@13..20
+        IdentPat@13..15
+          Ident@13..14 "u"
+          Whitespace@14..15 " "
+        FatArrow@15..17 "=>"
+        Whitespace@17..18 " "
+        VariableRef@18..20
+          Ident@18..19 "u"
+          Whitespace@19..20 " "
+      RBrace@20..21 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_struct_expr_in_parenthesized_scrutinee() {
+        check(
+            "match (User {}) { _ => 1 }",
+            expect![[r#"
+Root@0..26
+  MatchExpr@0..26
+    MatchKeyword@0..5 "match"
+    Whitespace@5..6 " "
+    ParenExpr@6..16
+      LParen@6..7 "("
+      StructExpr@7..14
+        Ident@7..11 "User"
+        Whitespace@11..12 " "
+        LBrace@12..13 "{"
+        RBrace@13..14 "}"
+      RParen@14..15 ")"
+      Whitespace@15..16 " "
+    MatchArmList@16..26
+      LBrace@16..17 "{"
+      Whitespace@17..18 " "
+      MatchArm@18..25
+        WildcardPat@18..20
+          Underscore@18..19 "_"
+          Whitespace@19..20 " "
+        FatArrow@20..22 "=>"
+        Whitespace@22..23 " "
+        Literal@23..25
+          Integer@23..24 "1"
+          Whitespace@24..25 " "
+      RBrace@25..26 "}""#]],
+        );
+    }
+
+    #[test]
+    fn recover_from_error_in_arg_list() {
+        check(
+            "f(1 +)\nstate b = 2",
+            expect![[r#"
+Root@0..18
+  CallExpr@0..7
+    VariableRef@0..1
+      Ident@0..1 "f"
+    ArgList@1..7
+      LParen@1..2 "("
+      InfixExpr@2..5
+        Literal@2..4
+          Integer@2..3 "1"
+          Whitespace@3..4 " "
+        Plus@4..5 "+"
+      RParen@5..6 ")"
+      Whitespace@6..7 "\n"
+  StateDef@7..18
+    StateKeyword@7..12 "state"
+    Whitespace@12..13 " "
+    Ident@13..14 "b"
+    Whitespace@14..15 " "
+    Equals@15..16 "="
+    Whitespace@16..17 " "
+    Literal@17..18
+      Integer@17..18 "2"
+error at 5..6: expected number, string, true, false, identifier, `-`, `!`, `(`, `|`, `||` or match, but found `)`"#]],
+        );
+    }
 }
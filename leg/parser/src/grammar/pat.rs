@@ -0,0 +1,139 @@
+use super::*;
+
+/// Parses a pattern
+/// _, user, Status::Loading, Status::Loaded(user)
+pub(super) fn parse_pattern(parser: &mut Parser) -> Option<CompletedMarker> {
+    let cm = if parser.at(TokenKind::Underscore) {
+        parse_wildcard_pattern(parser)
+    } else if parser.at(TokenKind::Ident) {
+        parse_ident_or_variant_pattern(parser)
+    } else {
+        parser.error();
+        return None;
+    };
+
+    Some(cm)
+}
+
+fn parse_wildcard_pattern(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::Underscore));
+    let marker = parser.start();
+    parser.bump();
+    marker.complete(parser, SyntaxKind::WildcardPat)
+}
+
+/// A single name binds the matched value, while a path refers to an enum variant.
+fn parse_ident_or_variant_pattern(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::Ident));
+    let marker = parser.start();
+    parser.bump();
+
+    if !parser.at(TokenKind::ColonColon) {
+        return marker.complete(parser, SyntaxKind::IdentPat);
+    }
+
+    while parser.at(TokenKind::ColonColon) {
+        parser.bump();
+        parser.expect(TokenKind::Ident);
+    }
+
+    if parser.at(TokenKind::LParen) {
+        parser.bump();
+        parser.push_recovery(&[TokenKind::RParen]);
+
+        while !parser.at(TokenKind::RParen) && !parser.at_end() {
+            if parse_pattern(parser).is_none() {
+                break;
+            }
+
+            if parser.at(TokenKind::Comma) {
+                parser.bump();
+            } else {
+                break;
+            }
+        }
+
+        parser.expect(TokenKind::RParen);
+        parser.pop_recovery();
+    }
+
+    marker.complete(parser, SyntaxKind::VariantPat)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check;
+    use expect_test::expect;
+
+    #[test]
+    fn parse_wildcard_pattern() {
+        check(
+            "match a { _ => 1 }",
+            expect![[r#"
+Root@0..18
+  MatchExpr@0..18
+    MatchKeyword@0..5 "match"
+    Whitespace@5..6 " "
+    VariableRef@6..8
+      Ident@6..7 "a"
+      Whitespace@7..8 " "
+    MatchArmList@8..18
+      LBrace@8..9 "{"
+      Whitespace@9..10 " "
+      MatchArm@10..17
+        WildcardPat@10..12
+          Underscore@10..11 "_"
+          Whitespace@11..12 " "
+        FatArrow@12..14 "=>"
+        Whitespace@14..15 " "
+        Literal@15..17
+          Integer@15..16 "1"
+          Whitespace@16..17 " "
+      RBrace@17..18 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_nested_variant_pattern() {
+        check(
+            "match a { Status::Loaded(User::Admin(name), _) => name }",
+            expect![[r#"
+Root@0..56
+  MatchExpr@0..56
+    MatchKeyword@0..5 "match"
+    Whitespace@5..6 " "
+    VariableRef@6..8
+      Ident@6..7 "a"
+      Whitespace@7..8 " "
+    MatchArmList@8..56
+      LBrace@8..9 "{"
+      Whitespace@9..10 " "
+      MatchArm@10..55
+        VariantPat@10..47
+          Ident@10..16 "Status"
+          ColonColon@16..18 "::"
+          Ident@18..24 "Loaded"
+          LParen@24..25 "("
+          VariantPat@25..42
+            Ident@25..29 "User"
+            ColonColon@29..31 "::"
+            Ident@31..36 "Admin"
+            LParen@36..37 "("
+            IdentPat@37..41
+              Ident@37..41 "name"
+            RParen@41..42 ")"
+          Comma@42..43 ","
+          Whitespace@43..44 " "
+          WildcardPat@44..45
+            Underscore@44..45 "_"
+          RParen@45..46 ")"
+          Whitespace@46..47 " "
+        FatArrow@47..49 "=>"
+        Whitespace@49..50 " "
+        VariableRef@50..55
+          Ident@50..54 "name"
+          Whitespace@54..55 " "
+      RBrace@55..56 "}""#]],
+        );
+    }
+}
@@ -1,18 +1,59 @@
 use crate::grammar::expr::parse_variable_ref;
 
 use super::expr::parse_expression;
+use super::ty::{parse_ret_type, parse_tuple_type, parse_type, parse_type_annotation};
 use super::CompletedMarker;
 use super::Parser;
+use crate::parser::ParseErrorKind;
 use lexer::TokenKind;
 use syntax::syntax_kind::SyntaxKind;
 
+/// Tokens that can both continue an expression and start a new one
+const AMBIGUOUS_CONTINUATION: [TokenKind; 3] =
+    [TokenKind::Minus, TokenKind::LParen, TokenKind::BarBar];
+
 pub(super) fn parse_statement(parser: &mut Parser) -> Option<CompletedMarker> {
-    if parser.at(TokenKind::LetKeyword) {
-        Some(parse_variable_def(parser))
+    // Declarations end with a `}`, so they don't need to be terminated
+    let (cm, needs_terminator) = if parser.at(TokenKind::LetKeyword) {
+        (Some(parse_variable_def(parser)), true)
+    } else if parser.at(TokenKind::StateKeyword) {
+        (Some(parse_state_def(parser)), true)
     } else if parser.at(TokenKind::FuncKeyword) {
-        Some(parse_func(parser))
+        (Some(parse_func(parser)), false)
+    } else if parser.at(TokenKind::StructKeyword) {
+        (Some(parse_struct_decl(parser)), false)
+    } else if parser.at(TokenKind::EnumKeyword) {
+        (Some(parse_enum_decl(parser)), false)
     } else {
-        parse_expression(parser)
+        (parse_expression(parser), true)
+    };
+
+    if cm.is_some() {
+        parse_terminator(parser, needs_terminator);
+    }
+
+    cm
+}
+
+/// A statement ends with a `;`, a newline, a `}` or the end of the input.
+/// A newline only ends a statement if the next line can't continue it,
+/// see `parse_expression_bp`.
+fn parse_terminator(parser: &mut Parser, needs_terminator: bool) {
+    if parser.at(TokenKind::Semicolon) {
+        parser.bump();
+        return;
+    }
+
+    if parser.at_end() || parser.at_set(&[TokenKind::RBrace]) {
+        return;
+    }
+
+    if parser.at_line_break() {
+        if parser.at_set(&AMBIGUOUS_CONTINUATION) {
+            parser.error_before_statement(|found| ParseErrorKind::AmbiguousContinuation { found });
+        }
+    } else if needs_terminator {
+        parser.error_before_statement(|found| ParseErrorKind::MissingTerminator { found });
     }
 }
 
@@ -27,6 +68,7 @@ pub(crate) fn parse_variable_def(parser: &mut Parser) -> CompletedMarker {
     parser.eat(TokenKind::MutKeyword);
 
     parser.expect(TokenKind::Ident);
+    parse_type_annotation(parser);
     parser.expect(TokenKind::Equals);
 
     parse_expression(parser);
@@ -34,8 +76,26 @@ pub(crate) fn parse_variable_def(parser: &mut Parser) -> CompletedMarker {
     marker.complete(parser, SyntaxKind::VariableDef)
 }
 
+/// Parse a state definition
+/// state count = 0
+pub(crate) fn parse_state_def(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::StateKeyword));
+    let marker = parser.start();
+
+    // Eat state keyword
+    parser.bump();
+
+    parser.expect(TokenKind::Ident);
+    parse_type_annotation(parser);
+    parser.expect(TokenKind::Equals);
+
+    parse_expression(parser);
+
+    marker.complete(parser, SyntaxKind::StateDef)
+}
+
 /// Parse a function statement
-/// func a() {}
+/// func a(b: i32) -> i32 {}
 pub(crate) fn parse_func(parser: &mut Parser) -> CompletedMarker {
     assert!(parser.at(TokenKind::FuncKeyword));
     let marker = parser.start();
@@ -43,11 +103,29 @@ pub(crate) fn parse_func(parser: &mut Parser) -> CompletedMarker {
     // Eat func keyword
     parser.bump();
 
-    parse_variable_ref(parser);
+    // A broken signature shouldn't take the body with it
+    parser.push_recovery(&[TokenKind::LParen, TokenKind::Arrow, TokenKind::LBrace]);
+
+    if parser.at(TokenKind::Ident) {
+        parse_variable_ref(parser);
+    } else {
+        parser.error();
+    }
+
+    if parser.at(TokenKind::LParen) {
+        parse_param_list(parser);
+    } else {
+        parser.error();
+    }
 
-    parse_param_list(parser);
+    parse_ret_type(parser);
+    parser.pop_recovery();
 
-    parse_block_stmt(parser);
+    if parser.at(TokenKind::LBrace) {
+        parse_block_stmt(parser);
+    } else {
+        parser.error();
+    }
 
     marker.complete(parser, SyntaxKind::FunctionDecl)
 }
@@ -58,17 +136,158 @@ pub(crate) fn parse_param_list(parser: &mut Parser) -> CompletedMarker {
     let marker = parser.start();
     parser.bump(); // Consume the leading paren
 
-    parser.expect(TokenKind::RParen);
+    parse_params(parser, TokenKind::RParen);
     marker.complete(parser, SyntaxKind::ParamList)
 }
 
+/// Parses a comma separated list of parameters up to and including the
+/// `closing` token, e.g. `a: i32, b)` or `a, b|`.
+pub(crate) fn parse_params(parser: &mut Parser, closing: TokenKind) {
+    let recovery: &'static [TokenKind] = match closing {
+        TokenKind::Bar => &[TokenKind::Bar, TokenKind::Comma],
+        _ => &[TokenKind::RParen, TokenKind::Comma],
+    };
+
+    parse_comma_separated(parser, closing, recovery, parse_param);
+}
+
+/// Parses elements that start with an identifier, separated by commas, up
+/// to and including `closing`. `recovery` holds `closing` and the comma, so
+/// that an error inside of an element stops at either. A missing comma or a
+/// stray token is reported without ending the list, which only ends at
+/// `closing` or at a token that an enclosing rule continues from.
+fn parse_comma_separated(
+    parser: &mut Parser,
+    closing: TokenKind,
+    recovery: &'static [TokenKind],
+    parse_element: fn(&mut Parser) -> CompletedMarker,
+) {
+    parser.push_recovery(recovery);
+
+    loop {
+        if parser.at(closing) || parser.at_end() {
+            break;
+        }
+
+        if parser.at(TokenKind::Ident) {
+            parse_element(parser);
+        } else if parser.at_recovery_set() && parser.peek() != Some(TokenKind::Comma) {
+            break;
+        } else {
+            // Skips the token, unless it is a comma after a missing element
+            parser.error();
+        }
+
+        if parser.at(TokenKind::Comma) {
+            parser.bump();
+        } else if parser.at(closing) || parser.at_end() {
+            break;
+        } else if parser.peek() == Some(TokenKind::Ident) {
+            // Only the comma is missing
+            parser.report_error();
+        } else if parser.at_recovery_set() {
+            break;
+        } else {
+            parser.error();
+        }
+    }
+
+    parser.expect(closing);
+    parser.pop_recovery();
+}
+
+/// Parse a single parameter with an optional type annotation
+/// a: i32
+fn parse_param(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::Ident));
+    let marker = parser.start();
+    parser.bump();
+
+    parse_type_annotation(parser);
+
+    marker.complete(parser, SyntaxKind::Param)
+}
+
+/// Parse a struct declaration
+/// struct User { name: String, age: i32 }
+pub(crate) fn parse_struct_decl(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::StructKeyword));
+    let marker = parser.start();
+
+    // Eat struct keyword
+    parser.bump();
+
+    parser.expect(TokenKind::Ident);
+    parser.expect(TokenKind::LBrace);
+    parse_comma_separated(
+        parser,
+        TokenKind::RBrace,
+        &[TokenKind::RBrace, TokenKind::Comma],
+        parse_field_decl,
+    );
+    marker.complete(parser, SyntaxKind::StructDecl)
+}
+
+/// name: String
+fn parse_field_decl(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::Ident));
+    let marker = parser.start();
+    parser.bump();
+
+    parser.expect(TokenKind::Colon);
+    parse_type(parser);
+
+    marker.complete(parser, SyntaxKind::FieldDecl)
+}
+
+/// Parse an enum declaration
+/// enum Status { Loading, Loaded(User), Failed(String) }
+pub(crate) fn parse_enum_decl(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::EnumKeyword));
+    let marker = parser.start();
+
+    // Eat enum keyword
+    parser.bump();
+
+    parser.expect(TokenKind::Ident);
+    parser.expect(TokenKind::LBrace);
+    parse_comma_separated(
+        parser,
+        TokenKind::RBrace,
+        &[TokenKind::RBrace, TokenKind::Comma],
+        parse_variant_decl,
+    );
+    marker.complete(parser, SyntaxKind::EnumDecl)
+}
+
+/// Loaded(User)
+fn parse_variant_decl(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::Ident));
+    let marker = parser.start();
+    parser.bump();
+
+    if parser.at(TokenKind::LParen) {
+        parse_tuple_type(parser);
+    }
+
+    marker.complete(parser, SyntaxKind::VariantDecl)
+}
+
 pub(crate) fn parse_block_stmt(parser: &mut Parser) -> CompletedMarker {
     assert!(parser.at(TokenKind::LBrace));
 
     let marker = parser.start();
     parser.bump(); // Consume the leading brace
+    let significant = parser.set_newlines_significant(true);
+    parser.push_recovery(&[TokenKind::RBrace]);
+
+    while !parser.at(TokenKind::RBrace) && !parser.at_end() {
+        parse_statement(parser);
+    }
 
     parser.expect(TokenKind::RBrace);
+    parser.pop_recovery();
+    parser.set_newlines_significant(significant);
     marker.complete(parser, SyntaxKind::BlockStmt)
 }
 
@@ -91,7 +310,7 @@ Root@0..13
     ParamList@8..11
       LParen@8..9 "("
       RParen@9..10 ")"
-    Whitespace@10..11 " "
+      Whitespace@10..11 " "
     BlockStmt@11..13
       LBrace@11..12 "{"
       RBrace@12..13 "}""#]],
@@ -158,7 +377,7 @@ Root@0..17
     Whitespace@15..16 " "
     VariableRef@16..17
       Ident@16..17 "a"
-error at 8..11: expected number, string, true, false, identifier, `-` or `(`, but found let"#]],
+error at 8..11: expected number, string, true, false, identifier, `-`, `!`, `(`, `|`, `||` or match, but found let"#]],
         );
     }
 
@@ -182,4 +401,809 @@ Root@0..11
     Ident@10..11 "a""#]],
         );
     }
+
+    #[test]
+    fn parse_state_definition() {
+        check(
+            "state count = 0",
+            expect![[r#"
+Root@0..15
+  StateDef@0..15
+    StateKeyword@0..5 "state"
+    Whitespace@5..6 " "
+    Ident@6..11 "count"
+    Whitespace@11..12 " "
+    Equals@12..13 "="
+    Whitespace@13..14 " "
+    Literal@14..15
+      Integer@14..15 "0""#]],
+        );
+    }
+
+    #[test]
+    fn parse_function_with_params() {
+        check(
+            "func add(a: i32, b) {}",
+            expect![[r#"
+Root@0..22
+  FunctionDecl@0..22
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..8
+      Ident@5..8 "add"
+    ParamList@8..20
+      LParen@8..9 "("
+      Param@9..15
+        Ident@9..10 "a"
+        Colon@10..11 ":"
+        Whitespace@11..12 " "
+        PathType@12..15
+          Ident@12..15 "i32"
+      Comma@15..16 ","
+      Whitespace@16..17 " "
+      Param@17..18
+        Ident@17..18 "b"
+      RParen@18..19 ")"
+      Whitespace@19..20 " "
+    BlockStmt@20..22
+      LBrace@20..21 "{"
+      RBrace@21..22 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_typed_variable_definition() {
+        check(
+            "let a: i32 = 1",
+            expect![[r#"
+Root@0..14
+  VariableDef@0..14
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Colon@5..6 ":"
+    Whitespace@6..7 " "
+    PathType@7..11
+      Ident@7..10 "i32"
+      Whitespace@10..11 " "
+    Equals@11..12 "="
+    Whitespace@12..13 " "
+    Literal@13..14
+      Integer@13..14 "1""#]],
+        );
+    }
+
+    #[test]
+    fn parse_typed_state_definition() {
+        check(
+            "state count: i32 = 0",
+            expect![[r#"
+Root@0..20
+  StateDef@0..20
+    StateKeyword@0..5 "state"
+    Whitespace@5..6 " "
+    Ident@6..11 "count"
+    Colon@11..12 ":"
+    Whitespace@12..13 " "
+    PathType@13..17
+      Ident@13..16 "i32"
+      Whitespace@16..17 " "
+    Equals@17..18 "="
+    Whitespace@18..19 " "
+    Literal@19..20
+      Integer@19..20 "0""#]],
+        );
+    }
+
+    #[test]
+    fn parse_function_with_return_type() {
+        check(
+            "func f(x: String) -> bool {}",
+            expect![[r#"
+Root@0..28
+  FunctionDecl@0..28
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..6
+      Ident@5..6 "f"
+    ParamList@6..18
+      LParen@6..7 "("
+      Param@7..16
+        Ident@7..8 "x"
+        Colon@8..9 ":"
+        Whitespace@9..10 " "
+        PathType@10..16
+          Ident@10..16 "String"
+      RParen@16..17 ")"
+      Whitespace@17..18 " "
+    RetType@18..26
+      Arrow@18..20 "->"
+      Whitespace@20..21 " "
+      PathType@21..26
+        Ident@21..25 "bool"
+        Whitespace@25..26 " "
+    BlockStmt@26..28
+      LBrace@26..27 "{"
+      RBrace@27..28 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_function_with_body() {
+        check(
+            "func f() {\n  let a = 1\n  a\n}",
+            expect![[r#"
+Root@0..28
+  FunctionDecl@0..28
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..6
+      Ident@5..6 "f"
+    ParamList@6..9
+      LParen@6..7 "("
+      RParen@7..8 ")"
+      Whitespace@8..9 " "
+    BlockStmt@9..28
+      LBrace@9..10 "{"
+      Whitespace@10..13 "\n  "
+      VariableDef@13..25
+        LetKeyword@13..16 "let"
+        Whitespace@16..17 " "
+        Ident@17..18 "a"
+        Whitespace@18..19 " "
+        Equals@19..20 "="
+        Whitespace@20..21 " "
+        Literal@21..25
+          Integer@21..22 "1"
+          Whitespace@22..25 "\n  "
+      VariableRef@25..27
+        Ident@25..26 "a"
+        Whitespace@26..27 "\n"
+      RBrace@27..28 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_struct_declaration() {
+        check(
+            "struct User { name: String, age: i32 }",
+            expect![[r#"
+Root@0..38
+  StructDecl@0..38
+    StructKeyword@0..6 "struct"
+    Whitespace@6..7 " "
+    Ident@7..11 "User"
+    Whitespace@11..12 " "
+    LBrace@12..13 "{"
+    Whitespace@13..14 " "
+    FieldDecl@14..26
+      Ident@14..18 "name"
+      Colon@18..19 ":"
+      Whitespace@19..20 " "
+      PathType@20..26
+        Ident@20..26 "String"
+    Comma@26..27 ","
+    Whitespace@27..28 " "
+    FieldDecl@28..37
+      Ident@28..31 "age"
+      Colon@31..32 ":"
+      Whitespace@32..33 " "
+      PathType@33..37
+        Ident@33..36 "i32"
+        Whitespace@36..37 " "
+    RBrace@37..38 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_enum_declaration() {
+        check(
+            "enum Status { Loading, Loaded(User), Failed(String), }",
+            expect![[r#"
+Root@0..54
+  EnumDecl@0..54
+    EnumKeyword@0..4 "enum"
+    Whitespace@4..5 " "
+    Ident@5..11 "Status"
+    Whitespace@11..12 " "
+    LBrace@12..13 "{"
+    Whitespace@13..14 " "
+    VariantDecl@14..21
+      Ident@14..21 "Loading"
+    Comma@21..22 ","
+    Whitespace@22..23 " "
+    VariantDecl@23..35
+      Ident@23..29 "Loaded"
+      TupleType@29..35
+        LParen@29..30 "("
+        PathType@30..34
+          Ident@30..34 "User"
+        RParen@34..35 ")"
+    Comma@35..36 ","
+    Whitespace@36..37 " "
+    VariantDecl@37..51
+      Ident@37..43 "Failed"
+      TupleType@43..51
+        LParen@43..44 "("
+        PathType@44..50
+          Ident@44..50 "String"
+        RParen@50..51 ")"
+    Comma@51..52 ","
+    Whitespace@52..53 " "
+    RBrace@53..54 "}""#]],
+        );
+    }
+
+    #[test]
+    fn recover_from_missing_commas_between_params() {
+        check(
+            "func f(a b c) { }",
+            expect![[r#"
+Root@0..17
+  FunctionDecl@0..17
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..6
+      Ident@5..6 "f"
+    ParamList@6..14
+      LParen@6..7 "("
+      Param@7..9
+        Ident@7..8 "a"
+        Whitespace@8..9 " "
+      Param@9..11
+        Ident@9..10 "b"
+        Whitespace@10..11 " "
+      Param@11..12
+        Ident@11..12 "c"
+      RParen@12..13 ")"
+      Whitespace@13..14 " "
+    BlockStmt@14..17
+      LBrace@14..15 "{"
+      Whitespace@15..16 " "
+      RBrace@16..17 "}"
+error at 9..10: expected `:`, `,` or `)`, but found identifier
+error at 11..12: expected `:`, `,` or `)`, but found identifier"#]],
+        );
+    }
+
+    #[test]
+    fn recover_from_missing_field_type() {
+        check(
+            "struct S { a: , b: i32 }",
+            expect![[r#"
+Root@0..24
+  StructDecl@0..24
+    StructKeyword@0..6 "struct"
+    Whitespace@6..7 " "
+    Ident@7..8 "S"
+    Whitespace@8..9 " "
+    LBrace@9..10 "{"
+    Whitespace@10..11 " "
+    FieldDecl@11..14
+      Ident@11..12 "a"
+      Colon@12..13 ":"
+      Whitespace@13..14 " "
+    Comma@14..15 ","
+    Whitespace@15..16 " "
+    FieldDecl@16..23
+      Ident@16..17 "b"
+      Colon@17..18 ":"
+      Whitespace@18..19 " "
+      PathType@19..23
+        Ident@19..22 "i32"
+        Whitespace@22..23 " "
+    RBrace@23..24 "}"
+error at 14..15: expected identifier, `(` or `[`, but found `,`"#]],
+        );
+    }
+
+    #[test]
+    fn recover_from_stray_token_in_variants() {
+        check(
+            "enum E { A 1 B, }",
+            expect![[r#"
+Root@0..17
+  EnumDecl@0..17
+    EnumKeyword@0..4 "enum"
+    Whitespace@4..5 " "
+    Ident@5..6 "E"
+    Whitespace@6..7 " "
+    LBrace@7..8 "{"
+    Whitespace@8..9 " "
+    VariantDecl@9..11
+      Ident@9..10 "A"
+      Whitespace@10..11 " "
+    Error@11..13
+      Integer@11..12 "1"
+      Whitespace@12..13 " "
+    VariantDecl@13..14
+      Ident@13..14 "B"
+    Comma@14..15 ","
+    Whitespace@15..16 " "
+    RBrace@16..17 "}"
+error at 11..12: expected `(`, `,` or `}`, but found number"#]],
+        );
+    }
+
+    #[test]
+    fn list_each_expected_token_once() {
+        check(
+            "a\n)",
+            expect![[r#"
+Root@0..3
+  VariableRef@0..2
+    Ident@0..1 "a"
+    Whitespace@1..2 "\n"
+  Error@2..3
+    RParen@2..3 ")"
+error at 2..3: expected `::`, `{`, `(`, `+`, `-`, `/`, `*`, `%`, `&&`, `||`, `<`, `<=`, `>`, `>=`, `==`, `!=`, `=`, `;`, `}`, let, state, func, struct, enum, number, string, true, false, identifier, `!`, `|` or match, but found `)`"#]],
+        );
+    }
+
+    #[test]
+    fn parse_statements_separated_by_semicolons() {
+        check(
+            "let a = 1; a;",
+            expect![[r#"
+Root@0..13
+  VariableDef@0..9
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..9
+      Integer@8..9 "1"
+  Semicolon@9..10 ";"
+  Whitespace@10..11 " "
+  VariableRef@11..12
+    Ident@11..12 "a"
+  Semicolon@12..13 ";""#]],
+        );
+    }
+
+    #[test]
+    fn parse_statements_separated_by_newlines() {
+        check(
+            "let a = 1\nlet b = a",
+            expect![[r#"
+Root@0..19
+  VariableDef@0..10
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..10
+      Integer@8..9 "1"
+      Whitespace@9..10 "\n"
+  VariableDef@10..19
+    LetKeyword@10..13 "let"
+    Whitespace@13..14 " "
+    Ident@14..15 "b"
+    Whitespace@15..16 " "
+    Equals@16..17 "="
+    Whitespace@17..18 " "
+    VariableRef@18..19
+      Ident@18..19 "a""#]],
+        );
+    }
+
+    #[test]
+    fn parse_statements_on_one_line_without_semicolon() {
+        check(
+            "let a = 1 let b = a",
+            expect![[r#"
+Root@0..19
+  VariableDef@0..10
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..10
+      Integer@8..9 "1"
+      Whitespace@9..10 " "
+  VariableDef@10..19
+    LetKeyword@10..13 "let"
+    Whitespace@13..14 " "
+    Ident@14..15 "b"
+    Whitespace@15..16 " "
+    Equals@16..17 "="
+    Whitespace@17..18 " "
+    VariableRef@18..19
+      Ident@18..19 "a"
+error at 10..13: expected `;` or a newline, but found let"#]],
+        );
+    }
+
+    #[test]
+    fn leading_binary_operator_continues_statement() {
+        check(
+            "let a = 1\n  * 2",
+            expect![[r#"
+Root@0..15
+  VariableDef@0..15
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    InfixExpr@8..15
+      Literal@8..12
+        Integer@8..9 "1"
+        Whitespace@9..12 "\n  "
+      Star@12..13 "*"
+      Whitespace@13..14 " "
+      Literal@14..15
+        Integer@14..15 "2""#]],
+        );
+    }
+
+    #[test]
+    fn leading_minus_is_ambiguous() {
+        check(
+            "let a = 1\n-2",
+            expect![[r#"
+Root@0..12
+  VariableDef@0..10
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..10
+      Integer@8..9 "1"
+      Whitespace@9..10 "\n"
+  PrefixExpr@10..12
+    Minus@10..11 "-"
+    Literal@11..12
+      Integer@11..12 "2"
+error at 10..11: `-` at the start of a line is ambiguous, end the previous statement with `;` or move `-` to the end of the previous line"#]],
+        );
+    }
+
+    #[test]
+    fn leading_minus_after_semicolon_is_not_ambiguous() {
+        check(
+            "let a = 1;\n-2",
+            expect![[r#"
+Root@0..13
+  VariableDef@0..9
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..9
+      Integer@8..9 "1"
+  Semicolon@9..10 ";"
+  Whitespace@10..11 "\n"
+  PrefixExpr@11..13
+    Minus@11..12 "-"
+    Literal@12..13
+      Integer@12..13 "2""#]],
+        );
+    }
+
+    #[test]
+    fn call_on_next_line_is_ambiguous() {
+        check(
+            "f\n(1)",
+            expect![[r#"
+Root@0..5
+  VariableRef@0..2
+    Ident@0..1 "f"
+    Whitespace@1..2 "\n"
+  ParenExpr@2..5
+    LParen@2..3 "("
+    Literal@3..4
+      Integer@3..4 "1"
+    RParen@4..5 ")"
+error at 2..3: `(` at the start of a line is ambiguous, end the previous statement with `;` or move `(` to the end of the previous line"#]],
+        );
+    }
+
+    #[test]
+    fn newlines_inside_parentheses_are_not_significant() {
+        check(
+            "let a = (1\n-2)",
+            expect![[r#"
+Root@0..14
+  VariableDef@0..14
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    ParenExpr@8..14
+      LParen@8..9 "("
+      InfixExpr@9..13
+        Literal@9..11
+          Integer@9..10 "1"
+          Whitespace@10..11 "\n"
+        Minus@11..12 "-"
+        Literal@12..13
+          Integer@12..13 "2"
+      RParen@13..14 ")""#]],
+        );
+    }
+
+    #[test]
+    fn newlines_are_significant_inside_blocks() {
+        check(
+            "func f() {\n  a\n  -1\n}",
+            expect![[r#"
+Root@0..21
+  FunctionDecl@0..21
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..6
+      Ident@5..6 "f"
+    ParamList@6..9
+      LParen@6..7 "("
+      RParen@7..8 ")"
+      Whitespace@8..9 " "
+    BlockStmt@9..21
+      LBrace@9..10 "{"
+      Whitespace@10..13 "\n  "
+      VariableRef@13..17
+        Ident@13..14 "a"
+        Whitespace@14..17 "\n  "
+      PrefixExpr@17..20
+        Minus@17..18 "-"
+        Literal@18..20
+          Integer@18..19 "1"
+          Whitespace@19..20 "\n"
+      RBrace@20..21 "}"
+error at 17..18: `-` at the start of a line is ambiguous, end the previous statement with `;` or move `-` to the end of the previous line"#]],
+        );
+    }
+
+    #[test]
+    fn recover_from_error_in_param_type() {
+        check(
+            "func f(a: ) {\n  a\n}",
+            expect![[r#"
+Root@0..19
+  FunctionDecl@0..19
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..6
+      Ident@5..6 "f"
+    ParamList@6..12
+      LParen@6..7 "("
+      Param@7..10
+        Ident@7..8 "a"
+        Colon@8..9 ":"
+        Whitespace@9..10 " "
+      RParen@10..11 ")"
+      Whitespace@11..12 " "
+    BlockStmt@12..19
+      LBrace@12..13 "{"
+      Whitespace@13..16 "\n  "
+      VariableRef@16..18
+        Ident@16..17 "a"
+        Whitespace@17..18 "\n"
+      RBrace@18..19 "}"
+error at 10..11: expected identifier, `(` or `[`, but found `)`"#]],
+        );
+    }
+
+    #[test]
+    fn recover_from_error_at_end_of_block() {
+        check(
+            "func f() {\n  1 +\n}\nlet a = 1",
+            expect![[r#"
+Root@0..28
+  FunctionDecl@0..19
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..6
+      Ident@5..6 "f"
+    ParamList@6..9
+      LParen@6..7 "("
+      RParen@7..8 ")"
+      Whitespace@8..9 " "
+    BlockStmt@9..19
+      LBrace@9..10 "{"
+      Whitespace@10..13 "\n  "
+      InfixExpr@13..17
+        Literal@13..15
+          Integer@13..14 "1"
+          Whitespace@14..15 " "
+        Plus@15..16 "+"
+        Whitespace@16..17 "\n"
+      RBrace@17..18 "}"
+      Whitespace@18..19 "\n"
+  VariableDef@19..28
+    LetKeyword@19..22 "let"
+    Whitespace@22..23 " "
+    Ident@23..24 "a"
+    Whitespace@24..25 " "
+    Equals@25..26 "="
+    Whitespace@26..27 " "
+    Literal@27..28
+      Integer@27..28 "1"
+error at 17..18: expected number, string, true, false, identifier, `-`, `!`, `(`, `|`, `||` or match, but found `}`"#]],
+        );
+    }
+
+    #[test]
+    fn recover_from_missing_function_name() {
+        check(
+            "func (a) {}",
+            expect![[r#"
+Root@0..11
+  FunctionDecl@0..11
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    ParamList@5..9
+      LParen@5..6 "("
+      Param@6..7
+        Ident@6..7 "a"
+      RParen@7..8 ")"
+      Whitespace@8..9 " "
+    BlockStmt@9..11
+      LBrace@9..10 "{"
+      RBrace@10..11 "}"
+error at 5..6: expected identifier, but found `(`"#]],
+        );
+    }
+
+    #[test]
+    fn recover_from_missing_param_list() {
+        check(
+            "func f {}",
+            expect![[r#"
+Root@0..9
+  FunctionDecl@0..9
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..7
+      Ident@5..6 "f"
+      Whitespace@6..7 " "
+    BlockStmt@7..9
+      LBrace@7..8 "{"
+      RBrace@8..9 "}"
+error at 7..8: expected `(`, but found `{`"#]],
+        );
+    }
+
+    #[test]
+    fn attach_doc_comments_to_the_next_declaration() {
+        check(
+            "let a = 1\n/// Adds one\n/// to `n`\nfunc f(n) {}\n/// The user\nstruct User {\n  /// Full name\n  name: String\n}",
+            expect![[r#"
+Root@0..106
+  VariableDef@0..10
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..10
+      Integer@8..9 "1"
+      Whitespace@9..10 "\n"
+  FunctionDecl@10..47
+    DocComment@10..22 "/// Adds one"
+    Whitespace@22..23 "\n"
+    DocComment@23..33 "/// to `n`"
+    Whitespace@33..34 "\n"
+    FuncKeyword@34..38 "func"
+    Whitespace@38..39 " "
+    VariableRef@39..40
+      Ident@39..40 "f"
+    ParamList@40..44
+      LParen@40..41 "("
+      Param@41..42
+        Ident@41..42 "n"
+      RParen@42..43 ")"
+      Whitespace@43..44 " "
+    BlockStmt@44..47
+      LBrace@44..45 "{"
+      RBrace@45..46 "}"
+      Whitespace@46..47 "\n"
+  StructDecl@47..106
+    DocComment@47..59 "/// The user"
+    Whitespace@59..60 "\n"
+    StructKeyword@60..66 "struct"
+    Whitespace@66..67 " "
+    Ident@67..71 "User"
+    Whitespace@71..72 " "
+    LBrace@72..73 "{"
+    Whitespace@73..76 "\n  "
+    FieldDecl@76..105
+      DocComment@76..89 "/// Full name"
+      Whitespace@89..92 "\n  "
+      Ident@92..96 "name"
+      Colon@96..97 ":"
+      Whitespace@97..98 " "
+      PathType@98..105
+        Ident@98..104 "String"
+        Whitespace@104..105 "\n"
+    RBrace@105..106 "}""#]],
+        );
+    }
+
+    #[test]
+    fn doc_comments_before_expressions_stay_outside() {
+        check(
+            "/// one\n1\n/// end",
+            expect![[r#"
+Root@0..17
+  DocComment@0..7 "/// one"
+  Whitespace@7..8 "\n"
+  Literal@8..17
+    Integer@8..9 "1"
+    Whitespace@9..10 "\n"
+    DocComment@10..17 "/// end""#]],
+        );
+    }
+
+    #[test]
+    fn block_comment_with_a_newline_ends_the_statement() {
+        check(
+            "let a = 1 /* one\n*/ let b = 2",
+            expect![[r#"
+Root@0..29
+  VariableDef@0..20
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..20
+      Integer@8..9 "1"
+      Whitespace@9..10 " "
+      Comment@10..19 "/* one\n*/"
+      Whitespace@19..20 " "
+  VariableDef@20..29
+    LetKeyword@20..23 "let"
+    Whitespace@23..24 " "
+    Ident@24..25 "b"
+    Whitespace@25..26 " "
+    Equals@26..27 "="
+    Whitespace@27..28 " "
+    Literal@28..29
+      Integer@28..29 "2""#]],
+        );
+    }
+
+    #[test]
+    fn parse_statements_on_crlf_lines() {
+        check(
+            "let a = 1\r\n\tlet b = a",
+            expect![[r#"
+Root@0..21
+  VariableDef@0..12
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Whitespace@5..6 " "
+    Equals@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..12
+      Integer@8..9 "1"
+      Whitespace@9..12 "\r\n\t"
+  VariableDef@12..21
+    LetKeyword@12..15 "let"
+    Whitespace@15..16 " "
+    Ident@16..17 "b"
+    Whitespace@17..18 " "
+    Equals@18..19 "="
+    Whitespace@19..20 " "
+    VariableRef@20..21
+      Ident@20..21 "a""#]],
+        );
+    }
 }
@@ -0,0 +1,353 @@
+use super::*;
+
+/// Parses a type
+/// i32, (i32, String), () -> (), [String], User?
+pub(super) fn parse_type(parser: &mut Parser) -> Option<CompletedMarker> {
+    let mut cm = if parser.at(TokenKind::Ident) {
+        parse_path_type(parser)
+    } else if parser.at(TokenKind::LParen) {
+        parse_tuple_or_fn_type(parser)
+    } else if parser.at(TokenKind::LBracket) {
+        parse_array_type(parser)
+    } else {
+        parser.error();
+        return None;
+    };
+
+    while parser.at(TokenKind::Question) {
+        let marker = cm.precede(parser);
+        parser.bump();
+        cm = marker.complete(parser, SyntaxKind::OptionalType);
+    }
+
+    Some(cm)
+}
+
+/// Parses an optional type annotation
+/// : i32
+pub(super) fn parse_type_annotation(parser: &mut Parser) {
+    if parser.at(TokenKind::Colon) {
+        parser.bump();
+        parse_type(parser);
+    }
+}
+
+/// Parses an optional return type
+/// -> bool
+pub(super) fn parse_ret_type(parser: &mut Parser) -> Option<CompletedMarker> {
+    if !parser.at(TokenKind::Arrow) {
+        return None;
+    }
+
+    let marker = parser.start();
+    parser.bump();
+    parse_type(parser);
+    Some(marker.complete(parser, SyntaxKind::RetType))
+}
+
+fn parse_path_type(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::Ident));
+    let marker = parser.start();
+    parser.bump();
+    marker.complete(parser, SyntaxKind::PathType)
+}
+
+fn parse_array_type(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::LBracket));
+    let marker = parser.start();
+    parser.bump();
+    parser.push_recovery(&[TokenKind::RBracket]);
+    parse_type(parser);
+    parser.expect(TokenKind::RBracket);
+    parser.pop_recovery();
+    marker.complete(parser, SyntaxKind::ArrayType)
+}
+
+/// A parenthesized list of types is a tuple type,
+/// unless it is followed by an arrow which makes it a function type.
+fn parse_tuple_or_fn_type(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::LParen));
+    let marker = parser.start();
+    parse_type_list(parser);
+
+    if parse_ret_type(parser).is_some() {
+        marker.complete(parser, SyntaxKind::FnType)
+    } else {
+        marker.complete(parser, SyntaxKind::TupleType)
+    }
+}
+
+/// Parses a tuple type that can not be turned into a function type
+/// (String, i32)
+pub(super) fn parse_tuple_type(parser: &mut Parser) -> CompletedMarker {
+    assert!(parser.at(TokenKind::LParen));
+    let marker = parser.start();
+    parse_type_list(parser);
+    marker.complete(parser, SyntaxKind::TupleType)
+}
+
+fn parse_type_list(parser: &mut Parser) {
+    assert!(parser.at(TokenKind::LParen));
+    parser.bump();
+    parser.push_recovery(&[TokenKind::RParen]);
+
+    while !parser.at(TokenKind::RParen) && !parser.at_end() {
+        if parse_type(parser).is_none() {
+            break;
+        }
+
+        if parser.at(TokenKind::Comma) {
+            parser.bump();
+        } else {
+            break;
+        }
+    }
+
+    parser.expect(TokenKind::RParen);
+    parser.pop_recovery();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check;
+    use expect_test::expect;
+
+    #[test]
+    fn parse_fn_type_in_param() {
+        check(
+            "func a(onClick: () -> ()) {}",
+            expect![[r#"
+Root@0..28
+  FunctionDecl@0..28
+    FuncKeyword@0..4 "func"
+    Whitespace@4..5 " "
+    VariableRef@5..6
+      Ident@5..6 "a"
+    ParamList@6..26
+      LParen@6..7 "("
+      Param@7..24
+        Ident@7..14 "onClick"
+        Colon@14..15 ":"
+        Whitespace@15..16 " "
+        FnType@16..24
+          LParen@16..17 "("
+          RParen@17..18 ")"
+          Whitespace@18..19 " "
+          RetType@19..24
+            Arrow@19..21 "->"
+            Whitespace@21..22 " "
+            TupleType@22..24
+              LParen@22..23 "("
+              RParen@23..24 ")"
+      RParen@24..25 ")"
+      Whitespace@25..26 " "
+    BlockStmt@26..28
+      LBrace@26..27 "{"
+      RBrace@27..28 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_fn_type_with_params() {
+        check(
+            "|f: (i32, String) -> bool| f",
+            expect![[r#"
+Root@0..28
+  ClosureExpr@0..28
+    ParamList@0..27
+      Bar@0..1 "|"
+      Param@1..25
+        Ident@1..2 "f"
+        Colon@2..3 ":"
+        Whitespace@3..4 " "
+        FnType@4..25
+          LParen@4..5 "("
+          PathType@5..8
+            Ident@5..8 "i32"
+          Comma@8..9 ","
+          Whitespace@9..10 " "
+          PathType@10..16
+            Ident@10..16 "String"
+          RParen@16..17 ")"
+          Whitespace@17..18 " "
+          RetType@18..25
+            Arrow@18..20 "->"
+            Whitespace@20..21 " "
+            PathType@21..25
+              Ident@21..25 "bool"
+      Bar@25..26 "|"
+      Whitespace@26..27 " "
+    VariableRef@27..28
+      Ident@27..28 "f""#]],
+        );
+    }
+
+    #[test]
+    fn parse_tuple_type() {
+        check(
+            "|pair: (i32, i32)| pair",
+            expect![[r#"
+Root@0..23
+  ClosureExpr@0..23
+    ParamList@0..19
+      Bar@0..1 "|"
+      Param@1..17
+        Ident@1..5 "pair"
+        Colon@5..6 ":"
+        Whitespace@6..7 " "
+        TupleType@7..17
+          LParen@7..8 "("
+          PathType@8..11
+            Ident@8..11 "i32"
+          Comma@11..12 ","
+          Whitespace@12..13 " "
+          PathType@13..16
+            Ident@13..16 "i32"
+          RParen@16..17 ")"
+      Bar@17..18 "|"
+      Whitespace@18..19 " "
+    VariableRef@19..23
+      Ident@19..23 "pair""#]],
+        );
+    }
+
+    #[test]
+    fn parse_fn_type_without_return_type() {
+        check(
+            "|f: () ->| f",
+            expect![[r#"
+Root@0..12
+  ClosureExpr@0..12
+    ParamList@0..11
+      Bar@0..1 "|"
+      Param@1..9
+        Ident@1..2 "f"
+        Colon@2..3 ":"
+        Whitespace@3..4 " "
+        FnType@4..9
+          LParen@4..5 "("
+          RParen@5..6 ")"
+          Whitespace@6..7 " "
+          RetType@7..9
+            Arrow@7..9 "->"
+      Bar@9..10 "|"
+      Whitespace@10..11 " "
+    VariableRef@11..12
+      Ident@11..12 "f"
+error at 9..10: expected identifier, `(` or `[`, but found `|`"#]],
+        );
+    }
+
+    #[test]
+    fn parse_array_type() {
+        check(
+            "let names: [String] = names",
+            expect![[r#"
+Root@0..27
+  VariableDef@0..27
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..9 "names"
+    Colon@9..10 ":"
+    Whitespace@10..11 " "
+    ArrayType@11..20
+      LBracket@11..12 "["
+      PathType@12..18
+        Ident@12..18 "String"
+      RBracket@18..19 "]"
+      Whitespace@19..20 " "
+    Equals@20..21 "="
+    Whitespace@21..22 " "
+    VariableRef@22..27
+      Ident@22..27 "names""#]],
+        );
+    }
+
+    #[test]
+    fn parse_optional_type() {
+        check(
+            "let user: User? = user",
+            expect![[r#"
+Root@0..22
+  VariableDef@0..22
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..8 "user"
+    Colon@8..9 ":"
+    Whitespace@9..10 " "
+    OptionalType@10..16
+      PathType@10..14
+        Ident@10..14 "User"
+      Question@14..15 "?"
+      Whitespace@15..16 " "
+    Equals@16..17 "="
+    Whitespace@17..18 " "
+    VariableRef@18..22
+      Ident@18..22 "user""#]],
+        );
+    }
+
+    #[test]
+    fn parse_fn_type_returning_optional() {
+        check(
+            "let f: (i32) -> [i32]? = f",
+            expect![[r#"
+Root@0..26
+  VariableDef@0..26
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "f"
+    Colon@5..6 ":"
+    Whitespace@6..7 " "
+    FnType@7..23
+      LParen@7..8 "("
+      PathType@8..11
+        Ident@8..11 "i32"
+      RParen@11..12 ")"
+      Whitespace@12..13 " "
+      RetType@13..23
+        Arrow@13..15 "->"
+        Whitespace@15..16 " "
+        OptionalType@16..23
+          ArrayType@16..21
+            LBracket@16..17 "["
+            PathType@17..20
+              Ident@17..20 "i32"
+            RBracket@20..21 "]"
+          Question@21..22 "?"
+          Whitespace@22..23 " "
+    Equals@23..24 "="
+    Whitespace@24..25 " "
+    VariableRef@25..26
+      Ident@25..26 "f""#]],
+        );
+    }
+
+    #[test]
+    fn parse_unclosed_array_type() {
+        check(
+            "let a: [i32 = 1",
+            expect![[r#"
+Root@0..15
+  VariableDef@0..15
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Ident@4..5 "a"
+    Colon@5..6 ":"
+    Whitespace@6..7 " "
+    ArrayType@7..14
+      LBracket@7..8 "["
+      PathType@8..12
+        Ident@8..11 "i32"
+        Whitespace@11..12 " "
+      Error@12..14
+        Equals@12..13 "="
+        Whitespace@13..14 " "
+    Error@14..15
+      Integer@14..15 "1"
+error at 12..13: expected `?` or `]`, but found `=`
+error at 14..15: expected `?` or `=`, but found number
+error at 14..15: expected number, string, true, false, identifier, `-`, `!`, `(`, `|`, `||` or match"#]],
+        );
+    }
+}
@@ -1,16 +1,20 @@
 mod event;
 mod grammar;
 mod parser;
+mod reparse;
 mod sink;
 mod source;
 
-use crate::parser::ParseError;
 use crate::parser::Parser;
+use crate::parser::{ParseError, ParseErrorKind};
+use diagnostics::Diagnostic;
 use lexer::Lexer;
 use rowan::GreenNode;
 use sink::Sink;
 use syntax::SyntaxNode;
 
+pub use reparse::{reparse, TextEdit};
+
 pub fn parse(input: &str) -> Parse {
     let tokens: Vec<_> = Lexer::new(input).collect();
     let parser = Parser::new(&tokens);
@@ -43,8 +47,16 @@ impl Parse {
         SyntaxNode::new_root(self.green_node.clone())
     }
 
-    pub fn errors(&self) -> &[ParseError] {
-        &self.errors
+    /// Whether the input ended while something was still expected, like
+    /// the closing brace of a block, so more input could complete it
+    pub fn is_incomplete(&self) -> bool {
+        self.errors
+            .iter()
+            .any(|error| matches!(error.kind, ParseErrorKind::Expected { found: None, .. }))
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.errors.iter().map(ParseError::to_diagnostic).collect()
     }
 }
 
@@ -53,3 +65,24 @@ pub(crate) fn check(input: &str, expected_tree: expect_test::Expect) {
     let parse = parse(input);
     expected_tree.assert_eq(&parse.debug_tree());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unclosed_block_is_incomplete() {
+        assert!(parse("func f() {\n  let a = 1").is_incomplete());
+    }
+
+    #[test]
+    fn missing_operand_is_incomplete() {
+        assert!(parse("let a = 1 +").is_incomplete());
+    }
+
+    #[test]
+    fn unexpected_token_is_not_incomplete() {
+        assert!(!parse("let a = )").is_incomplete());
+        assert!(!parse("let a = 1").is_incomplete());
+    }
+}
@@ -6,16 +6,24 @@ use crate::grammar;
 use crate::source::Source;
 use lexer::{Token, TokenKind};
 use marker::Marker;
-pub(crate) use parse_error::ParseError;
+pub(crate) use parse_error::{ParseError, ParseErrorKind};
 use std::mem;
 use syntax::syntax_kind::SyntaxKind;
 
-const RECOVERY_SET: [TokenKind; 1] = [TokenKind::LetKeyword];
-
 pub struct Parser<'t, 'input> {
     source: Source<'t, 'input>,
     events: Vec<Event>,
     expected_kinds: Vec<TokenKind>,
+    /// The tokens that grammar rules further up know how to continue from.
+    /// An error never skips over one of these, so that a mistake in one
+    /// place doesn't swallow the rest of the enclosing block or list.
+    recovery_sets: Vec<&'static [TokenKind]>,
+    /// Whether `Name {` starts a struct expression. This is turned off
+    /// where the brace belongs to something else, like a `match` scrutinee.
+    struct_exprs_allowed: bool,
+    /// Whether a newline ends the current statement. Newlines are not
+    /// significant inside of parentheses and other delimited lists.
+    newlines_significant: bool,
 }
 
 impl<'t, 'input> Parser<'t, 'input> {
@@ -24,6 +32,9 @@ impl<'t, 'input> Parser<'t, 'input> {
             source: Source::new(tokens),
             events: Vec::new(),
             expected_kinds: Vec::new(),
+            recovery_sets: Vec::new(),
+            struct_exprs_allowed: true,
+            newlines_significant: true,
         }
     }
 
@@ -32,6 +43,13 @@ impl<'t, 'input> Parser<'t, 'input> {
         self.events
     }
 
+    /// Parses the tokens as a single block, or returns `None` if the block
+    /// ends before the tokens do
+    pub(crate) fn parse_block(mut self) -> Option<Vec<Event>> {
+        grammar::block(&mut self);
+        self.at_end().then_some(self.events)
+    }
+
     pub(crate) fn start(&mut self) -> Marker {
         let pos = self.events.len();
         self.events.push(Event::tombstone());
@@ -53,7 +71,7 @@ impl<'t, 'input> Parser<'t, 'input> {
         }
     }
 
-    fn peek(&mut self) -> Option<TokenKind> {
+    pub(crate) fn peek(&mut self) -> Option<TokenKind> {
         self.source.peek_kind()
     }
 
@@ -71,6 +89,18 @@ impl<'t, 'input> Parser<'t, 'input> {
     }
 
     pub(crate) fn error(&mut self) {
+        self.report_error();
+
+        if !self.at_recovery_set() && !self.at_end() {
+            let marker = self.start();
+            self.bump();
+            marker.complete(self, SyntaxKind::Error);
+        }
+    }
+
+    /// Reports that the current token is none of the expected ones, like
+    /// `error`, but leaves it for the caller to parse
+    pub(crate) fn report_error(&mut self) {
         let current_token = self.source.peek_token();
 
         let (found, range) = if let Some(Token { kind, range, .. }) = current_token {
@@ -79,26 +109,107 @@ impl<'t, 'input> Parser<'t, 'input> {
             (None, self.source.last_token_range().unwrap())
         };
 
-        self.events.push(Event::Error(ParseError {
-            expected: mem::take(&mut self.expected_kinds),
-            found,
-            range,
-        }));
+        // Lookaheads that overlap, like the ones for the end of a statement
+        // and the start of the next one, can expect a kind more than once
+        let mut expected = Vec::new();
+        for kind in mem::take(&mut self.expected_kinds) {
+            if !expected.contains(&kind) {
+                expected.push(kind);
+            }
+        }
+
+        if !self.at_lex_error() {
+            self.events.push(Event::Error(ParseError {
+                kind: ParseErrorKind::Expected { expected, found },
+                range,
+            }));
+        }
+    }
 
-        if !self.at_set(&RECOVERY_SET) && !self.at_end() {
-            let marker = self.start();
-            self.bump();
-            marker.complete(self, SyntaxKind::Error);
+    /// Reports an error at the current token without consuming it, so that
+    /// parsing can continue with a new statement from there.
+    pub(crate) fn error_before_statement(
+        &mut self,
+        kind: impl FnOnce(TokenKind) -> ParseErrorKind,
+    ) {
+        self.expected_kinds.clear();
+
+        if self.at_lex_error() {
+            return;
+        }
+
+        if let Some(Token {
+            kind: found, range, ..
+        }) = self.source.peek_token()
+        {
+            let error = ParseError {
+                kind: kind(*found),
+                range: *range,
+            };
+            self.events.push(Event::Error(error));
         }
     }
 
+    /// Whether the next token is one that the lexer could not make sense
+    /// of and already reported an error for, which explains the problem
+    /// better than an error from the parser would
+    fn at_lex_error(&mut self) -> bool {
+        matches!(
+            self.source.peek_token(),
+            Some(Token {
+                kind: TokenKind::Error,
+                error: Some(_),
+                ..
+            })
+        )
+    }
+
     pub(crate) fn at_set(&mut self, set: &[TokenKind]) -> bool {
+        self.expected_kinds.extend_from_slice(set);
         self.peek().map_or(false, |k| set.contains(&k))
     }
 
     pub(crate) fn at_end(&mut self) -> bool {
         self.peek().is_none()
     }
+
+    pub(crate) fn at_recovery_set(&mut self) -> bool {
+        match self.peek() {
+            Some(kind) => self.recovery_sets.iter().any(|set| set.contains(&kind)),
+            None => false,
+        }
+    }
+
+    /// Makes errors stop at any token in `set` until the matching `pop_recovery`
+    pub(crate) fn push_recovery(&mut self, set: &'static [TokenKind]) {
+        self.recovery_sets.push(set);
+    }
+
+    pub(crate) fn pop_recovery(&mut self) {
+        self.recovery_sets.pop();
+    }
+
+    /// Whether the next token starts a new line in a place where a newline
+    /// ends the current statement
+    pub(crate) fn at_line_break(&mut self) -> bool {
+        self.newlines_significant && self.source.peek_preceded_by_newline()
+    }
+
+    /// Sets whether newlines end statements and returns the previous value
+    /// so that it can be restored.
+    pub(crate) fn set_newlines_significant(&mut self, significant: bool) -> bool {
+        mem::replace(&mut self.newlines_significant, significant)
+    }
+
+    pub(crate) fn struct_exprs_allowed(&self) -> bool {
+        self.struct_exprs_allowed
+    }
+
+    /// Sets whether struct expressions are allowed and returns the
+    /// previous value so that it can be restored.
+    pub(crate) fn set_struct_exprs_allowed(&mut self, allowed: bool) -> bool {
+        mem::replace(&mut self.struct_exprs_allowed, allowed)
+    }
 }
 
 #[cfg(test)]
@@ -130,4 +241,164 @@ mod tests {
           Comment@0..8 "// hello""#]],
         )
     }
+
+    #[test]
+    fn parse_nested_block_comment() {
+        check(
+            "1 /* a /* b */ c */ + 2",
+            expect![[r#"
+        Root@0..23
+          InfixExpr@0..23
+            Literal@0..20
+              Integer@0..1 "1"
+              Whitespace@1..2 " "
+              Comment@2..19 "/* a /* b */ c */"
+              Whitespace@19..20 " "
+            Plus@20..21 "+"
+            Whitespace@21..22 " "
+            Literal@22..23
+              Integer@22..23 "2""#]],
+        )
+    }
+
+    #[test]
+    fn parse_unterminated_block_comment() {
+        check(
+            "let a = 1 /* b /* c */",
+            expect![[r#"
+        Root@0..22
+          VariableDef@0..22
+            LetKeyword@0..3 "let"
+            Whitespace@3..4 " "
+            Ident@4..5 "a"
+            Whitespace@5..6 " "
+            Equals@6..7 "="
+            Whitespace@7..8 " "
+            Literal@8..22
+              Integer@8..9 "1"
+              Whitespace@9..10 " "
+              Comment@10..22 "/* b /* c */"
+        error at 10..12: unterminated block comment"#]],
+        )
+    }
+
+    #[test]
+    fn parse_unterminated_string() {
+        check(
+            "let a = \"b\nc",
+            expect![[r#"
+        Root@0..12
+          VariableDef@0..12
+            LetKeyword@0..3 "let"
+            Whitespace@3..4 " "
+            Ident@4..5 "a"
+            Whitespace@5..6 " "
+            Equals@6..7 "="
+            Whitespace@7..8 " "
+            Literal@8..12
+              String@8..12 "\"b\nc"
+        error at 8..9: unterminated string"#]],
+        )
+    }
+
+    #[test]
+    fn parse_invalid_character() {
+        check(
+            "let a = 1 # 2",
+            expect![[r##"
+        Root@0..13
+          VariableDef@0..10
+            LetKeyword@0..3 "let"
+            Whitespace@3..4 " "
+            Ident@4..5 "a"
+            Whitespace@5..6 " "
+            Equals@6..7 "="
+            Whitespace@7..8 " "
+            Literal@8..10
+              Integer@8..9 "1"
+              Whitespace@9..10 " "
+          Error@10..12
+            Error@10..11 "#"
+            Whitespace@11..12 " "
+          Literal@12..13
+            Integer@12..13 "2"
+        error at 10..11: invalid character `#` (U+0023)"##]],
+        )
+    }
+
+    #[test]
+    fn parse_invalid_character_instead_of_expression() {
+        check(
+            "let a = @",
+            expect![[r#"
+        Root@0..9
+          VariableDef@0..9
+            LetKeyword@0..3 "let"
+            Whitespace@3..4 " "
+            Ident@4..5 "a"
+            Whitespace@5..6 " "
+            Equals@6..7 "="
+            Whitespace@7..8 " "
+            Error@8..9
+              Error@8..9 "@"
+        error at 8..9: invalid character `@` (U+0040)"#]],
+        )
+    }
+
+    #[test]
+    fn parse_invalid_number_suffix() {
+        check(
+            "let a = 10px",
+            expect![[r#"
+        Root@0..12
+          VariableDef@0..12
+            LetKeyword@0..3 "let"
+            Whitespace@3..4 " "
+            Ident@4..5 "a"
+            Whitespace@5..6 " "
+            Equals@6..7 "="
+            Whitespace@7..8 " "
+            Literal@8..12
+              Integer@8..12 "10px"
+        error at 10..12: invalid suffix `px` on a number"#]],
+        )
+    }
+
+    #[test]
+    fn parse_float() {
+        check(
+            "let a = 1.5",
+            expect![[r#"
+        Root@0..11
+          VariableDef@0..11
+            LetKeyword@0..3 "let"
+            Whitespace@3..4 " "
+            Ident@4..5 "a"
+            Whitespace@5..6 " "
+            Equals@6..7 "="
+            Whitespace@7..8 " "
+            Error@8..11
+              Error@8..11 "1.5"
+        error at 8..11: floating point numbers are not supported"#]],
+        )
+    }
+
+    #[test]
+    fn parse_invalid_escape() {
+        check(
+            r#"let a = "b\qc\n""#,
+            expect![[r#"
+        Root@0..16
+          VariableDef@0..16
+            LetKeyword@0..3 "let"
+            Whitespace@3..4 " "
+            Ident@4..5 "a"
+            Whitespace@5..6 " "
+            Equals@6..7 "="
+            Whitespace@7..8 " "
+            Literal@8..16
+              String@8..16 "\"b\\qc\\n\""
+        error at 10..12: invalid escape `\q` in a string"#]],
+        )
+    }
 }
@@ -1,42 +1,176 @@
+use diagnostics::{Code, Diagnostic, Fix, Label};
 use lexer::TokenKind;
 use std::fmt;
 use text_size::TextRange;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ParseError {
-    pub expected: Vec<TokenKind>,
-    pub found: Option<TokenKind>,
+    pub kind: ParseErrorKind,
     pub range: TextRange,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// None of the expected tokens were found
+    Expected {
+        expected: Vec<TokenKind>,
+        found: Option<TokenKind>,
+    },
+    /// Another statement starts on the same line as the previous one
+    MissingTerminator { found: TokenKind },
+    /// A line starts with a token that could either continue the previous
+    /// statement or start a new one, like the `-` in `let a = 1\n-2`
+    AmbiguousContinuation { found: TokenKind },
+    /// The input ends inside of a `/*` comment, the range is its `/*`
+    UnterminatedComment,
+    /// The input ends inside of a string, the range is its opening `"`
+    UnterminatedString,
+    /// A character that does not start any token
+    InvalidCharacter(char),
+    /// Letters right after the digits of a number, like the `px` in `10px`
+    InvalidNumberSuffix { suffix: String },
+    /// A `\` in a string followed by a character that can't be escaped
+    InvalidEscape(char),
+    /// A number with a fraction or an exponent, like `1.5`
+    UnsupportedFloat,
+}
+
+impl ParseError {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match &self.kind {
+            ParseErrorKind::Expected { found, .. } => {
+                let label = match found {
+                    Some(found) => format!("found {}", found),
+                    None => "the input ends here".to_string(),
+                };
+
+                Diagnostic::error(Code::UNEXPECTED_TOKEN, self.kind.to_string())
+                    .with_label(Label::primary(self.range, label))
+            }
+            ParseErrorKind::MissingTerminator { .. } => {
+                Diagnostic::error(Code::MISSING_TERMINATOR, self.kind.to_string())
+                    .with_label(Label::primary(
+                        self.range,
+                        "expected `;` or a newline before this",
+                    ))
+                    .with_fix(Fix::insert(
+                        self.range.start(),
+                        "; ",
+                        "separate the statements with `;`",
+                    ))
+            }
+            ParseErrorKind::AmbiguousContinuation { found } => Diagnostic::error(
+                Code::AMBIGUOUS_CONTINUATION,
+                format!("{} at the start of a line is ambiguous", found),
+            )
+            .with_label(Label::primary(
+                self.range,
+                "this could continue the previous statement or start a new one",
+            ))
+            .with_help(format!(
+                "end the previous statement with `;` or move {} to the end of the previous line",
+                found,
+            )),
+            ParseErrorKind::UnterminatedComment => {
+                Diagnostic::error(Code::UNTERMINATED_COMMENT, self.kind.to_string())
+                    .with_label(Label::primary(self.range, "the comment starts here"))
+                    .with_help("close it with `*/`, comments inside of it need their own `*/`")
+            }
+            ParseErrorKind::UnterminatedString => {
+                Diagnostic::error(Code::UNTERMINATED_STRING, self.kind.to_string())
+                    .with_label(Label::primary(self.range, "the string starts here"))
+                    .with_help("close it with `\"`, write `\\\"` for a quote inside of it")
+            }
+            ParseErrorKind::InvalidCharacter(_) => {
+                Diagnostic::error(Code::INVALID_CHARACTER, self.kind.to_string()).with_label(
+                    Label::primary(self.range, "this character is not part of any token"),
+                )
+            }
+            ParseErrorKind::InvalidNumberSuffix { .. } => {
+                Diagnostic::error(Code::INVALID_NUMBER_SUFFIX, self.kind.to_string())
+                    .with_label(Label::primary(self.range, "numbers can't have a suffix"))
+            }
+            ParseErrorKind::InvalidEscape(_) => {
+                Diagnostic::error(Code::INVALID_ESCAPE, self.kind.to_string())
+                    .with_label(Label::primary(self.range, "unknown escape"))
+                    .with_help(
+                        "the escapes are `\\\\`, `\\\"`, `\\n`, `\\t`, `\\r` and `\\0`, \
+                         write `\\\\` for a backslash",
+                    )
+            }
+            ParseErrorKind::UnsupportedFloat => {
+                Diagnostic::error(Code::UNSUPPORTED_FLOAT, self.kind.to_string())
+                    .with_label(Label::primary(self.range, "only integers are supported"))
+            }
+        }
+    }
+}
+
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "error at {}..{}: expected ",
+            "error at {}..{}: {}",
             u32::from(self.range.start()),
             u32::from(self.range.end()),
-        )?;
-
-        let num_expected = self.expected.len();
-        let is_first = |idx| idx == 0;
-        let is_last = |idx| idx == num_expected - 1;
-
-        for (idx, expected_kind) in self.expected.iter().enumerate() {
-            if is_first(idx) {
-                write!(f, "{}", expected_kind)?;
-            } else if is_last(idx) {
-                write!(f, " or {}", expected_kind)?;
-            } else {
-                write!(f, ", {}", expected_kind)?;
-            }
-        }
+            self.kind,
+        )
+    }
+}
 
-        if let Some(found) = self.found {
-            write!(f, ", but found {}", found)?;
-        }
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Expected { expected, found } => {
+                write!(f, "expected ")?;
+
+                let num_expected = expected.len();
+                let is_first = |idx| idx == 0;
+                let is_last = |idx| idx == num_expected - 1;
 
-        Ok(())
+                for (idx, expected_kind) in expected.iter().enumerate() {
+                    if is_first(idx) {
+                        write!(f, "{}", expected_kind)?;
+                    } else if is_last(idx) {
+                        write!(f, " or {}", expected_kind)?;
+                    } else {
+                        write!(f, ", {}", expected_kind)?;
+                    }
+                }
+
+                if let Some(found) = found {
+                    write!(f, ", but found {}", found)?;
+                }
+
+                Ok(())
+            }
+            ParseErrorKind::MissingTerminator { found } => {
+                write!(f, "expected `;` or a newline, but found {}", found)
+            }
+            ParseErrorKind::AmbiguousContinuation { found } => write!(
+                f,
+                "{} at the start of a line is ambiguous, end the previous statement with `;` \
+                 or move {} to the end of the previous line",
+                found, found,
+            ),
+            ParseErrorKind::UnterminatedComment => write!(f, "unterminated block comment"),
+            ParseErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ParseErrorKind::InvalidCharacter(c) => write!(
+                f,
+                "invalid character `{}` (U+{:04X})",
+                c.escape_debug(),
+                u32::from(*c),
+            ),
+            ParseErrorKind::InvalidNumberSuffix { suffix } => {
+                write!(f, "invalid suffix `{}` on a number", suffix)
+            }
+            ParseErrorKind::InvalidEscape(c) => {
+                write!(f, "invalid escape `\\{}` in a string", c.escape_debug())
+            }
+            ParseErrorKind::UnsupportedFloat => {
+                write!(f, "floating point numbers are not supported")
+            }
+        }
     }
 }
 
@@ -52,8 +186,7 @@ mod tests {
         output: &str,
     ) {
         let error = ParseError {
-            expected,
-            found,
+            kind: ParseErrorKind::Expected { expected, found },
             range: {
                 let start = range.start.into();
                 let end = range.end.into();
@@ -108,4 +241,176 @@ mod tests {
             "error at 100..105: expected number, identifier, `-` or `(`, but found let",
         );
     }
+
+    #[test]
+    fn expected_diagnostic_labels_what_was_found() {
+        let label = |found| {
+            let error = ParseError {
+                kind: ParseErrorKind::Expected {
+                    expected: vec![TokenKind::Integer],
+                    found,
+                },
+                range: TextRange::new(8.into(), 9.into()),
+            };
+            error.to_diagnostic().labels[0].message.clone()
+        };
+
+        assert_eq!(label(Some(TokenKind::Ident)), "found identifier");
+        assert_eq!(label(Some(TokenKind::Error)), "found an unrecognized token");
+        assert_eq!(label(None), "the input ends here");
+    }
+
+    #[test]
+    fn missing_terminator() {
+        let error = ParseError {
+            kind: ParseErrorKind::MissingTerminator {
+                found: TokenKind::LetKeyword,
+            },
+            range: TextRange::new(10.into(), 13.into()),
+        };
+
+        assert_eq!(
+            format!("{}", error),
+            "error at 10..13: expected `;` or a newline, but found let",
+        );
+    }
+
+    #[test]
+    fn ambiguous_continuation() {
+        let error = ParseError {
+            kind: ParseErrorKind::AmbiguousContinuation {
+                found: TokenKind::Minus,
+            },
+            range: TextRange::new(10.into(), 11.into()),
+        };
+
+        assert_eq!(
+            format!("{}", error),
+            "error at 10..11: `-` at the start of a line is ambiguous, end the previous \
+             statement with `;` or move `-` to the end of the previous line",
+        );
+    }
+
+    #[test]
+    fn missing_terminator_diagnostic_inserts_semicolon() {
+        let error = ParseError {
+            kind: ParseErrorKind::MissingTerminator {
+                found: TokenKind::LetKeyword,
+            },
+            range: TextRange::new(10.into(), 13.into()),
+        };
+
+        let fixes = error.to_diagnostic().fixes;
+
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].range, TextRange::empty(10.into()));
+        assert_eq!(fixes[0].replacement, "; ");
+    }
+
+    #[test]
+    fn ambiguous_continuation_diagnostic_has_help() {
+        let error = ParseError {
+            kind: ParseErrorKind::AmbiguousContinuation {
+                found: TokenKind::Minus,
+            },
+            range: TextRange::new(10.into(), 11.into()),
+        };
+
+        let diagnostic = error.to_diagnostic();
+
+        assert_eq!(diagnostic.code, Code::AMBIGUOUS_CONTINUATION);
+        assert_eq!(
+            diagnostic.message,
+            "`-` at the start of a line is ambiguous"
+        );
+        assert_eq!(diagnostic.range(), Some(error.range));
+        assert_eq!(
+            diagnostic.help.as_deref(),
+            Some("end the previous statement with `;` or move `-` to the end of the previous line"),
+        );
+    }
+
+    #[test]
+    fn unterminated_comment_diagnostic_points_at_its_start() {
+        let error = ParseError {
+            kind: ParseErrorKind::UnterminatedComment,
+            range: TextRange::new(4.into(), 6.into()),
+        };
+
+        let diagnostic = error.to_diagnostic();
+
+        assert_eq!(diagnostic.code, Code::UNTERMINATED_COMMENT);
+        assert_eq!(diagnostic.message, "unterminated block comment");
+        assert_eq!(diagnostic.range(), Some(error.range));
+    }
+
+    fn lex_error(kind: ParseErrorKind, range: StdRange<u32>) -> ParseError {
+        ParseError {
+            kind,
+            range: TextRange::new(range.start.into(), range.end.into()),
+        }
+    }
+
+    #[test]
+    fn unterminated_string_diagnostic() {
+        let error = lex_error(ParseErrorKind::UnterminatedString, 8..9);
+        let diagnostic = error.to_diagnostic();
+
+        assert_eq!(format!("{}", error), "error at 8..9: unterminated string");
+        assert_eq!(diagnostic.code, Code::UNTERMINATED_STRING);
+        assert_eq!(diagnostic.range(), Some(error.range));
+    }
+
+    #[test]
+    fn invalid_character_diagnostic_shows_code_point() {
+        let error = lex_error(ParseErrorKind::InvalidCharacter('#'), 2..3);
+        let diagnostic = error.to_diagnostic();
+
+        assert_eq!(diagnostic.code, Code::INVALID_CHARACTER);
+        assert_eq!(diagnostic.message, "invalid character `#` (U+0023)");
+        assert_eq!(
+            lex_error(ParseErrorKind::InvalidCharacter('\u{7}'), 0..1)
+                .kind
+                .to_string(),
+            "invalid character `\\u{7}` (U+0007)",
+        );
+    }
+
+    #[test]
+    fn invalid_number_suffix_diagnostic() {
+        let error = lex_error(
+            ParseErrorKind::InvalidNumberSuffix {
+                suffix: "px".to_string(),
+            },
+            10..12,
+        );
+        let diagnostic = error.to_diagnostic();
+
+        assert_eq!(diagnostic.code, Code::INVALID_NUMBER_SUFFIX);
+        assert_eq!(diagnostic.message, "invalid suffix `px` on a number");
+        assert_eq!(diagnostic.range(), Some(error.range));
+    }
+
+    #[test]
+    fn invalid_escape_diagnostic() {
+        let error = lex_error(ParseErrorKind::InvalidEscape('q'), 3..5);
+        let diagnostic = error.to_diagnostic();
+
+        assert_eq!(diagnostic.code, Code::INVALID_ESCAPE);
+        assert_eq!(diagnostic.message, "invalid escape `\\q` in a string");
+        assert_eq!(diagnostic.range(), Some(error.range));
+    }
+
+    #[test]
+    fn unsupported_float_diagnostic() {
+        let error = lex_error(ParseErrorKind::UnsupportedFloat, 8..11);
+        let diagnostic = error.to_diagnostic();
+
+        assert_eq!(diagnostic.code, Code::UNSUPPORTED_FLOAT);
+        assert_eq!(
+            diagnostic.message,
+            "floating point numbers are not supported"
+        );
+        assert_eq!(diagnostic.range(), Some(error.range));
+    }
 }
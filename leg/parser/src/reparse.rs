@@ -0,0 +1,515 @@
+use crate::parser::{ParseError, Parser};
+use crate::sink::Sink;
+use crate::{parse, Parse};
+use lexer::{Lexer, Token, TokenKind};
+use rowan::{GreenToken, Language, NodeOrToken};
+use std::ops::Range as StdRange;
+use syntax::{syntax_kind::SyntaxKind, SyntaxNode, SyntaxToken, WLanguage};
+use text_size::{TextRange, TextSize};
+
+/// Replaces the text in `delete` with `insert`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub delete: TextRange,
+    pub insert: String,
+}
+
+impl TextEdit {
+    pub fn apply(&self, text: &mut String) {
+        text.replace_range(StdRange::<usize>::from(self.delete), &self.insert);
+    }
+
+    /// How much longer the text gets
+    fn delta(&self) -> i64 {
+        self.insert.len() as i64 - i64::from(u32::from(self.delete.len()))
+    }
+}
+
+/// Parses the text of `old` after `edit`, reusing as much of the old tree
+/// as possible. An edit inside of a single token only relexes that token,
+/// an edit inside of a block only reparses the block. Anything else is
+/// parsed from scratch. The result is always the same as that of `parse`.
+pub fn reparse(old: &Parse, edit: TextEdit) -> Parse {
+    if let Some(parse) = reparse_token(old, &edit).or_else(|| reparse_block(old, &edit)) {
+        return parse;
+    }
+
+    let mut text = old.syntax().to_string();
+    edit.apply(&mut text);
+    parse(&text)
+}
+
+/// Relexes the token the edit is in. An insertion between two tokens could
+/// go into either of them.
+fn reparse_token(old: &Parse, edit: &TextEdit) -> Option<Parse> {
+    let root = old.syntax();
+    let tokens: Vec<_> = if edit.delete.is_empty() {
+        root.token_at_offset(edit.delete.start()).collect()
+    } else {
+        root.covering_element(edit.delete)
+            .into_token()
+            .into_iter()
+            .collect()
+    };
+
+    tokens
+        .into_iter()
+        .find_map(|token| relex_token(old, &token, edit))
+}
+
+/// This only works if the token stays a single token of the same kind,
+/// since the parser only looks at kinds, and if whitespace keeps separating
+/// the same lines, since newlines end statements.
+fn relex_token(old: &Parse, token: &SyntaxToken, edit: &TextEdit) -> Option<Parse> {
+    if !matches!(
+        token.kind(),
+        SyntaxKind::Ident
+            | SyntaxKind::Integer
+            | SyntaxKind::String
+            | SyntaxKind::Whitespace
+            | SyntaxKind::Comment
+            | SyntaxKind::DocComment
+    ) {
+        return None;
+    }
+
+    let mut text = token.text().to_string();
+    TextEdit {
+        delete: edit.delete - token.text_range().start(),
+        insert: edit.insert.clone(),
+    }
+    .apply(&mut text);
+
+    if token.kind() == SyntaxKind::Whitespace && token.text().contains('\n') != text.contains('\n')
+    {
+        return None;
+    }
+
+    // Newlines inside of a block comment separate lines like whitespace does,
+    // and the errors of the token could change
+    if token.text().starts_with("/*") || text.starts_with("/*") {
+        return None;
+    }
+    if has_lex_error(token.text()) || has_lex_error(&text) {
+        return None;
+    }
+
+    // The new text could also merge with the tokens around it
+    if !lexes_like(token, &text) {
+        return None;
+    }
+
+    let green = token.replace_with(GreenToken::new(WLanguage::kind_to_raw(token.kind()), &text));
+
+    Some(Parse {
+        green_node: green,
+        errors: old
+            .errors
+            .iter()
+            .map(|error| shift_error(error, token.text_range(), edit.delta()))
+            .collect(),
+    })
+}
+
+fn has_lex_error(text: &str) -> bool {
+    Lexer::new(text).any(|token| token.error.is_some())
+}
+
+/// Whether `text` lexes as a single token of the same kind as `token`, with
+/// the tokens around it unchanged
+fn lexes_like(token: &SyntaxToken, text: &str) -> bool {
+    let prev = token.prev_token();
+    let next = token.next_token();
+
+    let mut expected = Vec::new();
+    let mut context = String::new();
+    for (kind, text) in prev
+        .iter()
+        .map(|prev| (prev.kind(), prev.text()))
+        .chain([(token.kind(), text)])
+        .chain(next.iter().map(|next| (next.kind(), next.text())))
+    {
+        expected.push((kind, text.len()));
+        context.push_str(text);
+    }
+
+    let lexed: Vec<_> = Lexer::new(&context)
+        .map(|token| (SyntaxKind::from(token.kind), token.text.len()))
+        .collect();
+
+    lexed == expected
+}
+
+/// Reparses the innermost block the edit is inside of. The edit has to be
+/// between the braces and the braces have to stay balanced, so that the
+/// block still ends in the same place. The trivia after the block, which
+/// decides how the next statement is separated from it, has to stay the
+/// same too, which it does not when the edit makes another `}` close the
+/// block, like by commenting out the old one.
+fn reparse_block(old: &Parse, edit: &TextEdit) -> Option<Parse> {
+    let root = old.syntax();
+    let parent = match root.covering_element(edit.delete) {
+        NodeOrToken::Node(node) => node,
+        NodeOrToken::Token(token) => token.parent()?,
+    };
+
+    parent
+        .ancestors()
+        .filter(|node| node.kind() == SyntaxKind::BlockStmt)
+        .find_map(|block| reparse_block_node(old, &block, edit))
+}
+
+fn reparse_block_node(old: &Parse, block: &SyntaxNode, edit: &TextEdit) -> Option<Parse> {
+    let l_brace = block.first_token()?;
+    let r_brace = block
+        .children_with_tokens()
+        .filter_map(NodeOrToken::into_token)
+        .filter(|token| token.kind() == SyntaxKind::RBrace)
+        .last()?;
+    if l_brace.kind() != SyntaxKind::LBrace {
+        return None;
+    }
+
+    let inside = TextRange::new(l_brace.text_range().end(), r_brace.text_range().start());
+    if !inside.contains_range(edit.delete) {
+        return None;
+    }
+
+    let start = block.text_range().start();
+    let old_text = block.text().to_string();
+    let mut text = old_text.clone();
+    TextEdit {
+        delete: edit.delete - start,
+        insert: edit.insert.clone(),
+    }
+    .apply(&mut text);
+
+    let tokens: Vec<_> = Lexer::new(&text).collect();
+    if !is_balanced_block(tokens.iter().map(|token| token.kind)) {
+        return None;
+    }
+    let closing = tokens
+        .iter()
+        .rposition(|token| token.kind == TokenKind::RBrace)?;
+    let trailing_trivia: String = tokens[closing + 1..]
+        .iter()
+        .map(|token| token.text)
+        .collect();
+    if trailing_trivia != old_text[usize::from(r_brace.text_range().end() - start)..] {
+        return None;
+    }
+    let block_parse = parse_block(&tokens)?;
+
+    // Errors are kept in the order they were found, so the errors found
+    // while parsing the old block sit between the errors found before it,
+    // which can point at its `{`, and the errors found after it, which can
+    // point at its `}`
+    let old_tokens: Vec<_> = Lexer::new(&old_text).collect();
+    let old_block_errors = parse_block(&old_tokens)?.errors.len();
+    let before = old
+        .errors
+        .iter()
+        .take_while(|error| error.range.start() <= start)
+        .count();
+    let after = old.errors.get(before + old_block_errors..)?;
+
+    let mut errors = old.errors[..before].to_vec();
+    errors.extend(block_parse.errors.into_iter().map(|error| ParseError {
+        range: error.range + start,
+        ..error
+    }));
+    errors.extend(
+        after
+            .iter()
+            .map(|error| shift_error(error, inside, edit.delta())),
+    );
+
+    Some(Parse {
+        green_node: block.replace_with(block_parse.green_node),
+        errors,
+    })
+}
+
+fn parse_block(tokens: &[Token]) -> Option<Parse> {
+    let events = Parser::new(tokens).parse_block()?;
+    Some(Sink::new(tokens, events).finish())
+}
+
+/// Whether the tokens are a `{`, and the `}` that closes it at the end,
+/// ignoring trivia
+fn is_balanced_block(kinds: impl Iterator<Item = TokenKind>) -> bool {
+    let mut kinds = kinds.filter(|kind| !kind.is_trivia()).peekable();
+    if kinds.peek() != Some(&TokenKind::LBrace) {
+        return false;
+    }
+
+    let mut depth = 0;
+    while let Some(kind) = kinds.next() {
+        match kind {
+            TokenKind::LBrace => depth += 1,
+            TokenKind::RBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    return kinds.next().is_none();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Moves an error that was found in the old text to where it is after
+/// `changed` grew by `delta`
+fn shift_error(error: &ParseError, changed: TextRange, delta: i64) -> ParseError {
+    let shift = |offset: TextSize| TextSize::from((i64::from(u32::from(offset)) + delta) as u32);
+
+    let range = if error.range.start() >= changed.end() {
+        TextRange::new(shift(error.range.start()), shift(error.range.end()))
+    } else if error.range.contains_range(changed) {
+        TextRange::new(error.range.start(), shift(error.range.end()))
+    } else {
+        error.range
+    };
+
+    ParseError {
+        range,
+        ..error.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Applies `insert` at the `$0` in `input`, or in place of the text
+    /// between `$0` and `$1`
+    fn edit(input: &str, insert: &str) -> (String, TextEdit) {
+        let start = input.find("$0").unwrap();
+        let input = input.replace("$0", "");
+        let end = input.find("$1").unwrap_or(start);
+        let input = input.replace("$1", "");
+
+        let edit = TextEdit {
+            delete: TextRange::new(
+                TextSize::try_from(start).unwrap(),
+                TextSize::try_from(end).unwrap(),
+            ),
+            insert: insert.to_string(),
+        };
+
+        (input, edit)
+    }
+
+    fn assert_same_as_parse(old: &str, edit: &TextEdit, reparsed: &Parse) {
+        let mut text = old.to_string();
+        edit.apply(&mut text);
+
+        assert_eq!(reparsed.debug_tree(), parse(&text).debug_tree());
+    }
+
+    fn check_token(input: &str, insert: &str, reused: bool) {
+        let (input, edit) = edit(input, insert);
+        let old = parse(&input);
+
+        let reparsed = reparse_token(&old, &edit);
+        assert_eq!(reparsed.is_some(), reused);
+        assert_same_as_parse(&input, &edit, &reparse(&old, edit.clone()));
+
+        if let Some(reparsed) = reparsed {
+            assert_same_as_parse(&input, &edit, &reparsed);
+        }
+    }
+
+    fn check_block(input: &str, insert: &str, reused: bool) {
+        let (input, edit) = edit(input, insert);
+        let old = parse(&input);
+
+        let reparsed = reparse_block(&old, &edit);
+        assert_eq!(reparsed.is_some(), reused);
+        assert_same_as_parse(&input, &edit, &reparse(&old, edit.clone()));
+
+        if let Some(reparsed) = reparsed {
+            assert_same_as_parse(&input, &edit, &reparsed);
+        }
+    }
+
+    #[test]
+    fn relex_identifier() {
+        check_token("let count$0 = 1", "er", true);
+        check_token("let a = $0foo$1 + 1", "bar", true);
+    }
+
+    #[test]
+    fn relex_literals_and_comments() {
+        check_token("let a = 12$0", "3", true);
+        check_token("let s = \"hi$0\"", " there", true);
+        check_token("let a = 1 // one$0", " or two", true);
+    }
+
+    #[test]
+    fn relex_whitespace_on_the_same_line() {
+        check_token("let a =$0 1", "   ", true);
+        check_token("let a = 1\n$0\nlet b = 2", "\n", true);
+    }
+
+    #[test]
+    fn do_not_relex_changed_kinds() {
+        // `le` becomes the `let` keyword
+        check_token("le$0 a = 1", "t", false);
+        check_token("let a = 1$0", "a", false);
+        check_token("let s = \"hi$0\"", "\"", false);
+    }
+
+    #[test]
+    fn do_not_relex_newlines_away() {
+        check_token("let a = 1$0\n$1let b = 2", " ", false);
+        check_token("let a = 1 $0 $1let b = 2", "\n", false);
+    }
+
+    #[test]
+    fn do_not_relex_into_neighbours() {
+        // `-` and `>` become `->`
+        check_token("a -$0 $1> b", "", false);
+    }
+
+    #[test]
+    fn reparse_edited_block() {
+        check_block(
+            "func f() {\n  let a = 1\n  $0\n}\nlet b = 2",
+            "log(a)",
+            true,
+        );
+        check_block("func f() {\n  $0a$1 + \n}", "func g() {}", true);
+    }
+
+    #[test]
+    fn reparse_innermost_block() {
+        let (input, edit) = edit("func f() {\n  func g() {\n    $0\n  }\n}", "1 + 2");
+        let old = parse(&input);
+        let reparsed = reparse_block(&old, &edit).unwrap();
+
+        assert_same_as_parse(&input, &edit, &reparsed);
+    }
+
+    #[test]
+    fn reparse_block_with_errors_around_it() {
+        check_block("let a =\nfunc f() {\n  $0\n}\nlet b = )", "let c = (", true);
+        check_block(
+            "let a =\nfunc f() {\n  let c = ($0\n}\nlet b = )",
+            ")",
+            true,
+        );
+    }
+
+    #[test]
+    fn reparse_block_with_errors_at_its_brace() {
+        check_block("func g() {\nfunc f() {\n  $0\n}", "let c = 1", true);
+        check_block("func g() {\nfunc f() {\n  let c = $0\n}", "1", true);
+    }
+
+    #[test]
+    fn do_not_reparse_unbalanced_block() {
+        check_block("func f() {\n  $0\n}\nlet b = 2", "}", false);
+        check_block("func f() {\n  $0\n}\nlet b = 2", "match a {", false);
+    }
+
+    #[test]
+    fn do_not_reparse_when_another_brace_closes_the_block() {
+        check_block("func g() {$0\n$1*/}\n}\n", "\n}\n/// d", false);
+        check_block("func g() {\n  $0}\nlet b = 2", "}\n// ", false);
+    }
+
+    #[test]
+    fn do_not_reparse_block_boundaries() {
+        check_block("func f() $0{}\nlet b = 2", " ", false);
+        check_block("func f() {}$0\n-1", ";", false);
+    }
+
+    /// Snippets that edits insert and source files are made of
+    const SNIPPETS: &[&str] = &[
+        "a",
+        "b1",
+        "1",
+        "23",
+        " ",
+        "\n",
+        ";",
+        "+",
+        "-",
+        ">",
+        "=",
+        "(",
+        ")",
+        "{",
+        "}",
+        "|",
+        ",",
+        "::",
+        "\"s\"",
+        "// c",
+        "/// d",
+        "/* e */",
+        "/*",
+        "*/",
+        "\"\\q\"",
+        "\"",
+        "@",
+        "2x",
+        "let",
+        "let x = ",
+        "state",
+        "func",
+        "func f(a) {\n",
+        "\n}\n",
+        "match a {",
+        "Some::V(x) => x",
+        "S { a: 1 }",
+        "le",
+        "t",
+    ];
+
+    fn snippets(max: usize) -> impl Strategy<Value = String> {
+        prop::collection::vec(prop::sample::select(SNIPPETS), 0..max)
+            .prop_map(|snippets| snippets.concat())
+    }
+
+    /// Source with at least one function, so that edits often land in a
+    /// block
+    fn text() -> impl Strategy<Value = String> {
+        (snippets(8), snippets(12), snippets(8)).prop_map(|(before, body, after)| {
+            format!("{}func g() {{\n{}\n}}\n{}", before, body, after)
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(512))]
+
+        #[test]
+        fn same_as_full_parse(
+            text in text(),
+            start in any::<prop::sample::Index>(),
+            len in 0usize..4,
+            insert in prop::collection::vec(prop::sample::select(SNIPPETS), 0..3),
+        ) {
+            // The snippets are ASCII, so every offset is a char boundary
+            let start = start.index(text.len() + 1);
+            let end = (start + len).min(text.len());
+            let edit = TextEdit {
+                delete: TextRange::new(
+                    TextSize::try_from(start).unwrap(),
+                    TextSize::try_from(end).unwrap(),
+                ),
+                insert: insert.concat(),
+            };
+
+            let mut new_text = text.clone();
+            edit.apply(&mut new_text);
+
+            let reparsed = reparse(&parse(&text), edit);
+            prop_assert_eq!(reparsed.debug_tree(), parse(&new_text).debug_tree());
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use super::event::Event;
-use crate::{parser::ParseError, Parse};
-use lexer::Token;
+use crate::parser::{ParseError, ParseErrorKind};
+use crate::Parse;
+use lexer::{LexErrorKind, Token, TokenKind};
 use rowan::{GreenNodeBuilder, Language};
 use std::mem;
 use syntax::{syntax_kind::SyntaxKind, WLanguage};
@@ -27,6 +28,10 @@ impl<'t, 'input> Sink<'t, 'input> {
     /// Generate the syntax tree with the control of events.
     pub(super) fn finish(mut self) -> Parse {
         let mut forward_parents = Vec::new();
+        let last_token = self
+            .events
+            .iter()
+            .rposition(|event| matches!(event, Event::AddToken));
 
         for idx in 0..self.events.len() {
             match mem::replace(&mut self.events[idx], Event::tombstone()) {
@@ -68,7 +73,14 @@ impl<'t, 'input> Sink<'t, 'input> {
                 Event::Error(error) => self.errors.push(error),
             }
 
-            self.eat_trivia();
+            let hold_docs = match self.events.get(idx + 1) {
+                Some(Event::Start { kind, .. }) => {
+                    *kind == SyntaxKind::Tombstone || is_documented(*kind)
+                }
+                Some(Event::AddToken) | None => false,
+                Some(Event::Finish | Event::Error(_)) => true,
+            };
+            self.eat_trivia(hold_docs && last_token.is_some_and(|last| idx < last));
         }
 
         Parse {
@@ -77,9 +89,13 @@ impl<'t, 'input> Sink<'t, 'input> {
         }
     }
 
-    fn eat_trivia(&mut self) {
+    /// Trivia goes into the node that was started or finished last. With
+    /// `hold_docs`, doc comments and the trivia after them wait for the next
+    /// event, so that they end up at the start of the declaration they
+    /// document.
+    fn eat_trivia(&mut self, hold_docs: bool) {
         while let Some(token) = self.tokens.get(self.cursor) {
-            if !token.kind.is_trivia() {
+            if !token.kind.is_trivia() || (token.kind == TokenKind::DocComment && hold_docs) {
                 break;
             }
 
@@ -88,7 +104,30 @@ impl<'t, 'input> Sink<'t, 'input> {
     }
 
     fn token(&mut self) {
-        let Token { kind, text, .. } = self.tokens[self.cursor];
+        let Token {
+            kind,
+            text,
+            range,
+            error,
+        } = self.tokens[self.cursor];
+
+        if let Some(error) = error {
+            let kind = match error.kind {
+                LexErrorKind::UnterminatedString => ParseErrorKind::UnterminatedString,
+                LexErrorKind::UnterminatedComment => ParseErrorKind::UnterminatedComment,
+                LexErrorKind::InvalidCharacter(c) => ParseErrorKind::InvalidCharacter(c),
+                LexErrorKind::InvalidNumberSuffix => ParseErrorKind::InvalidNumberSuffix {
+                    suffix: text[usize::from(error.range.start() - range.start())..].to_string(),
+                },
+                LexErrorKind::InvalidEscape(c) => ParseErrorKind::InvalidEscape(c),
+                LexErrorKind::UnsupportedFloat => ParseErrorKind::UnsupportedFloat,
+            };
+
+            self.errors.push(ParseError {
+                kind,
+                range: error.range,
+            });
+        }
 
         self.builder
             .token(WLanguage::kind_to_raw(kind.into()), text);
@@ -96,3 +135,17 @@ impl<'t, 'input> Sink<'t, 'input> {
         self.cursor += 1;
     }
 }
+
+/// The nodes doc comments are attached to
+fn is_documented(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::VariableDef
+            | SyntaxKind::StateDef
+            | SyntaxKind::FunctionDecl
+            | SyntaxKind::StructDecl
+            | SyntaxKind::FieldDecl
+            | SyntaxKind::EnumDecl
+            | SyntaxKind::VariantDecl
+    )
+}
@@ -40,6 +40,17 @@ impl<'t, 'input> Source<'t, 'input> {
         self.peek_token_raw()
     }
 
+    /// Whether the next token is on a different line than the one before it
+    pub(super) fn peek_preceded_by_newline(&mut self) -> bool {
+        self.eat_trivia();
+
+        self.tokens[..self.cursor]
+            .iter()
+            .rev()
+            .take_while(|token| token.kind.is_trivia())
+            .any(|token| token.text.contains('\n'))
+    }
+
     fn peek_kind_raw(&self) -> Option<TokenKind> {
         self.peek_token_raw().map(|Token { kind, .. }| *kind)
     }
@@ -21,6 +21,21 @@ pub type SyntaxToken = rowan::SyntaxToken<WLanguage>;
 pub type SyntaxElement = rowan::SyntaxElement<WLanguage>;
 pub type SyntaxNodeChildren = rowan::SyntaxNodeChildren<WLanguage>;
 pub type SyntaxElementChildren = rowan::SyntaxElementChildren<WLanguage>;
+pub type SyntaxNodePtr = rowan::ast::SyntaxNodePtr<WLanguage>;
+
+/// The range of `node` without its trailing whitespace and comments, which
+/// the parser attaches to the node they follow
+pub fn trimmed_range(node: &SyntaxNode) -> rowan::TextRange {
+    let start = node.text_range().start();
+    let end = node
+        .descendants_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+        .filter(|token| !token.kind().is_trivia())
+        .last()
+        .map_or(start, |token| token.text_range().end());
+
+    rowan::TextRange::new(start, end)
+}
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum WLanguage {}
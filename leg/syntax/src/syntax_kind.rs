@@ -1,4 +1,4 @@
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[repr(u16)]
 pub enum SyntaxKind {
     Root,
@@ -9,19 +9,25 @@ pub enum SyntaxKind {
     // Trivia
     Whitespace,
     Comment,
+    DocComment,
 
     // Tokens
     Comma,
     Colon,
+    ColonColon,
+    Question,
     Semicolon,
     True,
     False,
     Ident,
+    Underscore,
     Plus,
     Minus,
+    Arrow,
     Star,
     Slash,
     Equals,
+    FatArrow,
     EqualsEquals,
     BangEquals,
     Percent,
@@ -31,6 +37,7 @@ pub enum SyntaxKind {
     GreaterThanEqual,
     AmpersandAmpersand,
     BarBar,
+    Bar,
     Bang,
     LParen,
     RParen,
@@ -46,23 +53,60 @@ pub enum SyntaxKind {
     LetKeyword,
     StateKeyword,
     MutKeyword,
+    StructKeyword,
+    EnumKeyword,
+    MatchKeyword,
     EffectKeyword,
     OnMountKeyword,
     OnUpdateKeyword,
     OnDestroyKeyword,
 
     // Nodes
+    ArgList,
+    ArrayType,
+    AssignExpr,
     BlockStmt,
+    CallExpr,
+    ClosureExpr,
+    EnumDecl,
+    FieldDecl,
+    FnType,
     FunctionDecl,
+    IdentPat,
     InfixExpr,
+    MatchArm,
+    MatchArmList,
+    MatchExpr,
+    OptionalType,
+    Param,
     ParamList,
     ParenExpr,
+    PathExpr,
+    PathType,
     PrefixExpr,
+    RetType,
+    StateDef,
+    StructDecl,
+    StructExpr,
+    StructExprField,
+    TupleType,
     VariableDef,
     VariableRef,
+    VariantDecl,
+    VariantPat,
+    WildcardPat,
 
     // This variant is only used to guard against memory errors
     // when converting from and into a u16.
     #[doc(hidden)]
     __LAST,
 }
+
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Whitespace | SyntaxKind::Comment | SyntaxKind::DocComment
+        )
+    }
+}